@@ -12,7 +12,8 @@
 /// - Full path: Our complete implementation walking the commit tree
 use criterion::{criterion_group, criterion_main, Criterion};
 use git2::{Repository, Signature};
-use gitstats::analysis::analyze_repo_async;
+use gitstats::analysis::{analyze_repo_async, analyze_repo_with_options, list_contributors};
+use gitstats::types::{AnalysisOptions, Parallelism};
 use std::fs;
 use std::path::Path;
 use std::process::Command;
@@ -288,19 +289,18 @@ fn bench_analysis(c: &mut Criterion) {
         b.iter(|| get_git_contributor_stats(real_dir.path(), "HEAD"));
     });
 
+    // Fast path counts authors without computing any diffs
     group.bench_function("contributor_stats_ours_fast", |b| {
         b.iter(|| {
-            let result = rt.block_on(async {
-                analyze_repo_async(
+            let contributors = rt.block_on(async {
+                list_contributors(
                     real_dir.path().to_str().unwrap().to_string(),
                     "main".to_string(),
-                    "All".to_string(),
-                    None,
                 )
                 .await
                 .unwrap()
             });
-            assert!(!result.top_contributors.is_empty());
+            assert!(!contributors.is_empty());
         });
     });
 
@@ -365,6 +365,75 @@ fn bench_analysis(c: &mut Criterion) {
     group.finish();
 }
 
+/// Benchmark date-range filtered analysis
+/// Compares a full analysis of the real-world repository against one limited to the
+/// 30 days before its latest commit, to check that the date filter skips work rather
+/// than discarding results after diffing.
+///
+/// Tests the following operations:
+/// - analysis_full: Analysis of every commit
+/// - analysis_30_day_window: Analysis with `since`/`until` set to a 30-day window
+fn bench_filtered_analysis(c: &mut Criterion) {
+    let mut group = c.benchmark_group("filtered_analysis");
+    let rt = Runtime::new().unwrap();
+
+    let (real_dir, real_repo) = setup_real_world_repo();
+    let repo_path = real_dir.path().to_str().unwrap().to_string();
+
+    // Anchor the window to the latest commit so it always contains some history
+    let head_time = real_repo
+        .head()
+        .unwrap()
+        .peel_to_commit()
+        .unwrap()
+        .time()
+        .seconds();
+    let until = chrono::DateTime::from_timestamp(head_time, 0)
+        .unwrap()
+        .date_naive();
+    let windowed_options = AnalysisOptions {
+        since: Some(until - chrono::Duration::days(30)),
+        until: Some(until),
+        ..Default::default()
+    };
+
+    let analyze = |options: AnalysisOptions| {
+        rt.block_on(analyze_repo_with_options(
+            repo_path.clone(),
+            "main".to_string(),
+            "All".to_string(),
+            options,
+            None,
+        ))
+        .unwrap()
+    };
+
+    // Report how much work each variant actually does
+    let full_commits = analyze(AnalysisOptions::default()).commit_count;
+    let windowed_commits = analyze(windowed_options.clone()).commit_count;
+    println!(
+        "Commits processed: full = {}, 30-day window = {}",
+        full_commits, windowed_commits
+    );
+    assert!(windowed_commits < full_commits);
+
+    group.bench_function("analysis_full", |b| {
+        b.iter(|| {
+            let result = analyze(AnalysisOptions::default());
+            assert!(result.commit_count > 0);
+        });
+    });
+
+    group.bench_function("analysis_30_day_window", |b| {
+        b.iter(|| {
+            let result = analyze(windowed_options.clone());
+            assert!(result.commit_count <= full_commits);
+        });
+    });
+
+    group.finish();
+}
+
 /// Benchmark plot generation operations
 /// Tests the performance of different visualization types and options:
 ///
@@ -514,11 +583,78 @@ fn bench_caching(c: &mut Criterion) {
     group.finish();
 }
 
+/// Benchmark the tokio and rayon parallel processing paths against each other
+fn bench_parallelism(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parallelism");
+    let rt = Runtime::new().unwrap();
+
+    let (real_dir, _real_repo) = setup_real_world_repo();
+    let repo_path = real_dir.path().to_str().unwrap().to_string();
+
+    for (name, parallelism) in [
+        ("analysis_tokio", Parallelism::Tokio),
+        ("analysis_rayon", Parallelism::Rayon),
+    ] {
+        let options = AnalysisOptions {
+            parallelism,
+            ..Default::default()
+        };
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                let result = rt
+                    .block_on(analyze_repo_with_options(
+                        repo_path.clone(),
+                        "main".to_string(),
+                        "All".to_string(),
+                        options.clone(),
+                        None,
+                    ))
+                    .unwrap();
+                assert!(result.commit_count > 0);
+            });
+        });
+    }
+
+    group.finish();
+}
+
+/// Benchmark counting lines hunk by hunk against taking them from diff stats
+fn bench_fast_stats(c: &mut Criterion) {
+    let mut group = c.benchmark_group("diff_counting");
+    let rt = Runtime::new().unwrap();
+
+    let (real_dir, _real_repo) = setup_real_world_repo();
+    let repo_path = real_dir.path().to_str().unwrap().to_string();
+
+    for (name, fast_stats) in [("analysis_hunks", false), ("analysis_fast_stats", true)] {
+        let options = AnalysisOptions {
+            fast_stats,
+            ..Default::default()
+        };
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                let result = rt
+                    .block_on(analyze_repo_with_options(
+                        repo_path.clone(),
+                        "main".to_string(),
+                        "All".to_string(),
+                        options.clone(),
+                        None,
+                    ))
+                    .unwrap();
+                assert!(result.commit_count > 0);
+            });
+        });
+    }
+
+    group.finish();
+}
+
 criterion_group!(
     name = benches;
     config = Criterion::default()
         .sample_size(50)  // Keep 50 samples for statistical significance
         .measurement_time(std::time::Duration::from_secs(15)); // Increase time limit to 15 seconds
-    targets = bench_analysis, bench_plotting, bench_caching
+    targets = bench_analysis, bench_filtered_analysis, bench_parallelism, bench_fast_stats, bench_plotting, bench_caching
 );
 criterion_main!(benches);
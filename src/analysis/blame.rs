@@ -0,0 +1,76 @@
+/// Module for blame-based code ownership.
+/// Attributes the lines that survive in a branch's tip to the authors who last touched them.
+/// This is far more expensive than the commit walk, so it is kept separate and opt-in.
+use git2::{BlameOptions, Error, ObjectType, Oid, Repository, TreeWalkMode, TreeWalkResult};
+use std::collections::HashMap;
+use std::path::Path;
+use tokio::task::spawn_blocking;
+
+use super::git::branch_head;
+
+/// Count the lines each author currently owns on a branch
+///
+/// Every text file in the branch's tip tree is blamed, and each surviving line is
+/// credited to the author of the commit that last changed it. Binary files are skipped.
+/// `branch` is resolved with [`branch_head`] like the main analysis does, so tags and
+/// other revisions work and unknown branches fall back to HEAD.
+///
+/// # Returns
+/// * `HashMap<String, usize>` - Author name -> number of lines owned
+pub async fn blame_ownership(
+    path: String,
+    branch: String,
+) -> Result<HashMap<String, usize>, Error> {
+    spawn_blocking(move || -> Result<HashMap<String, usize>, Error> {
+        let repo = Repository::open(&path)?;
+        let tip = repo.find_commit(branch_head(&repo, &branch)?)?;
+
+        // Collect file paths first; blaming inside the walk callback can't propagate errors
+        let mut files = Vec::new();
+        tip.tree()?.walk(TreeWalkMode::PreOrder, |dir, entry| {
+            if entry.kind() == Some(ObjectType::Blob) {
+                if let Some(name) = entry.name() {
+                    files.push((format!("{}{}", dir, name), entry.id()));
+                }
+            }
+            TreeWalkResult::Ok
+        })?;
+
+        let mut ownership = HashMap::new();
+        for (file, blob_id) in files {
+            blame_file_into(&repo, tip.id(), &file, blob_id, &mut ownership)?;
+        }
+
+        Ok(ownership)
+    })
+    .await
+    .map_err(|e: tokio::task::JoinError| Error::from_str(&e.to_string()))?
+}
+
+/// Blame a single file at `commit` and add its line counts to `ownership`
+fn blame_file_into(
+    repo: &Repository,
+    commit: Oid,
+    file: &str,
+    blob_id: Oid,
+    ownership: &mut HashMap<String, usize>,
+) -> Result<(), Error> {
+    if repo.find_blob(blob_id)?.is_binary() {
+        return Ok(());
+    }
+
+    let mut opts = BlameOptions::new();
+    opts.newest_commit(commit);
+    let blame = repo.blame_file(Path::new(file), Some(&mut opts))?;
+
+    for hunk in blame.iter() {
+        let author = hunk
+            .final_signature()
+            .name()
+            .unwrap_or("Unknown")
+            .to_string();
+        *ownership.entry(author).or_insert(0) += hunk.lines_in_hunk();
+    }
+
+    Ok(())
+}
@@ -1,9 +1,30 @@
-use crate::types::{AnalysisResult, CacheKey};
+use crate::types::{add_delete_ratios, weekly_author_commits, AnalysisResult, CacheKey};
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
 use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// A cached result as written to disk by [`CacheManager::save_to_disk`]
+#[derive(Serialize, Deserialize)]
+struct DiskEntry {
+    key: CacheKey,
+    /// Commit the result was computed at, if recorded
+    head: Option<String>,
+    result: AnalysisResult,
+}
 
 /// Manages caching of analysis results
+#[derive(Clone)]
 pub struct CacheManager {
     cache: HashMap<CacheKey, AnalysisResult>,
+    /// Commit (hex ID) each result was computed at, for results stored with one
+    heads: HashMap<CacheKey, String>,
+    /// Lookups that found a result, counted for tuning
+    hits: Cell<usize>,
+    /// Lookups that found nothing
+    misses: Cell<usize>,
 }
 
 impl CacheManager {
@@ -11,22 +32,104 @@ impl CacheManager {
     pub fn new() -> Self {
         Self {
             cache: HashMap::new(),
+            heads: HashMap::new(),
+            hits: Cell::new(0),
+            misses: Cell::new(0),
         }
     }
 
     /// Store a result in the cache
     pub fn store(&mut self, key: CacheKey, result: AnalysisResult) {
+        self.heads.remove(&key);
+        self.cache.insert(key, result);
+    }
+
+    /// Store a result along with the commit (hex ID) its branch pointed at
+    pub fn store_at_head(&mut self, key: CacheKey, head: String, result: AnalysisResult) {
+        self.heads.insert(key.clone(), head);
         self.cache.insert(key, result);
     }
 
-    /// Retrieve a result from the cache
+    /// Commit the cached result for `key` was computed at, if it was stored with one
+    pub fn head(&self, key: &CacheKey) -> Option<&str> {
+        self.heads.get(key).map(String::as_str)
+    }
+
+    /// Retrieve a result from the cache, counting the lookup as a hit or a miss
     pub fn get(&self, key: &CacheKey) -> Option<&AnalysisResult> {
+        let result = self.cache.get(key);
+        let counter = if result.is_some() {
+            &self.hits
+        } else {
+            &self.misses
+        };
+        counter.set(counter.get() + 1);
+        result
+    }
+
+    /// Whether a result is cached for `key`, without counting a hit or a miss
+    pub fn contains(&self, key: &CacheKey) -> bool {
+        self.cache.contains_key(key)
+    }
+
+    /// Look at the result cached for `key`, without counting a hit or a miss
+    pub fn peek(&self, key: &CacheKey) -> Option<&AnalysisResult> {
         self.cache.get(key)
     }
 
+    /// Number of `(hits, misses)` recorded by [`CacheManager::get`]
+    pub fn stats(&self) -> (usize, usize) {
+        (self.hits.get(), self.misses.get())
+    }
+
+    /// Whether no results are cached
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+
     /// Clear the cache
+    ///
+    /// The hit and miss counters keep running, since they describe the whole session.
     pub fn clear(&mut self) {
         self.cache.clear();
+        self.heads.clear();
+    }
+
+    /// Write every cached result, with the commit it was computed at, to a JSON file
+    pub fn save_to_disk(&self, path: &Path) -> io::Result<()> {
+        let entries: Vec<DiskEntry> = self
+            .cache
+            .iter()
+            .map(|(key, result)| DiskEntry {
+                key: key.clone(),
+                head: self.heads.get(key).cloned(),
+                result: result.clone(),
+            })
+            .collect();
+        fs::write(path, serde_json::to_vec(&entries)?)
+    }
+
+    /// Load a cache written by [`CacheManager::save_to_disk`]
+    ///
+    /// Statistics that aren't serialized are rebuilt from each result's activity. The
+    /// hit and miss counters start from zero.
+    pub fn load_from_disk(path: &Path) -> io::Result<Self> {
+        let entries: Vec<DiskEntry> = serde_json::from_slice(&fs::read(path)?)?;
+        let mut cache = Self::new();
+        for DiskEntry {
+            key,
+            head,
+            mut result,
+        } in entries
+        {
+            result.contributor_add_delete_ratio = add_delete_ratios(&result.commit_activity);
+            result.weekly_commits_by_author = weekly_author_commits(&result.commit_activity);
+            match head {
+                Some(head) => cache.store_at_head(key, head, result),
+                None => cache.store(key, result),
+            }
+        }
+        Ok(cache)
     }
 }
 
@@ -35,3 +138,52 @@ impl Default for CacheManager {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::CommitActivity;
+
+    #[test]
+    fn test_hit_miss_stats() {
+        let mut cache = CacheManager::new();
+        let key = CacheKey {
+            branch: "main".to_string(),
+            contributor: "All".to_string(),
+        };
+
+        assert!(cache.get(&key).is_none());
+        cache.store(key.clone(), AnalysisResult::default());
+        assert!(cache.get(&key).is_some());
+
+        assert_eq!(cache.stats(), (1, 1));
+        assert!(cache.contains(&key));
+        assert_eq!(cache.stats(), (1, 1));
+    }
+
+    #[test]
+    fn test_disk_round_trip() {
+        let mut cache = CacheManager::new();
+        let key = CacheKey {
+            branch: "main".to_string(),
+            contributor: "All".to_string(),
+        };
+        let result = AnalysisResult {
+            commit_count: 1,
+            commit_activity: vec![CommitActivity::new("2023-01-01", 3, 0, "Alice")],
+            ..Default::default()
+        };
+        cache.store_at_head(key.clone(), "abc123".to_string(), result);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache.json");
+        cache.save_to_disk(&path).unwrap();
+        let loaded = CacheManager::load_from_disk(&path).unwrap();
+
+        assert_eq!(loaded.head(&key), Some("abc123"));
+        let result = loaded.get(&key).unwrap();
+        assert_eq!(result.commit_count, 1);
+        assert_eq!(result.commit_activity.len(), 1);
+        assert_eq!(result.contributor_add_delete_ratio["Alice"], f64::INFINITY);
+    }
+}
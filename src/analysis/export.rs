@@ -0,0 +1,53 @@
+/// Module for exporting analysis results to other data tools.
+/// Only built with the `dataframe` feature.
+use polars::prelude::*;
+
+use crate::types::AnalysisResult;
+
+/// Convert a result's commit activity into a Polars [`DataFrame`]
+///
+/// Each row is one commit, in the order of [`AnalysisResult::commit_activity`], with
+/// `date`, `added`, `deleted` and `author` columns.
+pub fn to_dataframe(result: &AnalysisResult) -> DataFrame {
+    let activity = &result.commit_activity;
+    let dates: Vec<&str> = activity.iter().map(|a| a.date.as_str()).collect();
+    let added: Vec<u64> = activity.iter().map(|a| a.lines_added as u64).collect();
+    let deleted: Vec<u64> = activity.iter().map(|a| a.lines_deleted as u64).collect();
+    let authors: Vec<&str> = activity.iter().map(|a| a.author.as_str()).collect();
+
+    polars::df!(
+        "date" => dates,
+        "added" => added,
+        "deleted" => deleted,
+        "author" => authors,
+    )
+    .expect("every column has one value per commit")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::CommitActivity;
+
+    #[test]
+    fn test_to_dataframe() {
+        let result = AnalysisResult {
+            commit_activity: vec![
+                CommitActivity::new("2023-01-01", 10, 5, "Alice"),
+                CommitActivity::new("2023-01-02", 15, 8, "Bob"),
+                CommitActivity::new("2023-01-03", 20, 10, "Alice"),
+            ],
+            ..Default::default()
+        };
+
+        let df = to_dataframe(&result);
+
+        assert_eq!(df.width(), 4);
+        assert_eq!(df.height(), 3);
+        assert_eq!(
+            df.get_column_names(),
+            vec!["date", "added", "deleted", "author"]
+        );
+        assert_eq!(df.column("added").unwrap().u64().unwrap().sum(), Some(45));
+    }
+}
@@ -1,55 +1,685 @@
 /// Module for Git repository analysis and statistics collection.
 /// Provides async functions for analyzing repositories, handling branches, and processing commits.
-use crate::types::{AnalysisResult, ProgressEstimate};
-use chrono::{DateTime, Utc};
-use git2::{Error, Oid, Repository};
-use std::collections::HashMap;
+use crate::types::{
+    add_delete_ratios, author_commit_frequency, commit_size_histogram, commit_type_counts,
+    extension_line_counts, file_hotspots, monthly_commit_frequency, primary_language, revert_count,
+    sort_activity, weekly_author_commits, yearly_commit_counts, AccuracyMode, AnalysisOptions,
+    AnalysisResult, CommitActivity, Parallelism, PartialStats, ProgressEstimate, RefKind,
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use git2::{
+    Commit, Diff, DiffDelta, DiffFindOptions, DiffOptions, Error, Odb, Oid, Repository, Tree,
+};
+use rayon::prelude::*;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 use tokio::task::spawn_blocking;
 
 /// Tuple containing commit statistics (count, lines added, lines deleted)
 type CommitData = (usize, usize, usize);
-/// Vector of activity data entries (date, lines added, lines deleted)
-type ActivityData = Vec<(String, usize, usize)>;
+/// Vector of per-commit activity entries
+type ActivityData = Vec<CommitActivity>;
 /// Map of contributor names to their commit counts
 type ContributorData = HashMap<String, usize>;
-/// Combined tuple of all process results
-type ProcessResult = (CommitData, ActivityData, ContributorData);
+/// Map of contributor names to the date of their earliest commit
+type FirstCommitData = HashMap<String, String>;
+/// Map of contributor names to the number of commits made under each of their emails
+type EmailData = HashMap<String, HashMap<String, usize>>;
 /// Result type for chunk processing operations
-type ChunkResult = Result<ProcessResult, Error>;
+type ChunkResult = Result<ChunkStats, Error>;
+
+/// Statistics gathered from a chunk of commits
+///
+/// Each parallel task produces one of these, and they are folded together
+/// with [`ChunkStats::merge`] once all chunks have completed.
+#[derive(Debug, Default)]
+struct ChunkStats {
+    /// Commit count, lines added and lines deleted
+    totals: CommitData,
+    /// Per-commit activity entries
+    activity: ActivityData,
+    /// Commit counts per author
+    author_commit_count: ContributorData,
+    /// Earliest commit date per author
+    author_first_commit: FirstCommitData,
+    /// Commit counts per author and email
+    author_emails: EmailData,
+    /// Commits left out because their tree or diff couldn't be read
+    skipped_commits: usize,
+    /// Files over the size limit whose lines weren't counted
+    large_files_skipped: usize,
+    /// Commits that change nothing, whether or not they were left out
+    empty_commits: usize,
+    /// Counted commits per number of parents
+    parent_counts: HashMap<usize, usize>,
+}
+
+impl ChunkStats {
+    /// Fold the statistics of another chunk into this one
+    fn merge(&mut self, other: ChunkStats) {
+        self.totals.0 += other.totals.0;
+        self.totals.1 += other.totals.1;
+        self.totals.2 += other.totals.2;
+        self.activity.extend(other.activity);
+        self.skipped_commits += other.skipped_commits;
+        self.large_files_skipped += other.large_files_skipped;
+        self.empty_commits += other.empty_commits;
+        for (parents, count) in other.parent_counts {
+            *self.parent_counts.entry(parents).or_insert(0) += count;
+        }
+        for (author, count) in other.author_commit_count {
+            *self.author_commit_count.entry(author).or_insert(0) += count;
+        }
+        for (author, date) in other.author_first_commit {
+            record_first_commit(&mut self.author_first_commit, author, date);
+        }
+        for (author, emails) in other.author_emails {
+            let known = self.author_emails.entry(author).or_default();
+            for (email, count) in emails {
+                *known.entry(email).or_insert(0) += count;
+            }
+        }
+    }
+
+    /// Merge authors whose names differ only in case or whitespace
+    ///
+    /// Each group of variants is renamed to its most common spelling (ties broken
+    /// alphabetically) across counts, first-commit dates, emails and activity.
+    fn merge_similar_authors(&mut self) {
+        let mut groups: HashMap<String, Vec<(&String, usize)>> = HashMap::new();
+        for (author, count) in &self.author_commit_count {
+            groups
+                .entry(normalize_author_name(author))
+                .or_default()
+                .push((author, *count));
+        }
+
+        let mut canonical: HashMap<String, String> = HashMap::new();
+        for variants in groups.values().filter(|variants| variants.len() > 1) {
+            let (primary, _) = variants
+                .iter()
+                .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+                .copied()
+                .unwrap();
+            for (variant, _) in variants {
+                if *variant != primary {
+                    canonical.insert(variant.to_string(), primary.clone());
+                }
+            }
+        }
+        if canonical.is_empty() {
+            return;
+        }
+
+        let rename = |author: String| canonical.get(&author).cloned().unwrap_or(author);
+        let mut merged = ChunkStats {
+            totals: self.totals,
+            activity: std::mem::take(&mut self.activity),
+            skipped_commits: self.skipped_commits,
+            large_files_skipped: self.large_files_skipped,
+            empty_commits: self.empty_commits,
+            parent_counts: std::mem::take(&mut self.parent_counts),
+            ..Default::default()
+        };
+        for activity in &mut merged.activity {
+            if let Some(primary) = canonical.get(&activity.author) {
+                activity.author = primary.clone();
+            }
+        }
+        for (author, count) in std::mem::take(&mut self.author_commit_count) {
+            *merged
+                .author_commit_count
+                .entry(rename(author))
+                .or_insert(0) += count;
+        }
+        for (author, date) in std::mem::take(&mut self.author_first_commit) {
+            record_first_commit(&mut merged.author_first_commit, rename(author), date);
+        }
+        for (author, emails) in std::mem::take(&mut self.author_emails) {
+            let known = merged.author_emails.entry(rename(author)).or_default();
+            for (email, count) in emails {
+                *known.entry(email).or_insert(0) += count;
+            }
+        }
+        *self = merged;
+    }
+}
+
+impl ChunkStats {
+    /// Replace author names with `Contributor N` pseudonyms, numbered from 1 in
+    /// descending commit count with ties broken alphabetically, and drop the emails
+    fn anonymize_authors(&mut self) {
+        let mut ranked: Vec<(&String, usize)> = self
+            .author_commit_count
+            .iter()
+            .map(|(author, count)| (author, *count))
+            .collect();
+        ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        let pseudonyms: HashMap<String, String> = ranked
+            .into_iter()
+            .enumerate()
+            .map(|(rank, (author, _))| (author.clone(), format!("Contributor {}", rank + 1)))
+            .collect();
+
+        // Every author with activity or a first commit also has a commit count
+        let rename = |author: &str| pseudonyms[author].clone();
+        for activity in &mut self.activity {
+            activity.author = rename(&activity.author);
+        }
+        self.author_commit_count = std::mem::take(&mut self.author_commit_count)
+            .into_iter()
+            .map(|(author, count)| (rename(&author), count))
+            .collect();
+        self.author_first_commit = std::mem::take(&mut self.author_first_commit)
+            .into_iter()
+            .map(|(author, date)| (rename(&author), date))
+            .collect();
+        self.author_emails.clear();
+    }
+}
+
+/// Normalize an author name for fuzzy matching: lowercase with single spaces
+fn normalize_author_name(name: &str) -> String {
+    name.split_whitespace()
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Record `date` as the author's first commit if it is earlier than the one already known
+///
+/// Dates are `YYYY-MM-DD` strings, so lexical order is chronological order.
+fn record_first_commit(first_commits: &mut FirstCommitData, author: String, date: String) {
+    match first_commits.get_mut(&author) {
+        Some(existing) if date < *existing => *existing = date,
+        Some(_) => {}
+        None => {
+            first_commits.insert(author, date);
+        }
+    }
+}
+
+/// Pick each author's most frequently used email, breaking ties alphabetically
+fn primary_emails(author_emails: EmailData) -> HashMap<String, String> {
+    author_emails
+        .into_iter()
+        .filter_map(|(author, emails)| {
+            emails
+                .into_iter()
+                .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(&a.0)))
+                .map(|(email, _)| (author, email))
+        })
+        .collect()
+}
+
+/// Check whether an author matches any of the configured exclusion patterns
+fn is_excluded_author(name: &str, email: &str, exclude_authors: &[String]) -> bool {
+    let name = name.to_lowercase();
+    let email = email.to_lowercase();
+    exclude_authors.iter().any(|pattern| {
+        let pattern = pattern.to_lowercase();
+        name.contains(&pattern) || email.contains(&pattern)
+    })
+}
+
+/// Check whether a commit timestamp falls within an inclusive `since..=until` date range
+fn in_date_range(time: i64, since: Option<NaiveDate>, until: Option<NaiveDate>) -> bool {
+    match DateTime::<Utc>::from_timestamp(time, 0).map(|dt| dt.date_naive()) {
+        Some(date) => !(since.is_some_and(|s| date < s) || until.is_some_and(|u| date > u)),
+        None => false,
+    }
+}
+
+/// Parse `Co-authored-by: Name <email>` trailers from a commit message
+///
+/// Returns `(name, email)` pairs; the trailer key is matched case-insensitively.
+fn parse_coauthors(message: &str) -> Vec<(String, String)> {
+    const TRAILER: &str = "co-authored-by:";
+
+    message
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            let key = line.get(..TRAILER.len())?;
+            if !key.eq_ignore_ascii_case(TRAILER) {
+                return None;
+            }
+            let value = line[TRAILER.len()..].trim();
+            let (name, email) = match value.split_once('<') {
+                Some((name, email)) => (name.trim(), email.trim_end_matches('>').trim()),
+                None => (value, ""),
+            };
+            (!name.is_empty()).then(|| (name.to_string(), email.to_string()))
+        })
+        .collect()
+}
+
+/// Parse the conventional-commit type from a commit message, e.g. `feat` from
+/// `feat(ui): add slider` or `fix!: handle empty repos`
+///
+/// Returns `None` when the first line has no `type:` prefix.
+fn parse_commit_type(message: &str) -> Option<String> {
+    let first_line = message.lines().next()?;
+    let (prefix, _) = first_line.split_once(':')?;
+    let prefix = prefix.trim_end_matches('!');
+    let commit_type = match prefix.split_once('(') {
+        Some((commit_type, scope)) if scope.ends_with(')') => commit_type,
+        Some(_) => return None,
+        None => prefix,
+    };
+
+    if commit_type.is_empty() || !commit_type.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    Some(commit_type.to_ascii_lowercase())
+}
+
+/// Check whether a commit message is that of a revert, either by the `Revert "..."`
+/// subject `git revert` writes or by its `This reverts commit <sha>.` line
+fn is_revert_message(message: &str) -> bool {
+    if message.starts_with("Revert \"") {
+        return true;
+    }
+    message.lines().any(|line| {
+        line.trim()
+            .strip_prefix("This reverts commit ")
+            .and_then(|rest| rest.split(|c: char| !c.is_ascii_hexdigit()).next())
+            .is_some_and(|sha| sha.len() >= 7)
+    })
+}
+
+/// Check whether a path lies under one of the excluded paths
+///
+/// An excluded path matches as a whole sequence of path components anywhere in the
+/// file's path, so `vendor` matches `vendor/lib.rs` and `web/vendor/app.js` but not
+/// `vendored.rs`.
+fn is_excluded_path(path: &Path, exclude_paths: &[String]) -> bool {
+    let components: Vec<_> = path.components().collect();
+    exclude_paths.iter().any(|pattern| {
+        let pattern: Vec<_> = Path::new(pattern.trim_matches('/')).components().collect();
+        !pattern.is_empty()
+            && components
+                .windows(pattern.len())
+                .any(|window| window == pattern)
+    })
+}
+
+/// Check whether a diff delta belongs to an excluded path
+fn is_excluded_delta(delta: &DiffDelta, exclude_paths: &[String]) -> bool {
+    !exclude_paths.is_empty()
+        && delta
+            .new_file()
+            .path()
+            .or_else(|| delta.old_file().path())
+            .is_some_and(|path| is_excluded_path(path, exclude_paths))
+}
+
+/// Path of the file a diff delta touches, the new one for renames
+fn delta_path(delta: &DiffDelta) -> Option<String> {
+    delta
+        .new_file()
+        .path()
+        .or_else(|| delta.old_file().path())
+        .map(|path| path.to_string_lossy().into_owned())
+}
+
+/// Extension of the file a diff delta touches, lowercased, if it has one
+fn delta_extension(delta: &DiffDelta) -> Option<String> {
+    delta
+        .new_file()
+        .path()
+        .or_else(|| delta.old_file().path())
+        .and_then(|path| path.extension())
+        .and_then(|extension| extension.to_str())
+        .map(|extension| extension.to_lowercase())
+}
+
+/// Add a delta's added and deleted lines to its extension's counts
+fn record_extension_lines(
+    by_extension: &mut BTreeMap<String, (usize, usize)>,
+    delta: &DiffDelta,
+    added: usize,
+    deleted: usize,
+) {
+    if let Some(extension) = delta_extension(delta) {
+        let counts = by_extension.entry(extension).or_insert((0, 0));
+        counts.0 += added;
+        counts.1 += deleted;
+    }
+}
+
+/// Lines and files changed by a single diff
+#[derive(Debug, Default)]
+struct DiffCounts {
+    lines_added: usize,
+    lines_deleted: usize,
+    files_changed: usize,
+    /// Lines added and deleted per file extension; files without one aren't counted
+    lines_by_extension: BTreeMap<String, (usize, usize)>,
+    /// Whether any changed file is code rather than docs or config, following
+    /// `options.non_code_extensions`
+    touches_code: bool,
+    /// Paths of the changed files, after any rename; empty for stat-only diffs
+    paths: Vec<String>,
+    /// Renamed files as `(old path, new path)`, with `options.follow_renames`
+    renames: Vec<(String, String)>,
+    /// Files over `options.max_file_size_bytes`, whose lines weren't counted
+    large_files: usize,
+}
+
+/// Whether either version of a delta's file is larger than `limit` bytes
+///
+/// Sizes come from the object headers, so the blobs aren't loaded.
+fn exceeds_size_limit(odb: &Odb, delta: &DiffDelta, limit: u64) -> bool {
+    [delta.old_file().id(), delta.new_file().id()]
+        .into_iter()
+        .filter(|id| !id.is_zero())
+        .any(|id| {
+            odb.read_header(id)
+                .is_ok_and(|(size, _)| size as u64 > limit)
+        })
+}
+
+/// Whether a diff delta changes a code file, i.e. one whose extension isn't listed in
+/// `non_code_extensions`. Files without an extension count as code.
+fn is_code_delta(delta: &DiffDelta, non_code_extensions: &[String]) -> bool {
+    delta_extension(delta).is_none_or(|extension| {
+        !non_code_extensions
+            .iter()
+            .any(|non_code| non_code.eq_ignore_ascii_case(&extension))
+    })
+}
+
+/// Lines added, lines deleted and files changed in a diff, leaving out files under
+/// `exclude_paths`
+fn excluded_stats(diff: &Diff, exclude_paths: &[String]) -> Result<(usize, usize, usize), Error> {
+    let mut totals = (0, 0, 0);
+    for (idx, delta) in diff.deltas().enumerate() {
+        if is_excluded_delta(&delta, exclude_paths) {
+            continue;
+        }
+        totals.2 += 1;
+        if let Some(patch) = git2::Patch::from_diff(diff, idx)? {
+            let (_, added, deleted) = patch.line_stats()?;
+            totals.0 += added;
+            totals.1 += deleted;
+        }
+    }
+    Ok(totals)
+}
+
+/// Count the lines added and deleted in a diff
+///
+/// `Fast` mode sums hunk header line counts, which is exact as long as the diff was
+/// generated without context lines. `GitNumstat` mode detects renames first, like
+/// `git log` does by default, then counts individual `+`/`-` lines. Files under
+/// `options.exclude_paths` are skipped in both modes; libgit2 doesn't support git's
+/// `:(exclude)` pathspec magic, so they can't be filtered out by the diff itself.
+///
+/// With `options.fast_stats`, and for diffs touching more files than
+/// `options.stat_only_threshold`, both modes are skipped and the totals come from
+/// [`Diff::stats`] instead, which has no per-file breakdown, so their lines aren't
+/// attributed to any extension. When paths are excluded, the totals are summed from
+/// each remaining file's patch instead.
+///
+/// Files over `options.max_file_size_bytes` are treated as binary by the diff (see
+/// [`diff_options`]), so no lines are counted for them in any mode; they are only
+/// tallied here.
+fn count_diff_lines(
+    repo: &Repository,
+    diff: &mut Diff,
+    options: &AnalysisOptions,
+) -> Result<DiffCounts, Error> {
+    let exclude_paths = &options.exclude_paths;
+    let touches_code = diff.deltas().any(|delta| {
+        !is_excluded_delta(&delta, exclude_paths)
+            && is_code_delta(&delta, &options.non_code_extensions)
+    });
+    let large_files = match options.max_file_size_bytes {
+        Some(limit) => {
+            let odb = repo.odb()?;
+            diff.deltas()
+                .filter(|delta| {
+                    !is_excluded_delta(delta, exclude_paths)
+                        && exceeds_size_limit(&odb, delta, limit)
+                })
+                .count()
+        }
+        None => 0,
+    };
+
+    if options.fast_stats
+        || options
+            .stat_only_threshold
+            .is_some_and(|threshold| diff.deltas().len() > threshold)
+    {
+        let (lines_added, lines_deleted, files_changed) = if exclude_paths.is_empty() {
+            let stats = diff.stats()?;
+            (stats.insertions(), stats.deletions(), stats.files_changed())
+        } else {
+            // The overall stats include excluded files, so sum the remaining files' own
+            excluded_stats(diff, exclude_paths)?
+        };
+        return Ok(DiffCounts {
+            lines_added,
+            lines_deleted,
+            files_changed,
+            touches_code,
+            large_files,
+            ..Default::default()
+        });
+    }
+
+    let mut lines_added = 0_usize;
+    let mut lines_deleted = 0_usize;
+    let mut lines_by_extension = BTreeMap::new();
+
+    match options.accuracy {
+        AccuracyMode::Fast => {
+            if options.follow_renames {
+                diff.find_similar(Some(DiffFindOptions::new().renames(true)))?;
+            }
+            diff.foreach(
+                &mut |delta, _progress| {
+                    // Skip binary files (git shows "-" for these)
+                    if delta.flags().contains(git2::DiffFlags::BINARY) {
+                        return true;
+                    }
+                    true
+                },
+                None,
+                Some(&mut |delta, hunk| {
+                    if is_excluded_delta(&delta, exclude_paths) {
+                        return true;
+                    }
+                    // Count actual line changes
+                    let (added, deleted) = (hunk.new_lines() as usize, hunk.old_lines() as usize);
+                    lines_added += added;
+                    lines_deleted += deleted;
+                    record_extension_lines(&mut lines_by_extension, &delta, added, deleted);
+                    true
+                }),
+                None,
+            )?;
+        }
+        AccuracyMode::GitNumstat => {
+            diff.find_similar(Some(DiffFindOptions::new().renames(true)))?;
+            diff.foreach(
+                &mut |_delta, _progress| true,
+                None,
+                None,
+                Some(&mut |delta, _hunk, line| {
+                    if is_excluded_delta(&delta, exclude_paths) {
+                        return true;
+                    }
+                    match line.origin() {
+                        '+' => {
+                            lines_added += 1;
+                            record_extension_lines(&mut lines_by_extension, &delta, 1, 0);
+                        }
+                        '-' => {
+                            lines_deleted += 1;
+                            record_extension_lines(&mut lines_by_extension, &delta, 0, 1);
+                        }
+                        _ => {}
+                    }
+                    true
+                }),
+            )?;
+        }
+    }
+
+    // Listed after rename detection, so a renamed file counts once
+    let mut paths = Vec::new();
+    let mut renames = Vec::new();
+    for delta in diff
+        .deltas()
+        .filter(|delta| !is_excluded_delta(delta, exclude_paths))
+    {
+        let Some(path) = delta_path(&delta) else {
+            continue;
+        };
+        if options.follow_renames && delta.status() == git2::Delta::Renamed {
+            if let Some(old) = delta.old_file().path() {
+                renames.push((old.to_string_lossy().into_owned(), path.clone()));
+            }
+        }
+        paths.push(path);
+    }
+
+    Ok(DiffCounts {
+        lines_added,
+        lines_deleted,
+        files_changed: paths.len(),
+        lines_by_extension,
+        touches_code,
+        paths,
+        renames,
+        large_files,
+    })
+}
+
+/// Diff settings used for every commit, following `options`
+fn diff_options(options: &AnalysisOptions) -> DiffOptions {
+    // Numstat mode mirrors git's defaults: Myers diff, whitespace counted
+    let numstat = options.accuracy == AccuracyMode::GitNumstat;
+
+    let mut diff_opts = DiffOptions::new();
+    diff_opts
+        .include_untracked(false)
+        .ignore_whitespace(options.ignore_whitespace && !numstat)
+        .ignore_whitespace_eol(options.normalize_line_endings)
+        .context_lines(0)
+        .ignore_filemode(true)
+        .ignore_submodules(true)
+        .minimal(!numstat)
+        .patience(!numstat)
+        .indent_heuristic(true); // Use indent heuristic like Git
+    if let Some(scope) = &options.scope_path {
+        diff_opts.pathspec(scope);
+    }
+    // libgit2 treats blobs over this size as binary and never loads their hunks
+    if let Some(limit) = options.max_file_size_bytes {
+        diff_opts.max_size(i64::try_from(limit).unwrap_or(i64::MAX));
+    }
+    diff_opts
+}
 
 /// Process a chunk of commits to gather statistics
-fn process_commit_chunk(repo: &Repository, chunk: &[Oid], contributor: &str) -> ChunkResult {
+fn process_commit_chunk(
+    repo: &Repository,
+    chunk: &[Oid],
+    contributor: &str,
+    options: &AnalysisOptions,
+) -> ChunkResult {
     let mut commit_count = 0;
     let mut total_lines_added = 0;
     let mut total_lines_deleted = 0;
     let mut author_commit_count = HashMap::new();
+    let mut author_first_commit = HashMap::new();
+    let mut author_emails: EmailData = HashMap::new();
     let mut commit_activity = Vec::with_capacity(chunk.len());
+    let mut skipped_commits = 0;
+    let mut empty_commits = 0;
+    let mut parent_counts = HashMap::new();
+    let mut large_files_skipped = 0;
 
     // Pre-allocate a diff options object to reuse
-    let mut diff_opts = git2::DiffOptions::new();
-    diff_opts
-        .include_untracked(false)
-        .ignore_whitespace(true)
-        .context_lines(0)
-        .ignore_filemode(true)
-        .ignore_submodules(true)
-        .minimal(true) // Use minimal diff like Git
-        .patience(true) // Use patience diff algorithm like Git
-        .indent_heuristic(true); // Use indent heuristic like Git
+    let mut diff_opts = diff_options(options);
+    let scope = options
+        .scope_path
+        .as_deref()
+        .map(|scope| Path::new(scope.trim_matches('/')));
 
     for &oid in chunk {
         let commit = repo.find_commit(oid)?;
-        let author = commit.author().name().unwrap_or("Unknown").to_string();
+        let signature = commit.author();
+        let author = signature.name().unwrap_or("Unknown").to_string();
 
         if contributor != "All" && author != contributor {
             continue;
         }
 
+        if !options.exclude_authors.is_empty()
+            && is_excluded_author(
+                &author,
+                signature.email().unwrap_or(""),
+                &options.exclude_authors,
+            )
+        {
+            continue;
+        }
+
+        if scope.is_some_and(|scope| !touches_scope(&commit, scope)) {
+            continue;
+        }
+
+        // Skipped root commits are never diffed, since their lines aren't reported, so
+        // they're assumed to touch code
+        let skip_lines = options.skip_initial_commit && commit.parent_count() == 0;
+        let line_counts = if skip_lines {
+            Some(DiffCounts {
+                touches_code: true,
+                ..Default::default()
+            })
+        } else {
+            commit_line_counts(repo, &commit, &mut diff_opts, options)
+        };
+
+        // A commit whose objects can't be read would show up as a misleading zero-line
+        // entry, so it's counted separately instead
+        let Some(DiffCounts {
+            lines_added: commit_lines_added,
+            lines_deleted: commit_lines_deleted,
+            lines_by_extension,
+            touches_code,
+            paths: files,
+            renames,
+            large_files,
+            ..
+        }) = line_counts
+        else {
+            skipped_commits += 1;
+            continue;
+        };
+
+        let is_empty = commit_lines_added + commit_lines_deleted == 0 && is_empty_commit(&commit);
+        if is_empty {
+            empty_commits += 1;
+            if options.exclude_empty_commits {
+                continue;
+            }
+        }
+        if options.code_only && !touches_code {
+            continue;
+        }
+
         commit_count += 1;
-        *author_commit_count.entry(author).or_insert(0) += 1;
+        *parent_counts.entry(commit.parent_count()).or_insert(0) += 1;
+        large_files_skipped += large_files;
 
         // Use safe timestamp conversion
         let time = commit.time().seconds();
@@ -57,105 +687,349 @@ fn process_commit_chunk(repo: &Repository, chunk: &[Oid], contributor: &str) ->
             .map(|dt| dt.naive_utc().date().to_string())
             .unwrap_or_else(|| "Unknown".to_string());
 
-        let mut commit_lines_added = 0_usize;
-        let mut commit_lines_deleted = 0_usize;
-
-        // Calculate diff stats for the commit
-        if let Ok(tree) = commit.tree() {
-            let parent_count = commit.parent_count();
-
-            // For non-merge commits or initial commits
-            if parent_count <= 1 {
-                let parent_tree = if parent_count == 1 {
-                    commit.parent(0).ok().and_then(|p| p.tree().ok())
-                } else {
-                    None
-                };
+        record_first_commit(&mut author_first_commit, author.clone(), date.clone());
+        if let Some(email) = signature.email().filter(|email| !email.is_empty()) {
+            *author_emails
+                .entry(author.clone())
+                .or_default()
+                .entry(email.to_string())
+                .or_insert(0) += 1;
+        }
+        *author_commit_count.entry(author.clone()).or_insert(0) += 1;
 
-                if let Ok(diff) =
-                    repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), Some(&mut diff_opts))
+        if options.count_coauthors {
+            let mut credited = vec![author.clone()];
+            for (name, email) in commit.message().map(parse_coauthors).unwrap_or_default() {
+                if credited.contains(&name)
+                    || is_excluded_author(&name, &email, &options.exclude_authors)
                 {
-                    // Process each file delta to match git's numstat behavior
-                    diff.foreach(
-                        &mut |delta, _progress| {
-                            // Skip binary files (git shows "-" for these)
-                            if delta.flags().contains(git2::DiffFlags::BINARY) {
-                                return true;
-                            }
-                            true
-                        },
-                        None,
-                        Some(&mut |_delta, hunk| {
-                            // Count actual line changes
-                            commit_lines_added += hunk.new_lines() as usize;
-                            commit_lines_deleted += hunk.old_lines() as usize;
-                            true
-                        }),
-                        None,
-                    )?;
-                }
-            } else {
-                // For merge commits, compare with each parent and take the maximum
-                let mut max_added = 0_usize;
-                let mut max_deleted = 0_usize;
-
-                for i in 0..parent_count {
-                    let mut parent_added = 0_usize;
-                    let mut parent_deleted = 0_usize;
-
-                    if let Ok(parent) = commit.parent(i) {
-                        if let Ok(parent_tree) = parent.tree() {
-                            if let Ok(diff) = repo.diff_tree_to_tree(
-                                Some(&parent_tree),
-                                Some(&tree),
-                                Some(&mut diff_opts),
-                            ) {
-                                diff.foreach(
-                                    &mut |delta, _progress| {
-                                        if delta.flags().contains(git2::DiffFlags::BINARY) {
-                                            return true;
-                                        }
-                                        true
-                                    },
-                                    None,
-                                    Some(&mut |_delta, hunk| {
-                                        parent_added += hunk.new_lines() as usize;
-                                        parent_deleted += hunk.old_lines() as usize;
-                                        true
-                                    }),
-                                    None,
-                                )?;
-                            }
-                        }
-                    }
-
-                    max_added = max_added.max(parent_added);
-                    max_deleted = max_deleted.max(parent_deleted);
+                    continue;
                 }
-
-                commit_lines_added = max_added;
-                commit_lines_deleted = max_deleted;
+                *author_commit_count.entry(name.clone()).or_insert(0) += 1;
+                credited.push(name);
             }
         }
 
+        if skip_lines {
+            continue;
+        }
+
         total_lines_added += commit_lines_added;
         total_lines_deleted += commit_lines_deleted;
-        commit_activity.push((date, commit_lines_added, commit_lines_deleted));
+        if !options.collect_activity {
+            continue;
+        }
+        commit_activity.push(CommitActivity {
+            date,
+            timestamp: time,
+            oid: oid.to_string(),
+            lines_added: commit_lines_added,
+            lines_deleted: commit_lines_deleted,
+            author,
+            commit_type: commit.message().and_then(parse_commit_type),
+            is_revert: commit.message().is_some_and(is_revert_message),
+            is_empty,
+            lines_by_extension,
+            files,
+            renames,
+        });
     }
 
-    Ok((
-        (commit_count, total_lines_added, total_lines_deleted),
-        commit_activity,
+    Ok(ChunkStats {
+        totals: (commit_count, total_lines_added, total_lines_deleted),
+        activity: commit_activity,
         author_commit_count,
-    ))
+        author_first_commit,
+        author_emails,
+        skipped_commits,
+        large_files_skipped,
+        empty_commits,
+        parent_counts,
+    })
+}
+
+/// Count the lines added and deleted and the files changed by a single commit
+///
+/// Uses the same diff settings as an analysis run with `options`, so the numbers match
+/// the commit's activity entry; in `GitNumstat` mode they match the sums of
+/// `git show --numstat --diff-merges=first-parent`. Fails when the commit's trees or
+/// diff can't be read.
+pub fn commit_diff_stats(
+    repo: &Repository,
+    commit: &Commit,
+    options: &AnalysisOptions,
+) -> Result<(usize, usize, usize), Error> {
+    commit_line_counts(repo, commit, &mut diff_options(options), options)
+        .map(|counts| {
+            (
+                counts.lines_added,
+                counts.lines_deleted,
+                counts.files_changed,
+            )
+        })
+        .ok_or_else(|| {
+            Error::from_str(&format!("Couldn't read the diff of commit {}", commit.id()))
+        })
+}
+
+/// Count the uncommitted changes in a repository's working tree, staged or not
+///
+/// Returns `(files_changed, lines_added, lines_deleted)` against HEAD, like
+/// `git diff HEAD --shortstat`; untracked files aren't counted. In a repository
+/// without commits, everything staged or tracked counts as added.
+pub fn working_tree_stats(path: &str) -> Result<(usize, usize, usize), Error> {
+    let repo = open_repository(path)?;
+    let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
+    let index = repo.index()?;
+
+    let mut diff = repo.diff_tree_to_index(head_tree.as_ref(), Some(&index), None)?;
+    diff.merge(&repo.diff_index_to_workdir(Some(&index), None)?)?;
+    let stats = diff.stats()?;
+    Ok((stats.files_changed(), stats.insertions(), stats.deletions()))
+}
+
+/// Count the lines added and deleted and the files changed by a commit
+///
+/// Merge commits are compared with each parent and the largest change is kept, except
+/// in numstat mode, where git diffs against the first parent only. The per-extension
+/// breakdown and changed files of a merge come from the parent with the most changed
+/// lines. Returns `None` when the commit's tree, a parent's tree or the diff can't be
+/// read, e.g. because an object is missing from a corrupt repository.
+fn commit_line_counts(
+    repo: &Repository,
+    commit: &Commit,
+    diff_opts: &mut DiffOptions,
+    options: &AnalysisOptions,
+) -> Option<DiffCounts> {
+    let tree = commit.tree().ok()?;
+    let parent_count = commit.parent_count();
+
+    if parent_count <= 1 || options.accuracy == AccuracyMode::GitNumstat {
+        let parent_tree = if parent_count >= 1 {
+            Some(commit.parent(0).ok()?.tree().ok()?)
+        } else {
+            None
+        };
+        let mut diff = repo
+            .diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), Some(diff_opts))
+            .ok()?;
+        return count_diff_lines(repo, &mut diff, options).ok();
+    }
+
+    let mut max = DiffCounts::default();
+    let mut max_lines = 0_usize;
+    for i in 0..parent_count {
+        let parent_tree = commit.parent(i).ok()?.tree().ok()?;
+        let mut diff = repo
+            .diff_tree_to_tree(Some(&parent_tree), Some(&tree), Some(&mut *diff_opts))
+            .ok()?;
+        let counts = count_diff_lines(repo, &mut diff, options).ok()?;
+        max.lines_added = max.lines_added.max(counts.lines_added);
+        max.lines_deleted = max.lines_deleted.max(counts.lines_deleted);
+        max.files_changed = max.files_changed.max(counts.files_changed);
+        max.touches_code |= counts.touches_code;
+        max.large_files = max.large_files.max(counts.large_files);
+        let lines = counts.lines_added + counts.lines_deleted;
+        if i == 0 || lines > max_lines {
+            max_lines = lines;
+            max.lines_by_extension = counts.lines_by_extension;
+            max.paths = counts.paths;
+            max.renames = counts.renames;
+        }
+    }
+
+    Some(max)
+}
+
+/// Check whether a commit leaves its first parent's tree unchanged
+///
+/// A root commit counts as empty when its own tree is.
+fn is_empty_commit(commit: &Commit) -> bool {
+    match commit.parent(0) {
+        Ok(parent) => parent.tree_id() == commit.tree_id(),
+        Err(_) => commit.tree().is_ok_and(|tree| tree.is_empty()),
+    }
+}
+
+/// Check whether a commit changes anything under `scope`, like `git log -- <scope>`
+///
+/// Compares the subtree's id against each parent's, so no diff is needed. A merge
+/// only counts when its subtree differs from every parent, and a root commit when
+/// the subtree exists. Unreadable trees count as touching, so they still reach the
+/// diff and are reported as skipped.
+fn touches_scope(commit: &Commit, scope: &Path) -> bool {
+    let Ok(tree) = commit.tree() else {
+        return true;
+    };
+    let subtree_id = |tree: &Tree| tree.get_path(scope).ok().map(|entry| entry.id());
+    let own = subtree_id(&tree);
+
+    if commit.parent_count() == 0 {
+        return own.is_some();
+    }
+    commit.parents().all(|parent| {
+        parent
+            .tree()
+            .map_or(true, |parent_tree| subtree_id(&parent_tree) != own)
+    })
 }
 
 /// Calculate optimal chunk size for parallel processing based on commit count
 fn get_optimal_chunk_size(total_commits: usize) -> usize {
+    chunk_size_for(total_commits, num_cpus::get())
+}
+
+/// Chunk size for `total_commits` commits on a machine with `cpu_count` cores
+///
+/// Work is split into at least `MIN_CHUNK_COUNT` chunks, so a single core (as some
+/// containers report) still holds a bounded slice of the history at a time instead
+/// of one chunk spanning everything.
+fn chunk_size_for(total_commits: usize, cpu_count: usize) -> usize {
     const MIN_CHUNK_SIZE: usize = 100;
     const MAX_CHUNK_SIZE: usize = 1000;
-    let optimal_size = (total_commits / num_cpus::get()).max(MIN_CHUNK_SIZE);
-    optimal_size.min(MAX_CHUNK_SIZE)
+    const MIN_CHUNK_COUNT: usize = 4;
+    let per_cpu = total_commits / cpu_count.max(1);
+    let optimal_size = per_cpu.min(total_commits.div_ceil(MIN_CHUNK_COUNT));
+    optimal_size.clamp(MIN_CHUNK_SIZE, MAX_CHUNK_SIZE)
+}
+
+/// Exponential moving average of the processing rate
+///
+/// Chunks finish unevenly, so the raw rate swings from one progress update to the next.
+/// Smoothing it keeps the reported rate, and the ETA derived from it, steady.
+#[derive(Debug)]
+struct RateSmoother {
+    /// Weight of the newest sample, between 0 and 1
+    alpha: f64,
+    smoothed: Option<f64>,
+}
+
+impl RateSmoother {
+    fn new(alpha: f64) -> Self {
+        Self {
+            alpha,
+            smoothed: None,
+        }
+    }
+
+    /// Fold in a new rate sample and return the smoothed rate
+    fn update(&mut self, sample: f64) -> f64 {
+        // A chunk finishing right at the start gives an infinite rate; don't let it stick
+        if !sample.is_finite() {
+            return self.smoothed.unwrap_or(sample);
+        }
+        let smoothed = match self.smoothed {
+            Some(previous) => self.alpha * sample + (1.0 - self.alpha) * previous,
+            None => sample,
+        };
+        self.smoothed = Some(smoothed);
+        smoothed
+    }
+}
+
+impl Default for RateSmoother {
+    fn default() -> Self {
+        Self::new(0.3)
+    }
+}
+
+/// Progress shared between chunk tasks
+#[derive(Debug, Default)]
+struct ProgressState {
+    /// Running totals for partial results
+    totals: PartialStats,
+    /// Smoothed commits-per-second rate
+    rate: RateSmoother,
+}
+
+impl ProgressState {
+    /// Fold a finished chunk into the running totals and build the update to report
+    ///
+    /// `processed` counts every commit handed to a finished chunk so far, including
+    /// this one.
+    fn chunk_finished(
+        &mut self,
+        result: &ChunkResult,
+        processed: usize,
+        total_commits: usize,
+        elapsed: f64,
+        stream_partials: bool,
+    ) -> ProgressEstimate {
+        let partial = match result {
+            Ok(chunk_stats) if stream_partials => {
+                self.totals.commit_count += chunk_stats.totals.0;
+                self.totals.lines_added += chunk_stats.totals.1;
+                self.totals.lines_deleted += chunk_stats.totals.2;
+                Some(PartialStats {
+                    activity: chunk_stats.activity.clone(),
+                    ..self.totals.clone()
+                })
+            }
+            _ => None,
+        };
+        let current_date = result
+            .as_ref()
+            .ok()
+            .and_then(|chunk_stats| chunk_stats.activity.last())
+            .map(|activity| activity.date.clone());
+        let commits_per_second = self.rate.update(processed as f64 / elapsed);
+
+        ProgressEstimate {
+            total_commits,
+            processed_commits: processed,
+            estimated_total_time: total_commits as f64 / commits_per_second,
+            elapsed_time: elapsed,
+            commits_per_second,
+            partial,
+            current_date,
+        }
+    }
+}
+
+/// Commits analyzed per second on a typical machine, taken from the benchmarks
+///
+/// Used for estimates until a real run has measured the rate on this machine.
+pub const DEFAULT_COMMITS_PER_SECOND: f64 = 200.0;
+
+/// Progress reported before any chunk has finished
+fn initial_estimate(total_commits: usize, stream_partials: bool) -> ProgressEstimate {
+    ProgressEstimate {
+        total_commits,
+        processed_commits: 0,
+        estimated_total_time: total_commits as f64 / DEFAULT_COMMITS_PER_SECOND,
+        elapsed_time: 0.0,
+        commits_per_second: DEFAULT_COMMITS_PER_SECOND,
+        partial: stream_partials.then(PartialStats::default),
+        current_date: None,
+    }
+}
+
+/// Summary of a finished parallel run, shown as the result's processing statistics
+fn processing_summary(
+    total: &ChunkStats,
+    total_commits: usize,
+    elapsed_secs: f64,
+    chunk_size: usize,
+    tasks: usize,
+) -> String {
+    let commits_per_sec = total_commits as f64 / elapsed_secs;
+    let mut stats = format!(
+        "Processed {} commits in {:.2}s\nCommits/sec: {:.1}\nChunk size: {}\nParallel tasks: {}",
+        total_commits, elapsed_secs, commits_per_sec, chunk_size, tasks
+    );
+    if total.skipped_commits > 0 {
+        stats.push_str(&format!(
+            "\nSkipped {} unreadable commits",
+            total.skipped_commits
+        ));
+    }
+    if total.large_files_skipped > 0 {
+        stats.push_str(&format!(
+            "\nSkipped {} files over the size limit",
+            total.large_files_skipped
+        ));
+    }
+    stats
 }
 
 /// Get optimal number of parallel tasks based on system CPU count
@@ -169,12 +1043,15 @@ async fn process_commits_parallel(
     repo_path: std::path::PathBuf,
     commits: Vec<Oid>,
     contributor: String,
+    options: AnalysisOptions,
     chunk_size: usize,
     progress_tx: Option<mpsc::Sender<ProgressEstimate>>,
-) -> Result<(ProcessResult, String), Error> {
+) -> Result<(ChunkStats, String), Error> {
     let start_time = Instant::now();
     let total_commits = commits.len();
     let processed_commits = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let stream_partials = options.stream_partials;
+    let progress_state = Arc::new(tokio::sync::Mutex::new(ProgressState::default()));
 
     let chunks: Vec<_> = commits.chunks(chunk_size).collect();
     let mut results = Vec::with_capacity(chunks.len());
@@ -184,14 +1061,9 @@ async fn process_commits_parallel(
 
     // Initial progress estimate
     if let Some(tx) = &progress_tx {
-        let estimate = ProgressEstimate {
-            total_commits,
-            processed_commits: 0,
-            estimated_total_time: total_commits as f64 / 200.0, // Initial estimate based on benchmarks
-            elapsed_time: 0.0,
-            commits_per_second: 200.0, // Initial estimate from benchmarks
-        };
-        let _ = tx.send(estimate).await;
+        let _ = tx
+            .send(initial_estimate(total_commits, stream_partials))
+            .await;
     }
 
     for chunk in chunks {
@@ -199,7 +1071,9 @@ async fn process_commits_parallel(
         let chunk_len = chunk.len();
         let repo_path = repo_path.clone();
         let contributor = contributor.clone();
+        let options = options.clone();
         let processed_commits = Arc::clone(&processed_commits);
+        let progress_state = Arc::clone(&progress_state);
         let progress_tx = progress_tx.clone();
         let permit = semaphore
             .clone()
@@ -209,76 +1083,184 @@ async fn process_commits_parallel(
 
         let handle = tokio::spawn(async move {
             let _permit = permit;
-            let result = spawn_blocking(
-                move || -> Result<(CommitData, ActivityData, ContributorData), Error> {
-                    let repo = Repository::open(repo_path)?;
-                    process_commit_chunk(&repo, &chunk, &contributor)
-                },
-            )
+            let result = spawn_blocking(move || -> ChunkResult {
+                let repo = Repository::open(repo_path)?;
+                process_commit_chunk(&repo, &chunk, &contributor, &options)
+            })
             .await
             .map_err(|e: tokio::task::JoinError| Error::from_str(&e.to_string()))?;
 
-            // Update progress after each chunk
+            // Update progress after each chunk. The state lock is held until the update is
+            // sent so that running counts reach the receiver in increasing order.
+            let mut state = progress_state.lock().await;
             let current = processed_commits
                 .fetch_add(chunk_len, std::sync::atomic::Ordering::SeqCst)
                 + chunk_len;
             if let Some(tx) = &progress_tx {
                 let elapsed = start_time.elapsed().as_secs_f64();
-                let commits_per_second = current as f64 / elapsed;
-                let estimate = ProgressEstimate {
-                    total_commits,
-                    processed_commits: current,
-                    estimated_total_time: total_commits as f64 / commits_per_second,
-                    elapsed_time: elapsed,
-                    commits_per_second,
-                };
+                let estimate =
+                    state.chunk_finished(&result, current, total_commits, elapsed, stream_partials);
                 let _ = tx.send(estimate).await;
             }
+            drop(state);
 
             result
         });
         results.push(handle);
     }
 
-    let mut total_commit_count = 0;
-    let mut total_lines_added = 0;
-    let mut total_lines_deleted = 0;
-    let mut total_commit_activity = Vec::with_capacity(commits.len());
-    let mut total_author_count = HashMap::new();
+    let mut total = ChunkStats {
+        activity: Vec::with_capacity(commits.len()),
+        ..Default::default()
+    };
 
     for handle in results {
         match handle.await {
-            Ok(Ok(((commits, lines_added, lines_deleted), activity, authors))) => {
-                total_commit_count += commits;
-                total_lines_added += lines_added;
-                total_lines_deleted += lines_deleted;
-                total_commit_activity.extend(activity);
-                for (author, count) in authors {
-                    *total_author_count.entry(author).or_insert(0) += count;
-                }
-            }
+            Ok(Ok(chunk_stats)) => total.merge(chunk_stats),
             Ok(Err(e)) => eprintln!("Error processing commit chunk: {}", e),
             Err(e) => eprintln!("Task join error: {}", e),
         }
     }
 
-    let elapsed = start_time.elapsed();
-    let elapsed_secs = elapsed.as_secs_f64();
-    let commits_per_sec = total_commits as f64 / elapsed_secs;
+    let elapsed_secs = start_time.elapsed().as_secs_f64();
+    let stats = processing_summary(&total, total_commits, elapsed_secs, chunk_size, max_tasks);
 
-    let stats = format!(
-        "Processed {} commits in {:.2}s\nCommits/sec: {:.1}\nChunk size: {}\nParallel tasks: {}",
-        total_commits, elapsed_secs, commits_per_sec, chunk_size, max_tasks
-    );
+    Ok((total, stats))
+}
+
+/// Process commits in parallel chunks on rayon's thread pool
+///
+/// An alternative to [`process_commits_parallel`] that keeps the CPU-bound diffing off
+/// tokio's blocking pool. Chunks are merged in commit order, so the statistics are
+/// identical to the tokio path.
+async fn process_commits_rayon(
+    repo_path: std::path::PathBuf,
+    commits: Vec<Oid>,
+    contributor: String,
+    options: AnalysisOptions,
+    chunk_size: usize,
+    progress_tx: Option<mpsc::Sender<ProgressEstimate>>,
+) -> Result<(ChunkStats, String), Error> {
+    let start_time = Instant::now();
+    let total_commits = commits.len();
+    let stream_partials = options.stream_partials;
+
+    if let Some(tx) = &progress_tx {
+        let _ = tx
+            .send(initial_estimate(total_commits, stream_partials))
+            .await;
+    }
+
+    spawn_blocking(move || -> Result<(ChunkStats, String), Error> {
+        let processed_commits = std::sync::atomic::AtomicUsize::new(0);
+        let progress_state = std::sync::Mutex::new(ProgressState::default());
+
+        let results: Vec<ChunkResult> = commits
+            .par_chunks(chunk_size)
+            .map_init(
+                || Repository::open(&repo_path),
+                |repo, chunk| {
+                    let result = match repo {
+                        Ok(repo) => process_commit_chunk(repo, chunk, &contributor, &options),
+                        Err(e) => Err(Error::from_str(e.message())),
+                    };
+
+                    // As in the tokio path, the lock is held while sending so that running
+                    // counts reach the receiver in increasing order
+                    if let Ok(mut state) = progress_state.lock() {
+                        let current = processed_commits
+                            .fetch_add(chunk.len(), std::sync::atomic::Ordering::SeqCst)
+                            + chunk.len();
+                        if let Some(tx) = &progress_tx {
+                            let elapsed = start_time.elapsed().as_secs_f64();
+                            let estimate = state.chunk_finished(
+                                &result,
+                                current,
+                                total_commits,
+                                elapsed,
+                                stream_partials,
+                            );
+                            let _ = tx.blocking_send(estimate);
+                        }
+                    }
+
+                    result
+                },
+            )
+            .collect();
 
-    Ok((
-        (
-            (total_commit_count, total_lines_added, total_lines_deleted),
-            total_commit_activity,
-            total_author_count,
-        ),
-        stats,
-    ))
+        let mut total = ChunkStats {
+            activity: Vec::with_capacity(total_commits),
+            ..Default::default()
+        };
+        for result in results {
+            match result {
+                Ok(chunk_stats) => total.merge(chunk_stats),
+                Err(e) => eprintln!("Error processing commit chunk: {}", e),
+            }
+        }
+
+        let elapsed_secs = start_time.elapsed().as_secs_f64();
+        let stats = processing_summary(
+            &total,
+            total_commits,
+            elapsed_secs,
+            chunk_size,
+            rayon::current_num_threads(),
+        );
+        Ok((total, stats))
+    })
+    .await
+    .map_err(|e: tokio::task::JoinError| Error::from_str(&e.to_string()))?
+}
+
+/// Branch name reported for an analysis that fell back to a detached HEAD, and listed
+/// among the available branches while HEAD is detached, as is common in CI checkouts
+pub const DETACHED_HEAD: &str = "(detached)";
+
+/// Whether a commit belongs to a sample of `rate` of all commits
+///
+/// Commit IDs are hashes, so their leading bytes are spread evenly and make a
+/// reproducible pseudo-random pick.
+fn in_sample(oid: Oid, rate: f64) -> bool {
+    let mut prefix = [0; 8];
+    prefix.copy_from_slice(&oid.as_bytes()[..8]);
+    (u64::from_be_bytes(prefix) as f64) < rate * u64::MAX as f64
+}
+
+/// Commit a local branch or any other revision git understands, such as a tag, points
+/// at, if it can be resolved
+fn resolve_branch(repo: &Repository, branch: &str) -> Option<Oid> {
+    if let Ok(branch_ref) = repo.find_branch(branch, git2::BranchType::Local) {
+        if let Some(target) = branch_ref.get().target() {
+            return Some(target);
+        }
+    }
+    // Annotated tags point at a tag object, so peel through to the commit
+    repo.revparse_single(branch)
+        .and_then(|object| object.peel_to_commit())
+        .ok()
+        .map(|commit| commit.id())
+}
+
+/// Commit a local branch or any other revision git understands, such as a tag, points
+/// at, falling back to HEAD if it can't be resolved
+///
+/// This is the commit an analysis of `branch` starts from.
+pub fn branch_head(repo: &Repository, branch: &str) -> Result<Oid, Error> {
+    match resolve_branch(repo, branch) {
+        Some(oid) => Ok(oid),
+        None => Ok(repo.head()?.peel_to_commit()?.id()),
+    }
+}
+
+/// Start a revwalk at the commit [`branch_head`] resolves `branch` to
+fn push_branch_or_head(
+    repo: &Repository,
+    revwalk: &mut git2::Revwalk,
+    branch: &str,
+) -> Result<(), Error> {
+    revwalk.push(branch_head(repo, branch)?)
 }
 
 /// Analyze a Git repository with branch and contributor filters
@@ -286,54 +1268,145 @@ async fn analyze_repo_with_filter(
     repo: Repository,
     branch: &str,
     contributor: &str,
+    mut options: AnalysisOptions,
     progress_tx: Option<mpsc::Sender<ProgressEstimate>>,
 ) -> Result<AnalysisResult, Error> {
     let start_time = Instant::now();
-    let repo_path = repo.path().to_path_buf();
+    let known_branches = options.known_branches.take();
+    let repo_path = reopen_path(&repo);
+    let head_detached = repo.head_detached().unwrap_or(false);
+    let remote_url = repo
+        .find_remote("origin")
+        .ok()
+        .and_then(|remote| remote.url().map(str::to_string));
+    let selected_branch = if head_detached && resolve_branch(&repo, branch).is_none() {
+        DETACHED_HEAD.to_string()
+    } else {
+        branch.to_string()
+    };
 
     // Get all commits
-    let commits: Vec<Oid> = {
+    let mut commits: Vec<Oid> = {
         let repo_path = repo_path.clone();
         let branch = branch.to_string();
+        let (since, until) = (options.since, options.until);
+        let since_commit = options.since_commit.clone();
+        let first_parent = options.first_parent;
+        let oldest_first = options.oldest_first;
         spawn_blocking(move || -> Result<Vec<Oid>, Error> {
             let repo = Repository::open(&repo_path)?;
             let mut revwalk = repo.revwalk()?;
 
-            // Try to use the specified branch, fallback to HEAD
-            if let Ok(branch_ref) = repo.find_branch(&branch, git2::BranchType::Local) {
-                if let Some(branch_ref_name) = branch_ref.get().name() {
-                    revwalk.push_ref(branch_ref_name)?;
-                } else {
-                    revwalk.push_head()?;
-                }
-            } else {
-                revwalk.push_head()?;
+            push_branch_or_head(&repo, &mut revwalk, &branch)?;
+            if let Some(since_commit) = since_commit {
+                revwalk.hide(Oid::from_str(&since_commit)?)?;
+            }
+
+            let mut sorting = git2::Sort::NONE;
+            if first_parent {
+                sorting |= git2::Sort::TOPOLOGICAL;
+            }
+            if oldest_first {
+                // Topological too, so commits made in the same second keep parents first
+                sorting |= git2::Sort::TOPOLOGICAL | git2::Sort::TIME | git2::Sort::REVERSE;
+            }
+            revwalk.set_sorting(sorting)?;
+            if first_parent {
+                revwalk.simplify_first_parent()?;
+            }
+
+            if since.is_none() && until.is_none() {
+                return revwalk.collect::<Result<Vec<_>, _>>();
             }
 
-            revwalk.collect::<Result<Vec<_>, _>>()
+            // Drop commits outside the date range here so they are never diffed
+            let mut commits = Vec::new();
+            for oid in revwalk {
+                let oid = oid?;
+                if in_date_range(repo.find_commit(oid)?.time().seconds(), since, until) {
+                    commits.push(oid);
+                }
+            }
+            Ok(commits)
         })
         .await
         .map_err(|e: tokio::task::JoinError| Error::from_str(&e.to_string()))?
         .map_err(|e: Error| Error::from_str(&e.to_string()))?
     };
 
+    let sample_rate = options.sample.filter(|rate| *rate > 0.0 && *rate < 1.0);
+    if let Some(rate) = sample_rate {
+        commits.retain(|oid| in_sample(*oid, rate));
+    }
+
+    let fuzzy_dedup = options.fuzzy_dedup;
+    let oldest_first = options.oldest_first;
+    let include_tags = options.include_tags;
+    let anonymize = options.anonymize;
     let chunk_size = get_optimal_chunk_size(commits.len());
-    let ((commit_stats, commit_activity, author_commit_count), stats) = process_commits_parallel(
-        repo_path.clone(),
-        commits,
-        contributor.to_string(),
-        chunk_size,
-        progress_tx,
-    )
-    .await?;
+    let (mut chunk_stats, stats) = match options.parallelism {
+        Parallelism::Tokio => {
+            process_commits_parallel(
+                repo_path.clone(),
+                commits,
+                contributor.to_string(),
+                options,
+                chunk_size,
+                progress_tx,
+            )
+            .await?
+        }
+        Parallelism::Rayon => {
+            process_commits_rayon(
+                repo_path.clone(),
+                commits,
+                contributor.to_string(),
+                options,
+                chunk_size,
+                progress_tx,
+            )
+            .await?
+        }
+    };
 
-    let (commit_count, total_lines_added, total_lines_deleted) = commit_stats;
+    if fuzzy_dedup {
+        chunk_stats.merge_similar_authors();
+    }
+    if anonymize {
+        chunk_stats.anonymize_authors();
+    }
+
+    let ChunkStats {
+        totals: (mut commit_count, mut total_lines_added, mut total_lines_deleted),
+        activity: mut commit_activity,
+        author_commit_count,
+        author_first_commit,
+        author_emails,
+        skipped_commits,
+        large_files_skipped,
+        empty_commits: empty_commit_count,
+        parent_counts: parent_count_distribution,
+    } = chunk_stats;
+    // Chunks are merged in walk order, which an oldest-first walk wants kept as is
+    if !oldest_first {
+        sort_activity(&mut commit_activity);
+    }
+
+    // Estimate the whole history's totals from the sampled commits
+    if let Some(rate) = sample_rate {
+        let scale_up = |total: usize| (total as f64 / rate).round() as usize;
+        commit_count = scale_up(commit_count);
+        total_lines_added = scale_up(total_lines_added);
+        total_lines_deleted = scale_up(total_lines_deleted);
+    }
+
+    let contributor_emails = primary_emails(author_emails);
 
     let mut top_contributors: Vec<(String, usize)> = author_commit_count
         .iter()
         .map(|(k, v)| (k.clone(), *v))
         .collect();
-    top_contributors.sort_by(|a, b| b.1.cmp(&a.1));
+    top_contributors.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
     top_contributors.truncate(5);
 
     let average_commit_size = if commit_count > 0 {
@@ -342,38 +1415,36 @@ async fn analyze_repo_with_filter(
         0.0
     };
 
-    let mut commit_frequency = HashMap::new();
-    for (date, _, _) in &commit_activity {
-        let week = date[..7].to_string();
-        *commit_frequency.entry(week).or_insert(0) += 1;
-    }
+    let commit_frequency = monthly_commit_frequency(&commit_activity);
+    let author_commit_frequency = author_commit_frequency(&commit_activity, &top_contributors);
+    let commit_type_counts = commit_type_counts(&commit_activity);
+    let revert_count = revert_count(&commit_activity);
+    let lines_by_extension = extension_line_counts(&commit_activity);
+    let primary_language = primary_language(&lines_by_extension);
+    let file_hotspots = file_hotspots(&commit_activity);
+    let commits_by_year = yearly_commit_counts(&commit_activity);
+    let commit_size_histogram = commit_size_histogram(&commit_activity);
+    let contributor_add_delete_ratio = add_delete_ratios(&commit_activity);
+    let weekly_commits_by_author = weekly_author_commits(&commit_activity);
 
-    // Get available branches
-    let branch_names = {
-        let repo_path = repo_path.clone();
-        spawn_blocking(move || -> Result<Vec<String>, Error> {
-            let repo = Repository::open(repo_path)?;
-            let mut branch_names = Vec::new();
-            let branches = repo.branches(None)?;
-
-            for branch in branches.flatten() {
-                if let Ok(Some(name)) = branch.0.name() {
-                    branch_names.push(name.to_string());
-                }
-            }
-
-            branch_names.sort();
-            if let Some(main_idx) = branch_names.iter().position(|x| x == "main") {
-                branch_names.swap(0, main_idx);
-            } else if let Some(master_idx) = branch_names.iter().position(|x| x == "master") {
-                branch_names.swap(0, master_idx);
-            }
-
-            Ok(branch_names)
-        })
-        .await
-        .map_err(|e: tokio::task::JoinError| Error::from_str(&e.to_string()))?
-        .map_err(|e: Error| Error::from_str(&e.to_string()))?
+    // Get available branches, unless the caller already knows them
+    let branch_names = match known_branches {
+        Some(branches) => branches,
+        None => {
+            let repo_path = repo_path.clone();
+            spawn_blocking(move || -> Result<Vec<String>, Error> {
+                let repo = Repository::open(repo_path)?;
+                let refs = list_refs(&repo, include_tags)?;
+                let detached = head_detached.then(|| DETACHED_HEAD.to_string());
+                Ok(detached
+                    .into_iter()
+                    .chain(refs.into_iter().map(|(name, _)| name))
+                    .collect())
+            })
+            .await
+            .map_err(|e: tokio::task::JoinError| Error::from_str(&e.to_string()))?
+            .map_err(|e: Error| Error::from_str(&e.to_string()))?
+        }
     };
 
     let elapsed = start_time.elapsed();
@@ -383,13 +1454,33 @@ async fn analyze_repo_with_filter(
         total_lines_added,
         total_lines_deleted,
         top_contributors: top_contributors.clone(),
+        contributor_commit_counts: author_commit_count,
         commit_activity,
         average_commit_size,
         commit_frequency,
+        author_commit_frequency,
         top_contributors_by_lines: top_contributors,
         available_branches: branch_names,
+        selected_branch,
         elapsed_time: elapsed.as_secs_f64(),
         processing_stats: stats,
+        author_first_commit,
+        commit_type_counts,
+        commits_by_year,
+        commit_size_histogram,
+        contributor_add_delete_ratio,
+        weekly_commits_by_author,
+        skipped_commits,
+        large_files_skipped,
+        revert_count,
+        empty_commit_count,
+        lines_by_extension,
+        primary_language,
+        file_hotspots,
+        approximate: sample_rate.is_some(),
+        parent_count_distribution,
+        remote_url,
+        contributor_emails,
     })
 }
 
@@ -400,38 +1491,376 @@ pub async fn analyze_repo_async(
     contributor: String,
     progress_tx: Option<mpsc::Sender<ProgressEstimate>>,
 ) -> Result<AnalysisResult, Error> {
-    let repo = spawn_blocking(move || -> Result<Repository, Error> { Repository::open(&path) })
+    analyze_repo_with_options(
+        path,
+        branch,
+        contributor,
+        AnalysisOptions::default(),
+        progress_tx,
+    )
+    .await
+}
+
+/// Analyze a Git repository asynchronously with branch and contributor filters and
+/// custom [`AnalysisOptions`]
+pub async fn analyze_repo_with_options(
+    path: String,
+    branch: String,
+    contributor: String,
+    options: AnalysisOptions,
+    progress_tx: Option<mpsc::Sender<ProgressEstimate>>,
+) -> Result<AnalysisResult, Error> {
+    let repo = spawn_blocking(move || open_repository(&path))
         .await
-        .map_err(|e: tokio::task::JoinError| Error::from_str(&e.to_string()))?
-        .map_err(|e: Error| Error::from_str(&e.to_string()))?;
+        .map_err(|e: tokio::task::JoinError| Error::from_str(&e.to_string()))??;
 
-    analyze_repo_with_filter(repo, &branch, &contributor, progress_tx).await
+    analyze_repo_with_filter(repo, &branch, &contributor, options, progress_tx).await
 }
 
-/// Get list of available branches in the repository
-pub async fn get_available_branches(repo: &Repository) -> Result<Vec<String>, Error> {
-    let repo_path = repo.path().to_path_buf();
+/// Most branches analyzed at once by [`analyze_all_branches`]; each analysis already
+/// spreads its commits over several tasks
+const MAX_CONCURRENT_BRANCHES: usize = 4;
 
-    spawn_blocking(move || -> Result<Vec<String>, Error> {
-        let repo = Repository::open(repo_path)?;
-        let mut branch_names = Vec::new();
-        let branches = repo.branches(None)?;
+/// Analyze every local branch concurrently, for comparing branches side by side
+///
+/// Returns each branch's result keyed by branch name. Fails if any branch fails.
+pub async fn analyze_all_branches(
+    path: String,
+    contributor: String,
+) -> Result<HashMap<String, AnalysisResult>, Error> {
+    let repo = {
+        let path = path.clone();
+        spawn_blocking(move || open_repository(&path))
+            .await
+            .map_err(|e: tokio::task::JoinError| Error::from_str(&e.to_string()))??
+    };
+    let branches = get_available_branches(&repo).await?;
+    let semaphore = Arc::new(tokio::sync::Semaphore::new(MAX_CONCURRENT_BRANCHES));
+    let contributor = contributor.as_str();
 
-        for branch in branches.flatten() {
-            if let Ok(Some(name)) = branch.0.name() {
-                branch_names.push(name.to_string());
-            }
+    let analyses = branches.iter().map(|branch| {
+        let semaphore = Arc::clone(&semaphore);
+        let path = path.clone();
+        let options = AnalysisOptions {
+            known_branches: Some(branches.clone()),
+            ..Default::default()
+        };
+        async move {
+            let _permit = semaphore
+                .acquire()
+                .await
+                .map_err(|e| Error::from_str(&e.to_string()))?;
+            let repo = spawn_blocking(move || open_repository(&path))
+                .await
+                .map_err(|e: tokio::task::JoinError| Error::from_str(&e.to_string()))??;
+            let result = analyze_repo_with_filter(repo, branch, contributor, options, None).await?;
+            Ok::<_, Error>((branch.clone(), result))
+        }
+    });
+
+    Ok(futures::future::try_join_all(analyses)
+        .await?
+        .into_iter()
+        .collect())
+}
+
+/// Path to reopen `repo` from on worker threads
+///
+/// This is the working directory, so a linked worktree (whose `.git` is a file
+/// pointing into the main repository) reopens as that worktree with its own HEAD,
+/// or the git directory for a bare repository.
+fn reopen_path(repo: &Repository) -> std::path::PathBuf {
+    repo.workdir().unwrap_or_else(|| repo.path()).to_path_buf()
+}
+
+/// Open a repository, explaining in plain terms why it could not be opened
+///
+/// Distinguishes a path that doesn't exist, a directory that isn't a git repository,
+/// and a repository git2 found but couldn't read.
+fn open_repository(path: &str) -> Result<Repository, Error> {
+    if !std::path::Path::new(path).exists() {
+        return Err(Error::from_str(&format!("Path does not exist: {}", path)));
+    }
+
+    Repository::open(path).map_err(|e| {
+        if e.code() == git2::ErrorCode::NotFound {
+            Error::from_str(&format!(
+                "Not a git repository (no .git directory found): {}",
+                path
+            ))
+        } else {
+            Error::from_str(&format!(
+                "Git repository at {} could not be read and may be corrupt: {}",
+                path,
+                e.message()
+            ))
+        }
+    })
+}
+
+/// List every contributor on a branch with their commit count, most active first
+///
+/// Only commit headers are read and no diffs are computed, which makes this much faster
+/// than a full analysis when only contributor names and counts are needed, e.g. for
+/// autocompletion. Ties are ordered by name.
+pub async fn list_contributors(
+    path: String,
+    branch: String,
+) -> Result<Vec<(String, usize)>, Error> {
+    spawn_blocking(move || -> Result<Vec<(String, usize)>, Error> {
+        let repo = open_repository(&path)?;
+        let mut revwalk = repo.revwalk()?;
+        push_branch_or_head(&repo, &mut revwalk, &branch)?;
+
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for oid in revwalk {
+            let commit = repo.find_commit(oid?)?;
+            let author = commit.author().name().unwrap_or("Unknown").to_string();
+            *counts.entry(author).or_insert(0) += 1;
         }
 
-        branch_names.sort();
-        if let Some(main_idx) = branch_names.iter().position(|x| x == "main") {
-            branch_names.swap(0, main_idx);
-        } else if let Some(master_idx) = branch_names.iter().position(|x| x == "master") {
-            branch_names.swap(0, master_idx);
+        let mut contributors: Vec<(String, usize)> = counts.into_iter().collect();
+        contributors.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        Ok(contributors)
+    })
+    .await
+    .map_err(|e: tokio::task::JoinError| Error::from_str(&e.to_string()))?
+}
+
+/// Count the commits an analysis of `branch` would walk, without reading any diffs
+pub async fn estimate_commit_count(path: String, branch: String) -> Result<usize, Error> {
+    spawn_blocking(move || -> Result<usize, Error> {
+        let repo = open_repository(&path)?;
+        let mut revwalk = repo.revwalk()?;
+        push_branch_or_head(&repo, &mut revwalk, &branch)?;
+
+        let mut count = 0;
+        for oid in revwalk {
+            oid?;
+            count += 1;
         }
+        Ok(count)
+    })
+    .await
+    .map_err(|e: tokio::task::JoinError| Error::from_str(&e.to_string()))?
+}
+
+/// Time needed to analyze `commits` commits at `commits_per_second`
+///
+/// Falls back to [`DEFAULT_COMMITS_PER_SECOND`] when no usable rate has been measured.
+pub fn estimated_duration(commits: usize, commits_per_second: Option<f64>) -> Duration {
+    let rate = commits_per_second
+        .filter(|rate| rate.is_finite() && *rate > 0.0)
+        .unwrap_or(DEFAULT_COMMITS_PER_SECOND);
+    Duration::from_secs_f64(commits as f64 / rate)
+}
+
+/// Estimate how long a full analysis of `branch` will take before starting it
+///
+/// Pass the rate measured by the last run on this machine, if any, for a calibrated
+/// estimate; otherwise the benchmark rate is used.
+pub async fn estimate_analysis_duration(
+    path: String,
+    branch: String,
+    commits_per_second: Option<f64>,
+) -> Result<Duration, Error> {
+    let commits = estimate_commit_count(path, branch).await?;
+    Ok(estimated_duration(commits, commits_per_second))
+}
+
+/// Get list of available branches in the repository
+pub async fn get_available_branches(repo: &Repository) -> Result<Vec<String>, Error> {
+    let refs = get_available_refs(repo, false).await?;
+    Ok(refs.into_iter().map(|(name, _)| name).collect())
+}
 
-        Ok(branch_names)
+/// Get the branches and, if `include_tags` is set, the tags that can be analyzed
+///
+/// Branches come first, sorted with `main` (or `master`) at the front, followed by
+/// tags in name order.
+pub async fn get_available_refs(
+    repo: &Repository,
+    include_tags: bool,
+) -> Result<Vec<(String, RefKind)>, Error> {
+    let repo_path = reopen_path(repo);
+
+    spawn_blocking(move || -> Result<Vec<(String, RefKind)>, Error> {
+        let repo = Repository::open(repo_path)?;
+        list_refs(&repo, include_tags)
     })
     .await
     .map_err(|e: tokio::task::JoinError| Error::from_str(&e.to_string()))?
 }
+
+/// List local branches, then tags if requested, in display order
+fn list_refs(repo: &Repository, include_tags: bool) -> Result<Vec<(String, RefKind)>, Error> {
+    let mut branch_names = Vec::new();
+    let branches = repo.branches(None)?;
+
+    for branch in branches.flatten() {
+        if let Ok(Some(name)) = branch.0.name() {
+            branch_names.push(name.to_string());
+        }
+    }
+
+    branch_names.sort();
+    if let Some(main_idx) = branch_names.iter().position(|x| x == "main") {
+        branch_names.swap(0, main_idx);
+    } else if let Some(master_idx) = branch_names.iter().position(|x| x == "master") {
+        branch_names.swap(0, master_idx);
+    }
+
+    let mut refs: Vec<(String, RefKind)> = branch_names
+        .into_iter()
+        .map(|name| (name, RefKind::Branch))
+        .collect();
+
+    if include_tags {
+        let mut tag_names: Vec<String> = repo
+            .tag_names(None)?
+            .iter()
+            .flatten()
+            .map(String::from)
+            .collect();
+        tag_names.sort();
+        refs.extend(tag_names.into_iter().map(|name| (name, RefKind::Tag)));
+    }
+
+    Ok(refs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_size_on_single_core() {
+        assert_eq!(chunk_size_for(2_000, 1), 500);
+        assert_eq!(chunk_size_for(1_000_000, 1), 1000);
+        assert_eq!(chunk_size_for(50, 1), 100);
+        // A core count of 0 must not divide by zero
+        assert_eq!(chunk_size_for(2_000, 0), 500);
+        assert_eq!(chunk_size_for(2_000, 8), 250);
+    }
+
+    #[test]
+    fn test_is_excluded_author() {
+        let exclude = vec!["[bot]".to_string(), "ci@".to_string()];
+
+        assert!(is_excluded_author(
+            "dependabot[bot]",
+            "49699333+dependabot[bot]@users.noreply.github.com",
+            &exclude
+        ));
+        assert!(is_excluded_author(
+            "Build Server",
+            "CI@example.com",
+            &exclude
+        ));
+        assert!(!is_excluded_author("Alice", "alice@example.com", &exclude));
+    }
+
+    #[test]
+    fn test_primary_emails_prefers_most_frequent() {
+        let mut author_emails: EmailData = HashMap::new();
+        author_emails.insert(
+            "Alice".to_string(),
+            HashMap::from([
+                ("alice@old.example".to_string(), 1),
+                ("alice@example.com".to_string(), 3),
+            ]),
+        );
+        author_emails.insert(
+            "Bob".to_string(),
+            HashMap::from([
+                ("bob@b.example".to_string(), 2),
+                ("bob@a.example".to_string(), 2),
+            ]),
+        );
+
+        let emails = primary_emails(author_emails);
+
+        assert_eq!(emails["Alice"], "alice@example.com");
+        assert_eq!(emails["Bob"], "bob@a.example");
+    }
+
+    #[test]
+    fn test_rate_smoother_reduces_variance() {
+        fn variance(values: &[f64]) -> f64 {
+            let mean = values.iter().sum::<f64>() / values.len() as f64;
+            values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64
+        }
+
+        let noisy = [
+            200.0, 600.0, 150.0, 550.0, 250.0, 700.0, 100.0, 500.0, 300.0, 650.0,
+        ];
+        let mut smoother = RateSmoother::default();
+        let smoothed: Vec<f64> = noisy.iter().map(|&rate| smoother.update(rate)).collect();
+
+        assert_eq!(smoothed[0], noisy[0]);
+        assert!(variance(&smoothed) < variance(&noisy) / 2.0);
+        assert_eq!(smoother.update(f64::INFINITY), smoothed[smoothed.len() - 1]);
+    }
+
+    #[test]
+    fn test_is_excluded_path() {
+        let exclude = vec!["vendor/".to_string(), "src/generated".to_string()];
+
+        assert!(is_excluded_path(Path::new("vendor/lib.rs"), &exclude));
+        assert!(is_excluded_path(Path::new("web/vendor/app.js"), &exclude));
+        assert!(is_excluded_path(
+            Path::new("src/generated/api.rs"),
+            &exclude
+        ));
+        assert!(!is_excluded_path(Path::new("vendored.rs"), &exclude));
+        assert!(!is_excluded_path(Path::new("src/main.rs"), &exclude));
+        assert!(!is_excluded_path(
+            Path::new("vendor/lib.rs"),
+            &["/".to_string()]
+        ));
+    }
+
+    #[test]
+    fn test_parse_coauthors() {
+        let message = "Pair on parser\n\nCo-authored-by: Bob <bob@example.com>\nco-authored-by: Carol Smith <carol@example.com>\nSigned-off-by: Alice <alice@example.com>";
+        assert_eq!(
+            parse_coauthors(message),
+            vec![
+                ("Bob".to_string(), "bob@example.com".to_string()),
+                ("Carol Smith".to_string(), "carol@example.com".to_string()),
+            ]
+        );
+        assert!(parse_coauthors("No trailers here").is_empty());
+    }
+
+    #[test]
+    fn test_is_revert_message() {
+        assert!(is_revert_message(
+            "Revert \"Add slider\"\n\nThis reverts commit 1a2b3c4d5e6f."
+        ));
+        assert!(is_revert_message(
+            "Undo slider\n\nThis reverts commit 1a2b3c4d5e6f7a8b9c0d."
+        ));
+        assert!(!is_revert_message("Revert the slider tweak"));
+        assert!(!is_revert_message(
+            "Explain why\n\nThis reverts commit nothing."
+        ));
+        assert!(!is_revert_message(""));
+    }
+
+    #[test]
+    fn test_parse_commit_type() {
+        assert_eq!(
+            parse_commit_type("feat: add slider"),
+            Some("feat".to_string())
+        );
+        assert_eq!(
+            parse_commit_type("fix(ui)!: crash\n\nbody"),
+            Some("fix".to_string())
+        );
+        assert_eq!(parse_commit_type("Docs: typo"), Some("docs".to_string()));
+        assert_eq!(parse_commit_type("Merge branch 'main': sync"), None);
+        assert_eq!(parse_commit_type("Add line"), None);
+        assert_eq!(parse_commit_type(""), None);
+    }
+}
@@ -0,0 +1,98 @@
+/// Module for metrics derived from an existing analysis result.
+/// These work on the stored per-commit activity and never touch the repository.
+use crate::types::AnalysisResult;
+use chrono::{Duration, NaiveDate};
+use std::collections::{BTreeMap, HashMap};
+
+/// Count distinct active contributors over a rolling window
+///
+/// For every day between the first and last commit, counts the authors with at
+/// least one commit in the `window_days` days ending on (and including) that day.
+/// Activity entries with unparseable dates are ignored.
+///
+/// # Returns
+/// * `Vec<(String, usize)>` - `(YYYY-MM-DD, active contributors)` per day, in date order
+pub fn rolling_active_contributors(
+    result: &AnalysisResult,
+    window_days: usize,
+) -> Vec<(String, usize)> {
+    let mut authors_by_day: BTreeMap<NaiveDate, Vec<&str>> = BTreeMap::new();
+    for activity in &result.commit_activity {
+        if let Ok(date) = NaiveDate::parse_from_str(&activity.date, "%Y-%m-%d") {
+            authors_by_day
+                .entry(date)
+                .or_default()
+                .push(activity.author.as_str());
+        }
+    }
+
+    let (Some(&first), Some(&last)) = (
+        authors_by_day.keys().next(),
+        authors_by_day.keys().next_back(),
+    ) else {
+        return Vec::new();
+    };
+
+    let window = Duration::days(window_days.max(1) as i64);
+    let mut commits_in_window: HashMap<&str, usize> = HashMap::new();
+    let mut series = Vec::new();
+    let mut day = first;
+
+    while day <= last {
+        // Slide the window forward: add today's commits, drop the day that fell out
+        for author in authors_by_day.get(&day).into_iter().flatten() {
+            *commits_in_window.entry(author).or_insert(0) += 1;
+        }
+        for author in authors_by_day.get(&(day - window)).into_iter().flatten() {
+            if let Some(count) = commits_in_window.get_mut(author) {
+                *count -= 1;
+                if *count == 0 {
+                    commits_in_window.remove(author);
+                }
+            }
+        }
+
+        series.push((day.to_string(), commits_in_window.len()));
+        day += Duration::days(1);
+    }
+
+    series
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::CommitActivity;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_rolling_active_contributors() {
+        let result = AnalysisResult {
+            commit_activity: vec![
+                CommitActivity::new("2023-01-01", 1, 0, "Alice"),
+                CommitActivity::new("2023-01-03", 1, 0, "Bob"),
+                CommitActivity::new("2023-01-05", 1, 0, "Alice"),
+                CommitActivity::new("2023-01-10", 1, 0, "Carol"),
+            ],
+            ..Default::default()
+        };
+
+        let series = rolling_active_contributors(&result, 7);
+
+        assert_eq!(series.len(), 10);
+        assert_eq!(series[0], ("2023-01-01".to_string(), 1));
+        assert_eq!(series[2], ("2023-01-03".to_string(), 2));
+        // Alice's commit on the 1st has left the window ending on the 8th
+        assert_eq!(series[7], ("2023-01-08".to_string(), 2));
+        // Bob's commit on the 3rd is still inside the window ending on the 9th
+        assert_eq!(series[8], ("2023-01-09".to_string(), 2));
+        // By the 10th only Alice (5th) and Carol (10th) remain in the window
+        assert_eq!(series[9], ("2023-01-10".to_string(), 2));
+    }
+
+    #[test]
+    fn test_rolling_active_contributors_empty() {
+        let result = AnalysisResult::default();
+        assert!(rolling_active_contributors(&result, 30).is_empty());
+    }
+}
@@ -1,14 +1,37 @@
-use crate::types::AnalysisResult;
+use crate::types::{AnalysisResult, CommitActivity};
+#[cfg(feature = "bert")]
 use anyhow::Result;
-use chrono::{DateTime, Datelike, NaiveDateTime, Timelike, Utc};
+use chrono::{DateTime, Datelike, Timelike, Utc};
+#[cfg(feature = "bert")]
 use rust_bert::pipelines::sequence_classification::SequenceClassificationModel;
+#[cfg(feature = "bert")]
 use rust_bert::RustBertError;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 // Constants for the model
 //const MAX_COMMITS: usize = 1000;
+#[cfg(feature = "bert")]
 const THRESHOLD: f32 = 0.95;
+/// Deviations from the median commit size, in MADs, beyond which
+/// [`StatisticalAnomalyDetector`] flags a commit by default
+pub const DEFAULT_MAD_MULTIPLE: f32 = 5.0;
+/// Commits this many standard deviations above the mean size are flagged by the
+/// statistical detector
+const Z_SCORE_THRESHOLD: f32 = 3.0;
+
+/// Reasons anomaly detection with the language model can fail
+#[derive(Debug, thiserror::Error)]
+pub enum AnomalyError {
+    /// The model couldn't be loaded, typically because it can't be downloaded offline.
+    /// [`StatisticalAnomalyDetector`] still works in this case.
+    #[error("ML anomaly detection is unavailable: {0}")]
+    MlUnavailable(String),
+    /// The model loaded but failed while scoring commits
+    #[cfg(feature = "bert")]
+    #[error("ML anomaly detection failed: {0}")]
+    Model(#[from] RustBertError),
+}
 
 /// Represents a commit feature vector for ML analysis
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,6 +48,8 @@ pub struct CommitFeatures {
     pub lines_deleted: f32,
     pub files_changed: f32,
     pub net_change_ratio: f32,
+    /// Lines added plus deleted, unscaled
+    pub lines_changed: f32,
 
     // Author-based features
     pub author_previous_commits: f32,
@@ -72,6 +97,7 @@ impl CommitFeatures {
         }
     }
 
+    #[cfg(feature = "bert")]
     fn to_input_string(&self) -> String {
         format!(
             "time:{:.2} day:{:.2} month:{:.2} weekend:{:.2} category:{:.2} added:{:.2} deleted:{:.2} files:{:.2} ratio:{:.2} commits:{:.2} last:{:.2} activity:{:.2}",
@@ -91,12 +117,90 @@ impl CommitFeatures {
     }
 }
 
+/// Anomaly detector flagging commits whose size is far from the median
+///
+/// Uses the median absolute deviation (MAD) of lines added plus deleted, which unlike
+/// the standard deviation isn't inflated by the outliers it is meant to find. Fast,
+/// and needs no model download.
+#[derive(Debug, Clone)]
+pub struct StatisticalAnomalyDetector {
+    mad_multiple: f32,
+    median: f32,
+    mad: f32,
+}
+
+impl Default for StatisticalAnomalyDetector {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAD_MULTIPLE)
+    }
+}
+
+impl StatisticalAnomalyDetector {
+    /// Create a detector flagging commits more than `mad_multiple` MADs from the median
+    pub fn new(mad_multiple: f32) -> Self {
+        Self {
+            mad_multiple,
+            median: 0.0,
+            mad: 0.0,
+        }
+    }
+
+    /// Learn the median commit size and its MAD from `features`
+    pub fn train(&mut self, features: &[CommitFeatures]) {
+        let sizes: Vec<f32> = features.iter().map(|f| f.lines_changed).collect();
+        self.median = median(sizes.clone());
+        self.mad = median(
+            sizes
+                .iter()
+                .map(|size| (size - self.median).abs())
+                .collect(),
+        );
+    }
+
+    /// Train on the commits of `result` and score them, in the order of
+    /// [`prepare_commit_features`]
+    pub fn analyze(mut self, result: &AnalysisResult) -> Vec<CommitFeatures> {
+        let mut features = prepare_commit_features(result);
+        self.train(&features);
+        self.detect_anomalies(&mut features);
+        features
+    }
+
+    /// Score commits by their distance from the median in MADs and flag outliers
+    pub fn detect_anomalies(&self, features: &mut [CommitFeatures]) {
+        // When most commits are the same size the MAD is 0; treat sizes within a line of
+        // each other as typical rather than flagging every other commit
+        let mad = self.mad.max(1.0);
+        for feature in features.iter_mut() {
+            let score = (feature.lines_changed - self.median).abs() / mad;
+            feature.anomaly_score = Some(score);
+            feature.is_anomalous = Some(score > self.mad_multiple);
+        }
+    }
+}
+
+/// Median of `values`, or 0 if there are none
+fn median(mut values: Vec<f32>) -> f32 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.sort_by(f32::total_cmp);
+    let mid = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    }
+}
+
 /// Anomaly detector using BERT architecture
+#[cfg(feature = "bert")]
 pub struct AnomalyDetector {
     model: SequenceClassificationModel,
     threshold: f32,
 }
 
+#[cfg(feature = "bert")]
 impl AnomalyDetector {
     pub fn new() -> Result<Self, RustBertError> {
         // Initialize the sequence classification model
@@ -153,6 +257,17 @@ impl AnomalyDetector {
     }
 }
 
+/// Commits with a valid timestamp, oldest first, in the order features are built for
+fn chronological_activity(analysis_result: &AnalysisResult) -> Vec<&CommitActivity> {
+    let mut activities: Vec<_> = analysis_result
+        .commit_activity
+        .iter()
+        .filter(|activity| DateTime::<Utc>::from_timestamp(activity.timestamp, 0).is_some())
+        .collect();
+    activities.sort_by_key(|activity| activity.timestamp);
+    activities
+}
+
 /// Prepares commit data for ML analysis
 pub fn prepare_commit_features(analysis_result: &AnalysisResult) -> Vec<CommitFeatures> {
     let mut author_commit_counts: HashMap<String, usize> = HashMap::new();
@@ -161,12 +276,8 @@ pub fn prepare_commit_features(analysis_result: &AnalysisResult) -> Vec<CommitFe
     let mut features = Vec::new();
 
     // Process commit activity chronologically
-    for (date_str, lines_added, lines_deleted) in &analysis_result.commit_activity {
-        if let Ok(naive_date) =
-            NaiveDateTime::parse_from_str(&format!("{} 00:00:00", date_str), "%Y-%m-%d %H:%M:%S")
-        {
-            let date = DateTime::<Utc>::from_naive_utc_and_offset(naive_date, Utc);
-
+    for activity in chronological_activity(analysis_result) {
+        if let Some(date) = DateTime::<Utc>::from_timestamp(activity.timestamp, 0) {
             // Extract time-based features
             let hour = date.hour();
             let day_of_week = date.weekday().num_days_from_monday() as f32;
@@ -175,8 +286,8 @@ pub fn prepare_commit_features(analysis_result: &AnalysisResult) -> Vec<CommitFe
             let time_of_day_category = CommitFeatures::calculate_time_of_day_category(hour);
 
             // Calculate size-based features
-            let lines_added = *lines_added as f32;
-            let lines_deleted = *lines_deleted as f32;
+            let lines_added = activity.lines_added as f32;
+            let lines_deleted = activity.lines_deleted as f32;
             let net_change_ratio = if lines_added + lines_deleted > 0.0 {
                 (lines_added - lines_deleted) / (lines_added + lines_deleted)
             } else {
@@ -212,6 +323,7 @@ pub fn prepare_commit_features(analysis_result: &AnalysisResult) -> Vec<CommitFe
                 lines_deleted: lines_deleted.log2().max(0.0),
                 files_changed: 1.0,
                 net_change_ratio,
+                lines_changed: lines_added + lines_deleted,
                 author_previous_commits: author_commits.log2().max(0.0),
                 days_since_last_commit: days_since_last.min(365.0) / 365.0,
                 author_activity_score: *activity_score,
@@ -227,10 +339,12 @@ pub fn prepare_commit_features(analysis_result: &AnalysisResult) -> Vec<CommitFe
     features
 }
 
+#[cfg(feature = "bert")]
 pub struct CommitAnalyzer {
     model: SequenceClassificationModel,
 }
 
+#[cfg(feature = "bert")]
 impl CommitAnalyzer {
     pub fn analyze_commits(&self, inputs: &[String]) -> Result<Vec<f32>> {
         let input_refs: Vec<&str> = inputs.iter().map(String::as_str).collect();
@@ -251,24 +365,67 @@ impl CommitAnalyzer {
     }
 }
 
+/// Flag commits whose size is unusually large, without the language model
+///
+/// Each commit's lines added plus deleted is compared to the mean over all commits;
+/// the z-score becomes the anomaly score and commits more than three standard
+/// deviations above the mean are anomalous. Simpler than
+/// [`StatisticalAnomalyDetector`], but a single huge commit raises the bar for the rest.
+pub fn detect_statistical_anomalies(result: &AnalysisResult) -> Vec<CommitFeatures> {
+    let sizes: Vec<f32> = chronological_activity(result)
+        .iter()
+        .map(|activity| (activity.lines_added + activity.lines_deleted) as f32)
+        .collect();
+    let count = sizes.len().max(1) as f32;
+    let mean = sizes.iter().sum::<f32>() / count;
+    let std_dev = (sizes.iter().map(|size| (size - mean).powi(2)).sum::<f32>() / count).sqrt();
+
+    let mut features = prepare_commit_features(result);
+    for (feature, size) in features.iter_mut().zip(sizes) {
+        // All commits the same size: nothing stands out
+        let z_score = if std_dev > 0.0 {
+            (size - mean) / std_dev
+        } else {
+            0.0
+        };
+        feature.anomaly_score = Some(z_score);
+        feature.is_anomalous = Some(z_score > Z_SCORE_THRESHOLD);
+    }
+    features
+}
+
+/// Flag unusually sized commits with [`StatisticalAnomalyDetector`]
+///
+/// Build with the `bert` feature to score commits with a language model instead.
+#[cfg(not(feature = "bert"))]
+pub async fn detect_anomalies(
+    result: &AnalysisResult,
+) -> Result<Vec<CommitFeatures>, AnomalyError> {
+    Ok(StatisticalAnomalyDetector::default().analyze(result))
+}
+
+/// Score commits with the language model
+///
+/// Returns [`AnomalyError::MlUnavailable`] instead of panicking when the model can't
+/// be loaded, e.g. offline.
+#[cfg(feature = "bert")]
 pub async fn detect_anomalies(
     result: &AnalysisResult,
-) -> Result<Vec<CommitFeatures>, RustBertError> {
+) -> Result<Vec<CommitFeatures>, AnomalyError> {
     let features = prepare_commit_features(result);
-    let features_clone = features.clone();
 
     // Run model operations in a blocking task
-    let features = tokio::task::spawn_blocking(move || {
-        let mut detector = AnomalyDetector::new()?;
-        let mut features = features_clone;
+    tokio::task::spawn_blocking(move || {
+        let mut detector =
+            AnomalyDetector::new().map_err(|e| AnomalyError::MlUnavailable(e.to_string()))?;
+        let mut features = features;
         detector.train(&features)?;
         detector.detect_anomalies(&mut features)?;
-        Ok::<_, RustBertError>(features)
+        Ok::<_, AnomalyError>(features)
     })
     .await
-    .unwrap()?;
-
-    Ok(features)
+    // Loading the model can panic inside its dependencies when files are missing
+    .map_err(|e| AnomalyError::MlUnavailable(e.to_string()))?
 }
 
 #[cfg(test)]
@@ -285,20 +442,31 @@ mod tests {
         // Add 95 normal commits
         for i in 1..96 {
             let lines = 10 + (i % 4) * 10; // Varies between 10-40 lines
-            commit_activity.push((
-                format!("2024-01-{:02}", i % 31 + 1),
+            commit_activity.push(CommitActivity::new(
+                &format!("2024-01-{:02}", i % 31 + 1),
                 lines as usize,
                 (lines / 2) as usize,
+                "test_author",
             ));
         }
 
         // Add 4 larger commits
         for i in 1..5 {
-            commit_activity.push((format!("2024-02-{:02}", i), 150 + i * 10, 75 + i * 5));
+            commit_activity.push(CommitActivity::new(
+                &format!("2024-02-{:02}", i),
+                150 + i * 10,
+                75 + i * 5,
+                "test_author",
+            ));
         }
 
         // Add 1 clearly anomalous commit
-        commit_activity.push(("2024-02-05".to_string(), 15000, 7500));
+        commit_activity.push(CommitActivity::new(
+            "2024-02-05",
+            15000,
+            7500,
+            "test_author",
+        ));
 
         AnalysisResult {
             commit_activity,
@@ -312,9 +480,11 @@ mod tests {
             top_contributors_by_lines: vec![],
             elapsed_time: 1.0,
             processing_stats: "test".to_string(),
+            ..Default::default()
         }
     }
 
+    #[cfg(feature = "bert")]
     #[tokio::test(flavor = "multi_thread")]
     async fn test_anomaly_detection() {
         let result = create_test_data();
@@ -348,6 +518,7 @@ mod tests {
         );
     }
 
+    #[cfg(feature = "bert")]
     #[tokio::test(flavor = "multi_thread")]
     async fn test_anomaly_detection_pipeline() {
         let result = create_test_data();
@@ -365,4 +536,76 @@ mod tests {
             "Expected 1-6 anomalies in 100 commits"
         );
     }
+
+    #[test]
+    fn test_statistical_anomaly_detection() {
+        let result = create_test_data();
+        let features = detect_statistical_anomalies(&result);
+
+        assert_eq!(features.len(), 100);
+        let anomalies: Vec<&CommitFeatures> = features
+            .iter()
+            .filter(|f| f.is_anomalous.unwrap())
+            .collect();
+        assert_eq!(
+            anomalies.len(),
+            1,
+            "Only the seeded large commit stands out"
+        );
+        assert_eq!(anomalies[0].lines_added, 15000f32.log2());
+    }
+
+    #[test]
+    fn test_statistical_detector() {
+        let result = create_test_data();
+        let mut detector = StatisticalAnomalyDetector::default();
+        let mut features = prepare_commit_features(&result);
+        detector.train(&features);
+        detector.detect_anomalies(&mut features);
+
+        // The last commit should be anomalous (it's our 22500-line commit)
+        assert!(
+            features[99].is_anomalous.unwrap(),
+            "Large commit should be detected as anomalous"
+        );
+
+        // The first commit should not be anomalous (it's a normal-sized commit)
+        assert!(
+            !features[0].is_anomalous.unwrap(),
+            "Small commit should not be anomalous"
+        );
+
+        // Count anomalies - should be roughly 5% of commits
+        let anomaly_count = features.iter().filter(|f| f.is_anomalous.unwrap()).count();
+        assert!(
+            (1..=6).contains(&anomaly_count),
+            "Expected 1-6 anomalies in 100 commits"
+        );
+
+        // A wider band leaves only the seeded commit
+        let mut detector = StatisticalAnomalyDetector::new(100.0);
+        detector.train(&features);
+        detector.detect_anomalies(&mut features);
+        let anomaly_count = features.iter().filter(|f| f.is_anomalous.unwrap()).count();
+        assert_eq!(anomaly_count, 1);
+    }
+
+    #[cfg(not(feature = "bert"))]
+    #[tokio::test]
+    async fn test_statistical_anomaly_pipeline() {
+        let result = create_test_data();
+        let anomalies = detect_anomalies(&result).await.unwrap();
+
+        assert_eq!(anomalies.len(), 100);
+
+        // The last commit should be anomalous
+        assert!(anomalies[99].is_anomalous.unwrap());
+
+        // Count anomalies - should be roughly 5% of commits
+        let anomaly_count = anomalies.iter().filter(|f| f.is_anomalous.unwrap()).count();
+        assert!(
+            (1..=6).contains(&anomaly_count),
+            "Expected 1-6 anomalies in 100 commits"
+        );
+    }
 }
@@ -1,7 +1,32 @@
+pub mod blame;
 mod cache;
+#[cfg(feature = "dataframe")]
+pub mod export;
 pub mod git;
+pub mod metrics;
 pub mod ml_pipeline;
+pub mod multi_repo;
+pub mod report;
 
+pub use blame::blame_ownership;
 pub use cache::CacheManager;
+#[cfg(feature = "dataframe")]
+pub use export::to_dataframe;
+pub use git::analyze_all_branches;
 pub use git::analyze_repo_async;
+pub use git::analyze_repo_with_options;
+pub use git::branch_head;
+pub use git::commit_diff_stats;
+pub use git::estimate_analysis_duration;
+pub use git::estimate_commit_count;
+pub use git::estimated_duration;
 pub use git::get_available_branches;
+pub use git::get_available_refs;
+pub use git::list_contributors;
+pub use git::working_tree_stats;
+pub use git::DEFAULT_COMMITS_PER_SECOND;
+pub use git::DETACHED_HEAD;
+pub use metrics::rolling_active_contributors;
+pub use multi_repo::analyze_repos;
+pub use multi_repo::extend_result;
+pub use report::export_markdown_summary;
@@ -0,0 +1,190 @@
+/// Module for analyzing several repositories at once and merging their results.
+/// Useful when a project is split across many small repositories (e.g. microservices).
+/// The same merge also extends a cached result with the commits made since.
+use crate::types::{AnalysisResult, MergeStrategy};
+use futures::future::try_join_all;
+use git2::Error;
+use std::path::Path;
+use std::time::Instant;
+
+use super::git::analyze_repo_async;
+
+/// Analyze several repositories concurrently and merge them into a single result
+///
+/// # Arguments
+/// * `paths` - Paths of the repositories to analyze
+/// * `branch` - Branch to analyze in every repository (falls back to HEAD)
+/// * `contributor` - Contributor filter, or "All"
+/// * `strategy` - How to combine contributors with the same name across repositories
+pub async fn analyze_repos(
+    paths: Vec<String>,
+    branch: String,
+    contributor: String,
+    strategy: MergeStrategy,
+) -> Result<AnalysisResult, Error> {
+    let start_time = Instant::now();
+
+    let labels: Vec<String> = paths.iter().map(|path| repo_label(path)).collect();
+    let results = try_join_all(
+        paths
+            .into_iter()
+            .map(|path| analyze_repo_async(path, branch.clone(), contributor.clone(), None)),
+    )
+    .await?;
+
+    let mut merged = merge_results(labels.into_iter().zip(results).collect(), strategy);
+    merged.elapsed_time = start_time.elapsed().as_secs_f64();
+    Ok(merged)
+}
+
+/// Short name used to label a repository's contributors, taken from its directory name
+fn repo_label(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string())
+}
+
+/// Extend an earlier result with one covering only the commits made since
+///
+/// `newer` is usually analyzed with [`AnalysisOptions::since_commit`] set to the commit
+/// `previous` was computed at. The two are merged with [`AnalysisResult::merge`],
+/// except that the branch list, timing and processing stats are taken from `newer`.
+///
+/// [`AnalysisOptions::since_commit`]: crate::types::AnalysisOptions::since_commit
+pub fn extend_result(previous: AnalysisResult, newer: AnalysisResult) -> AnalysisResult {
+    let mut merged = previous;
+    merged.merge(&newer);
+    merged.available_branches = newer.available_branches;
+    merged.elapsed_time = newer.elapsed_time;
+    merged.processing_stats = newer.processing_stats;
+    merged
+}
+
+/// Merge per-repository results, labelled with their repository name
+///
+/// Results are combined with [`AnalysisResult::merge`]; under
+/// [`MergeStrategy::KeepSeparate`] each contributor's name is first suffixed with
+/// their repository's label.
+fn merge_results(
+    results: Vec<(String, AnalysisResult)>,
+    strategy: MergeStrategy,
+) -> AnalysisResult {
+    let mut merged = AnalysisResult::default();
+    let mut processing_stats = Vec::with_capacity(results.len());
+
+    for (label, mut result) in results {
+        if strategy == MergeStrategy::KeepSeparate {
+            label_contributors(&mut result, &label);
+        }
+        merged.merge(&result);
+        processing_stats.push(format!("[{}]\n{}", label, result.processing_stats));
+    }
+
+    merged.processing_stats = processing_stats.join("\n");
+    merged
+}
+
+/// Suffix every contributor name in `result` with ` (label)`
+fn label_contributors(result: &mut AnalysisResult, label: &str) {
+    let name_for = |author: &str| format!("{} ({})", author, label);
+    for activity in &mut result.commit_activity {
+        activity.author = name_for(&activity.author);
+    }
+    for (author, _) in &mut result.top_contributors {
+        *author = name_for(author);
+    }
+    result.contributor_commit_counts = std::mem::take(&mut result.contributor_commit_counts)
+        .into_iter()
+        .map(|(author, count)| (name_for(&author), count))
+        .collect();
+    result.author_first_commit = std::mem::take(&mut result.author_first_commit)
+        .into_iter()
+        .map(|(author, date)| (name_for(&author), date))
+        .collect();
+    result.contributor_emails = std::mem::take(&mut result.contributor_emails)
+        .into_iter()
+        .map(|(author, email)| (name_for(&author), email))
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::CommitActivity;
+    use pretty_assertions::assert_eq;
+    use std::collections::HashMap;
+
+    fn result_with(
+        author: &str,
+        commits: usize,
+        activity: &[(&str, usize, usize)],
+    ) -> AnalysisResult {
+        AnalysisResult {
+            commit_count: commits,
+            total_lines_added: activity.iter().map(|(_, added, _)| added).sum(),
+            total_lines_deleted: activity.iter().map(|(_, _, deleted)| deleted).sum(),
+            top_contributors: vec![(author.to_string(), commits)],
+            contributor_commit_counts: HashMap::from([(author.to_string(), commits)]),
+            commit_activity: activity
+                .iter()
+                .map(|(date, added, deleted)| CommitActivity::new(date, *added, *deleted, author))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_merge_combines_same_names() {
+        let a = result_with("Alice", 2, &[("2023-02-01", 10, 0), ("2023-03-01", 5, 5)]);
+        let b = result_with("Alice", 1, &[("2023-01-15", 20, 2)]);
+
+        let merged = merge_results(
+            vec![("a".to_string(), a), ("b".to_string(), b)],
+            MergeStrategy::Combine,
+        );
+
+        assert_eq!(merged.top_contributors, vec![("Alice".to_string(), 3)]);
+        assert_eq!(merged.commit_activity[0].date, "2023-01-15");
+        assert_eq!(merged.commit_frequency.len(), 3);
+        assert_eq!(merged.average_commit_size, 42.0 / 3.0);
+    }
+
+    #[test]
+    fn test_merge_keeps_names_separate() {
+        let a = result_with("Alice", 2, &[("2023-02-01", 10, 0)]);
+        let b = result_with("Alice", 1, &[("2023-01-15", 20, 2)]);
+
+        let merged = merge_results(
+            vec![("a".to_string(), a), ("b".to_string(), b)],
+            MergeStrategy::KeepSeparate,
+        );
+
+        assert_eq!(
+            merged.top_contributors,
+            vec![("Alice (a)".to_string(), 2), ("Alice (b)".to_string(), 1)]
+        );
+    }
+
+    #[test]
+    fn test_extend_result_keeps_newer_stats() {
+        let previous = result_with("Alice", 2, &[("2023-01-01", 10, 0), ("2023-01-02", 5, 5)]);
+        let newer = AnalysisResult {
+            processing_stats: "Processed 1 commits".to_string(),
+            elapsed_time: 0.5,
+            ..result_with("Bob", 1, &[("2023-02-01", 20, 2)])
+        };
+
+        let extended = extend_result(previous, newer);
+
+        assert_eq!(extended.commit_count, 3);
+        assert_eq!(extended.total_lines_added, 35);
+        assert_eq!(extended.commit_activity.len(), 3);
+        assert_eq!(
+            extended.top_contributors,
+            vec![("Alice".to_string(), 2), ("Bob".to_string(), 1)]
+        );
+        assert_eq!(extended.processing_stats, "Processed 1 commits");
+        assert_eq!(extended.elapsed_time, 0.5);
+    }
+}
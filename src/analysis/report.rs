@@ -0,0 +1,64 @@
+/// Module for rendering an analysis result as text for sharing, e.g. in PR descriptions
+/// or wikis. Like the metrics module, it only reads the stored result.
+use crate::types::AnalysisResult;
+use std::fmt::Write;
+
+/// Render the headline numbers and top contributors as GitHub-flavored Markdown tables
+///
+/// Pipes in contributor names are escaped so they can't break the table layout.
+pub fn export_markdown_summary(result: &AnalysisResult) -> String {
+    let summary = result.summary();
+    let mut markdown = String::from("| Metric | Value |\n| --- | ---: |\n");
+    let rows = [
+        ("Commits", summary.commits.to_string()),
+        ("Lines added", summary.added.to_string()),
+        ("Lines deleted", summary.deleted.to_string()),
+        ("Average commit size", format!("{:.1}", summary.avg_size)),
+        ("Contributors", summary.contributors.to_string()),
+        ("Active span (days)", summary.span_days.to_string()),
+    ];
+    for (metric, value) in rows {
+        // Writing to a String can't fail
+        let _ = writeln!(markdown, "| {} | {} |", metric, value);
+    }
+
+    markdown.push_str("\n| Contributor | Commits |\n| --- | ---: |\n");
+    for (author, commits) in &result.top_contributors {
+        let _ = writeln!(markdown, "| {} | {} |", author.replace('|', "\\|"), commits);
+    }
+    markdown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_export_markdown_summary() {
+        let result = AnalysisResult {
+            commit_count: 5,
+            total_lines_added: 120,
+            top_contributors: vec![
+                ("Alice".to_string(), 3),
+                ("Bob".to_string(), 1),
+                ("Carol | Ops".to_string(), 1),
+            ],
+            ..Default::default()
+        };
+
+        let markdown = export_markdown_summary(&result);
+
+        assert!(markdown.contains("| Commits | 5 |"));
+        assert!(markdown.contains("| Lines added | 120 |"));
+        let contributor_rows: Vec<&str> = markdown
+            .lines()
+            .skip_while(|line| *line != "| Contributor | Commits |")
+            .skip(2)
+            .collect();
+        assert_eq!(
+            contributor_rows,
+            vec!["| Alice | 3 |", "| Bob | 1 |", "| Carol \\| Ops | 1 |"]
+        );
+    }
+}
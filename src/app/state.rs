@@ -1,12 +1,20 @@
 use eframe::App as EApp;
 use egui::TextureHandle;
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::runtime::Handle;
 use tokio::sync::mpsc;
+use tokio::task::AbortHandle;
+use tokio_util::task::TaskTracker;
 
 use crate::analysis::ml_pipeline::CommitFeatures;
+use crate::analysis::{CacheManager, DETACHED_HEAD};
+use crate::plotting::chart::PlotSource;
+use crate::plotting::{ChartStyle, ColorMode, LegendPosition};
 
-use crate::types::{AnalysisResult, CacheKey, ProgressEstimate};
+use crate::types::{AnalysisOptions, AnalysisResult, CacheKey, CommitActivity, ProgressEstimate};
 
 /// Main application state
 #[derive(Clone)]
@@ -17,9 +25,20 @@ pub struct App {
     pub total_lines_deleted: usize,
     pub top_contributors: Vec<(String, usize)>,
     pub all_contributors: Vec<(String, usize)>,
-    pub commit_activity: Vec<(String, usize, usize)>,
+    pub commit_activity: Vec<CommitActivity>,
+    /// Where plots are rendered before being loaded as a texture; each plot writes its
+    /// own file next to this path and removes it once loaded
     pub plot_path: String,
+    /// Absolute path the chart was last saved to, shown so the user can find it
+    pub saved_plot_path: Option<PathBuf>,
     pub plot_texture: Option<TextureHandle>,
+    /// Incremented for every plot request, so a plot that finishes after a newer one
+    /// was requested can be recognised as stale and dropped
+    pub plot_generation: u64,
+    /// Whether the latest plot request is still rendering
+    pub is_plotting: bool,
+    /// Where the displayed plot came from, once one has finished
+    pub last_plot_source: Option<PlotSource>,
     pub current_metric: String,
     pub average_commit_size: f64,
     pub commit_frequency: HashMap<String, usize>,
@@ -27,17 +46,44 @@ pub struct App {
     pub update_needed: bool,
     pub is_analyzing: bool,
     pub use_log_scale: bool,
+    /// Commits changing fewer lines than this are left out of the charts
+    pub min_commit_size: usize,
+    /// Device pixel ratio plots are rendered at, so they stay sharp on high-DPI displays
+    pub pixels_per_point: f32,
+    /// Charts are rendered this many times larger, then scaled down for smoother lines;
+    /// 1 turns supersampling off
+    pub supersample: u32,
+    /// Roughly how many horizontal grid lines charts draw
+    pub grid_divisions: usize,
+    /// strftime-style format for dates on chart axes, e.g. `%d/%m/%Y`
+    pub date_format: String,
+    /// Font family and size used for chart text
+    pub chart_style: ChartStyle,
+    /// Corner of the chart the legend is drawn in
+    pub legend_position: LegendPosition,
+    /// Color space bitmap charts are rendered in
+    pub color_mode: ColorMode,
     pub selected_branch: String,
     pub selected_contributor: String,
     pub available_branches: Vec<String>,
-    pub analysis_cache: HashMap<CacheKey, AnalysisResult>,
+    pub analysis_cache: CacheManager,
     pub last_analysis_time: Option<f64>,
     pub commits_per_second: Option<f64>,
+    /// How long the running analysis is expected to take, from a quick commit count
+    pub estimated_duration: Option<Duration>,
     pub processing_stats: String,
     pub analysis_result: Option<AnalysisResult>,
     pub error_message: Option<String>,
     pub progress: Option<ProgressEstimate>,
     pub anomalies: Option<Vec<CommitFeatures>>,
+    /// Why the anomaly detection model couldn't be loaded, if it failed; anomalies are
+    /// then found from commit sizes instead
+    pub ml_unavailable: Option<String>,
+    pub analysis_options: AnalysisOptions,
+    /// Handle to the background analysis task, so it can be cancelled
+    pub analysis_task: Option<AbortHandle>,
+    /// Every background task the UI spawns, so they can be waited for on exit
+    pub tasks: TaskTracker,
 }
 
 impl App {
@@ -61,12 +107,17 @@ impl App {
             }
         }
 
+        // Without a branch to show, select the entry standing for the detached HEAD
+        if result.selected_branch == DETACHED_HEAD {
+            self.selected_branch = DETACHED_HEAD.to_string();
+        }
+
         // Cache the result using both branch and contributor
         let cache_key = CacheKey {
             branch: self.selected_branch.clone(),
             contributor: self.selected_contributor.clone(),
         };
-        self.analysis_cache.insert(cache_key, result.clone());
+        self.analysis_cache.store(cache_key, result.clone());
 
         // Update performance metrics
         self.last_analysis_time = Some(result.elapsed_time);
@@ -96,6 +147,63 @@ impl App {
         self.analysis_cache.get(&cache_key).cloned()
     }
 
+    /// Whether the selected branch and contributor can be shown without analyzing,
+    /// either from their own cached result or by filtering the branch's cached "All"
+    /// result, e.g. to offer "Refresh" rather than "Analyze"
+    pub fn is_current_selection_cached(&self) -> bool {
+        let key = |contributor: &str| CacheKey {
+            branch: self.selected_branch.clone(),
+            contributor: contributor.to_string(),
+        };
+        self.analysis_cache
+            .contains(&key(&self.selected_contributor))
+            || self
+                .analysis_cache
+                .peek(&key("All"))
+                .is_some_and(AnalysisResult::has_complete_activity)
+    }
+
+    /// Clear all analysis results and the cache so a fresh repository can be analyzed
+    ///
+    /// The repository path and display preferences (metric, log scale, minimum commit
+    /// size, pixel ratio, grid density, date format, chart style, legend position, color
+    /// mode and analysis options) are kept. A running analysis is cancelled first, so it
+    /// can't repopulate the cleared state.
+    pub fn reset(&mut self) {
+        self.cancel_analysis();
+        *self = App {
+            repo_path: std::mem::take(&mut self.repo_path),
+            plot_path: std::mem::take(&mut self.plot_path),
+            // Plots still rendering for the old repository must stay stale
+            plot_generation: self.plot_generation,
+            current_metric: std::mem::take(&mut self.current_metric),
+            use_log_scale: self.use_log_scale,
+            min_commit_size: self.min_commit_size,
+            pixels_per_point: self.pixels_per_point,
+            supersample: self.supersample,
+            grid_divisions: self.grid_divisions,
+            date_format: std::mem::take(&mut self.date_format),
+            chart_style: std::mem::take(&mut self.chart_style),
+            legend_position: self.legend_position,
+            color_mode: self.color_mode,
+            // The measured rate describes this machine, so later estimates still use it
+            commits_per_second: self.commits_per_second,
+            ml_unavailable: self.ml_unavailable.take(),
+            analysis_options: std::mem::take(&mut self.analysis_options),
+            // Plots for the old repository may still be rendering
+            tasks: self.tasks.clone(),
+            ..Default::default()
+        };
+    }
+
+    /// Save the current chart to `path`, as PNG or SVG depending on its extension
+    pub fn save_plot_as(
+        &self,
+        path: &Path,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        crate::plotting::chart::save_plot(self, path)
+    }
+
     pub fn get_cache_key(&self) -> String {
         format!("{}:{}", self.selected_branch, self.selected_contributor)
     }
@@ -107,29 +215,89 @@ impl App {
         impl std::future::Future<Output = Result<AnalysisResult, git2::Error>>,
     ) {
         let (tx, rx) = mpsc::channel(32);
-        let future = crate::analysis::analyze_repo_async(
+        let future = crate::analysis::analyze_repo_with_options(
             self.repo_path.clone(),
             self.selected_branch.clone(),
             self.selected_contributor.clone(),
+            self.analysis_options.clone(),
             Some(tx),
         );
         (rx, future)
     }
 
-    pub fn update_progress(&mut self, progress: ProgressEstimate) {
+    /// Record a progress update, folding in any partial results it carries
+    ///
+    /// Partial activity is appended to the timeline and the running totals replace the
+    /// displayed counts, so the chart and commit count grow while analysis runs. The
+    /// final result replaces both once analysis completes.
+    pub fn update_progress(&mut self, mut progress: ProgressEstimate) {
+        // A late update must not fold partial activity into an already-final result
+        if let Some(partial) = progress.partial.as_mut().filter(|_| self.is_analyzing) {
+            if progress.processed_commits == 0 {
+                self.commit_activity.clear();
+            }
+            self.commit_count = partial.commit_count;
+            self.total_lines_added = partial.lines_added;
+            self.total_lines_deleted = partial.lines_deleted;
+            if !partial.activity.is_empty() {
+                self.commit_activity
+                    .extend(std::mem::take(&mut partial.activity));
+                self.update_needed = true;
+            }
+        }
         self.progress = Some(progress);
     }
 
     pub fn format_progress(&self) -> Option<String> {
         self.progress.as_ref().map(|p| {
-            format!(
+            let mut text = format!(
                 "{:.1}% complete ({}/{} commits)\nEstimated time remaining: {:.1}s\nCommits/sec: {:.1}",
                 p.percent_complete(),
                 p.processed_commits,
                 p.total_commits,
                 p.estimated_remaining_time(),
                 p.commits_per_second
-            )
+            );
+            if let Some(date) = &p.current_date {
+                text.push_str(&format!("\nProcessing commits from {}", date));
+            }
+            text
+        })
+    }
+
+    /// Abort the background analysis, if one is running, and clear its progress
+    ///
+    /// Diffs already handed to blocking threads finish, but their results are dropped.
+    pub fn cancel_analysis(&mut self) {
+        if let Some(task) = self.analysis_task.take() {
+            task.abort();
+        }
+        self.is_analyzing = false;
+        self.progress = None;
+    }
+
+    /// Start a new plot request, returning its generation
+    pub fn next_plot_generation(&mut self) -> u64 {
+        self.plot_generation += 1;
+        self.plot_generation
+    }
+
+    /// Whether a plot started with `generation` is still the latest request
+    ///
+    /// Plots render concurrently, so one requested for an earlier metric or setting
+    /// can finish last; it must not replace the texture of the newer request.
+    pub fn is_current_plot(&self, generation: u64) -> bool {
+        generation == self.plot_generation
+    }
+
+    /// Status line describing the chart, or `None` before any chart was requested
+    pub fn plot_status(&self) -> Option<&'static str> {
+        if self.is_plotting {
+            return Some("Rendering chart...");
+        }
+        self.last_plot_source.map(|source| match source {
+            PlotSource::Cache => "Chart loaded from cache",
+            PlotSource::Rendered => "Chart rendered",
         })
     }
 
@@ -149,8 +317,16 @@ impl Default for App {
             top_contributors: Vec::new(),
             all_contributors: Vec::new(),
             commit_activity: Vec::new(),
-            plot_path: "commit_activity.png".to_string(),
+            // Keep scratch files out of the working directory
+            plot_path: std::env::temp_dir()
+                .join(format!("gitstats-{}.png", std::process::id()))
+                .to_string_lossy()
+                .into_owned(),
+            saved_plot_path: None,
             plot_texture: None,
+            plot_generation: 0,
+            is_plotting: false,
+            last_plot_source: None,
             current_metric: "Commits".to_string(),
             average_commit_size: 0.0,
             commit_frequency: HashMap::new(),
@@ -158,32 +334,100 @@ impl Default for App {
             update_needed: false,
             is_analyzing: false,
             use_log_scale: false,
+            min_commit_size: 0,
+            pixels_per_point: 1.0,
+            supersample: 1,
+            grid_divisions: 10,
+            date_format: crate::plotting::chart::DEFAULT_DATE_FORMAT.to_string(),
+            chart_style: ChartStyle::default(),
+            legend_position: LegendPosition::default(),
+            color_mode: ColorMode::default(),
             selected_branch: "main".to_string(),
             selected_contributor: "All".to_string(),
             available_branches: Vec::new(),
-            analysis_cache: HashMap::new(),
+            analysis_cache: CacheManager::new(),
             last_analysis_time: None,
             commits_per_second: None,
+            estimated_duration: None,
             processing_stats: String::new(),
             analysis_result: None,
             error_message: None,
             progress: None,
             anomalies: None,
+            ml_unavailable: None,
+            analysis_options: AnalysisOptions {
+                stream_partials: true,
+                ..Default::default()
+            },
+            analysis_task: None,
+            tasks: TaskTracker::new(),
         }
     }
 }
 
 /// Thread-safe wrapper around App for use with eframe
+///
+/// Analysis and plotting run as tokio tasks on `runtime`, which must be a
+/// multi-threaded runtime: eframe's event loop blocks the UI thread, so a
+/// current-thread runtime driven from it would never run them.
 pub struct AppWrapper {
     pub app: Arc<Mutex<App>>,
+    /// Runtime background tasks are spawned on
+    pub runtime: Handle,
+}
+
+/// How long closing the window waits for background tasks to finish
+const EXIT_TIMEOUT: Duration = Duration::from_secs(2);
+
+impl AppWrapper {
+    /// Wrap `app`, spawning background tasks on the runtime this is called from
+    ///
+    /// # Panics
+    /// Panics when called outside a tokio runtime; use [`AppWrapper::with_runtime`]
+    /// to pass a runtime explicitly.
+    pub fn new(app: Arc<Mutex<App>>) -> Self {
+        Self::with_runtime(app, Handle::current())
+    }
+
+    /// Wrap `app`, spawning background tasks on `runtime`
+    pub fn with_runtime(app: Arc<Mutex<App>>, runtime: Handle) -> Self {
+        Self { app, runtime }
+    }
 }
 
 impl EApp for AppWrapper {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        // eframe may call this from a thread the runtime doesn't know about
+        let _runtime = self.runtime.enter();
         if let Ok(mut app) = self.app.lock() {
             super::ui::draw_ui(&mut app, ctx, Arc::clone(&self.app));
         } else {
             eprintln!("Failed to acquire app lock in update");
         }
     }
+
+    /// Stop any in-flight analysis and wait briefly for background tasks to finish,
+    /// so none of them outlives the window
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        let tasks = match self.app.lock() {
+            Ok(mut app) => {
+                app.cancel_analysis();
+                app.tasks.clone()
+            }
+            Err(_) => return,
+        };
+        tasks.close();
+
+        // eframe runs inside the runtime's `block_on`, where blocking on it again
+        // panics, so wait from a thread of our own. The app lock is released above,
+        // since the tasks need it to finish.
+        let runtime = self.runtime.clone();
+        let waited = std::thread::spawn(move || {
+            runtime.block_on(tokio::time::timeout(EXIT_TIMEOUT, tasks.wait()))
+        })
+        .join();
+        if !matches!(waited, Ok(Ok(()))) {
+            eprintln!("Background tasks were still running on exit");
+        }
+    }
 }
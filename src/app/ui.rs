@@ -5,20 +5,39 @@ use std::sync::{Arc, Mutex};
 use tokio::sync::mpsc;
 
 use super::App;
-use crate::analysis::analyze_repo_async;
-use crate::analysis::ml_pipeline::detect_anomalies;
+use crate::analysis::analyze_repo_with_options;
+use crate::analysis::estimate_analysis_duration;
+use crate::analysis::ml_pipeline::{detect_anomalies, AnomalyError, StatisticalAnomalyDetector};
+use crate::plotting::{ColorMode, LegendPosition};
 
 /// Draw the main application UI
 pub fn draw_ui(app: &mut App, ctx: &Context, app_arc: Arc<Mutex<App>>) {
+    // Re-render the plot when the window moves to a display with a different pixel ratio
+    if ctx.pixels_per_point() != app.pixels_per_point {
+        app.pixels_per_point = ctx.pixels_per_point();
+        app.update_needed = true;
+    }
+
+    // Set when Enter is pressed in the repository path field
+    let mut path_submitted = false;
+
     egui::SidePanel::left("side_panel").show(ctx, |ui| {
         ui.heading("Analysis Options");
         ui.separator();
 
         // Repository path input
-        ui.horizontal(|ui| {
-            ui.label("Repository Path:");
-            ui.text_edit_singleline(&mut app.repo_path);
-        });
+        path_submitted = ui
+            .horizontal(|ui| {
+                ui.label("Repository Path:");
+                let response = ui.text_edit_singleline(&mut app.repo_path);
+                // A single-line field gives up focus when Enter is pressed
+                is_path_submitted(
+                    response.lost_focus(),
+                    ui.input(|i| i.key_pressed(egui::Key::Enter)),
+                    app.is_analyzing,
+                )
+            })
+            .inner;
 
         // Branch and contributor selection
         if !app.available_branches.is_empty() {
@@ -71,67 +90,187 @@ pub fn draw_ui(app: &mut App, ctx: &Context, app_arc: Arc<Mutex<App>>) {
             app.current_metric = "Code Frequency".to_string();
             app.update_needed = true;
         }
+        if ui.button("Code Area").clicked() {
+            app.current_metric = "Code Area".to_string();
+            app.update_needed = true;
+        }
+        if ui.button("Commit Types").clicked() {
+            app.current_metric = "Commit Types".to_string();
+            app.update_needed = true;
+        }
+        if ui.button("Commits by Year").clicked() {
+            app.current_metric = "Commits by Year".to_string();
+            app.update_needed = true;
+        }
+        if ui.button("Size Histogram").clicked() {
+            app.current_metric = "Size Histogram".to_string();
+            app.update_needed = true;
+        }
+        if ui.button("Language Churn").clicked() {
+            app.current_metric = "Language Churn".to_string();
+            app.update_needed = true;
+        }
+        if ui.button("Commit Size Distribution").clicked() {
+            app.current_metric = "Commit Size Distribution".to_string();
+            app.update_needed = true;
+        }
+        if ui.button("Contributor Comparison").clicked() {
+            app.current_metric = "Contributor Comparison".to_string();
+            app.update_needed = true;
+        }
+        if ui.button("Calendar").clicked() {
+            app.current_metric = "Calendar".to_string();
+            app.update_needed = true;
+        }
+        if ui.button("Avg Commit Size").clicked() {
+            app.current_metric = "Avg Commit Size".to_string();
+            app.update_needed = true;
+        }
 
         ui.separator();
         ui.checkbox(&mut app.use_log_scale, "Log Scale");
+        if ui
+            .add(egui::Slider::new(&mut app.min_commit_size, 0..=100).text("Min Commit Size"))
+            .changed()
+        {
+            app.update_needed = true;
+        }
+        if ui
+            .add(egui::Slider::new(&mut app.supersample, 1..=4).text("Supersampling"))
+            .changed()
+        {
+            app.update_needed = true;
+        }
+        if ui
+            .add(egui::Slider::new(&mut app.grid_divisions, 2..=20).text("Grid Lines"))
+            .changed()
+        {
+            app.update_needed = true;
+        }
+        ui.horizontal(|ui| {
+            ui.label("Date Format:");
+            if ui.text_edit_singleline(&mut app.date_format).changed() {
+                app.update_needed = true;
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Legend:");
+            let prev_position = app.legend_position;
+            egui::ComboBox::new("legend_position_selector", "")
+                .selected_text(app.legend_position.label())
+                .show_ui(ui, |ui| {
+                    for position in LegendPosition::ALL {
+                        ui.selectable_value(&mut app.legend_position, position, position.label());
+                    }
+                });
+            if prev_position != app.legend_position {
+                app.update_needed = true;
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Colors:");
+            let prev_mode = app.color_mode;
+            egui::ComboBox::new("color_mode_selector", "")
+                .selected_text(app.color_mode.label())
+                .show_ui(ui, |ui| {
+                    for mode in ColorMode::ALL {
+                        ui.selectable_value(&mut app.color_mode, mode, mode.label());
+                    }
+                });
+            if prev_mode != app.color_mode {
+                app.update_needed = true;
+            }
+        });
+
+        // Cached results were counted with the previous setting, so drop them on change
+        if ui
+            .checkbox(
+                &mut app.analysis_options.ignore_whitespace,
+                "Ignore Whitespace",
+            )
+            .changed()
+        {
+            app.analysis_cache.clear();
+        }
+        if ui
+            .checkbox(
+                &mut app.analysis_options.normalize_line_endings,
+                "Ignore Line Endings",
+            )
+            .changed()
+        {
+            app.analysis_cache.clear();
+        }
+        if ui
+            .checkbox(
+                &mut app.analysis_options.count_coauthors,
+                "Credit Co-authors",
+            )
+            .changed()
+        {
+            app.analysis_cache.clear();
+        }
+        if ui
+            .checkbox(
+                &mut app.analysis_options.skip_initial_commit,
+                "Skip Initial Commit",
+            )
+            .changed()
+        {
+            app.analysis_cache.clear();
+        }
+        if ui
+            .checkbox(&mut app.analysis_options.code_only, "Code Commits Only")
+            .changed()
+        {
+            app.analysis_cache.clear();
+        }
+        if ui
+            .checkbox(
+                &mut app.analysis_options.exclude_empty_commits,
+                "Exclude Empty Commits",
+            )
+            .changed()
+        {
+            app.analysis_cache.clear();
+        }
+        // The branch list is only filled in by a fresh analysis
+        if ui
+            .checkbox(&mut app.analysis_options.include_tags, "Include Tags")
+            .changed()
+        {
+            app.analysis_cache.clear();
+            app.available_branches.clear();
+        }
 
-        // Performance metrics
+        // Performance metrics, e.g. to compare throughput with the benchmarks
         if let Some(analysis_time) = app.last_analysis_time {
             ui.separator();
-            ui.heading("Performance Metrics");
-            ui.label(format!("Analysis Time: {:.2}s", analysis_time));
-            if let Some(commits_per_sec) = app.commits_per_second {
-                ui.label(format!("Commits/sec: {:.1}", commits_per_sec));
-            }
-            if !app.processing_stats.is_empty() {
-                ui.label(&app.processing_stats);
-            }
+            egui::CollapsingHeader::new("Performance")
+                .default_open(true)
+                .show(ui, |ui| {
+                    ui.label(performance_summary(
+                        analysis_time,
+                        app.commits_per_second,
+                        &app.processing_stats,
+                    ));
+                    let (hits, misses) = app.analysis_cache.stats();
+                    ui.label(format!("Cache: {} hits, {} misses", hits, misses));
+                });
         }
     });
 
     egui::CentralPanel::default().show(ctx, |ui| {
         ui.heading("Git Repository Analysis");
 
-        // Analyze button
-        if ui.button("Analyze Repository").clicked() && !app.is_analyzing {
-            app.is_analyzing = true;
-            app.error_message = None;
-            let repo_path = app.repo_path.clone();
-            let selected_branch = app.selected_branch.clone();
-            let selected_contributor = app.selected_contributor.clone();
-            let app_clone = app_arc.clone();
-
-            tokio::spawn(async move {
-                let (tx, mut rx) = mpsc::channel(32);
-                let analyze_future =
-                    analyze_repo_async(repo_path, selected_branch, selected_contributor, Some(tx));
-
-                // Spawn a task to handle progress updates
-                let progress_app = app_clone.clone();
-                tokio::spawn(async move {
-                    while let Some(progress) = rx.recv().await {
-                        if let Ok(mut app) = progress_app.lock() {
-                            app.update_progress(progress);
-                        }
-                    }
-                });
+        // Clear results before analyzing a different repository
+        if ui.button("Clear").clicked() && !app.is_analyzing {
+            app.reset();
+        }
 
-                // Wait for analysis to complete
-                match analyze_future.await {
-                    Ok(result) => {
-                        if let Ok(mut app) = app_clone.lock() {
-                            app.update_with_result(result);
-                            app.is_analyzing = false;
-                        }
-                    }
-                    Err(e) => {
-                        if let Ok(mut app) = app_clone.lock() {
-                            app.error_message = Some(e.to_string());
-                            app.is_analyzing = false;
-                        }
-                    }
-                }
-            });
+        // Analyze button, or Enter in the repository path field
+        if (ui.button("Analyze Repository").clicked() || path_submitted) && !app.is_analyzing {
+            start_analysis(app, app_arc.clone());
         }
 
         // Show progress if available
@@ -144,6 +283,15 @@ pub fn draw_ui(app: &mut App, ctx: &Context, app_arc: Arc<Mutex<App>>) {
                 "Processing {} commits ({:.1} commits/sec)",
                 progress.processed_commits, progress.commits_per_second
             ));
+            if let Some(date) = &progress.current_date {
+                ui.label(format!("Processing commits from {}", date));
+            }
+            if let Some(partial) = &progress.partial {
+                ui.label(format!(
+                    "Found so far: {} commits (+{} / -{} lines)",
+                    partial.commit_count, partial.lines_added, partial.lines_deleted
+                ));
+            }
             ui.label(format!(
                 "Estimated time remaining: {:.1} seconds",
                 progress.estimated_remaining_time()
@@ -151,6 +299,9 @@ pub fn draw_ui(app: &mut App, ctx: &Context, app_arc: Arc<Mutex<App>>) {
         }
 
         if app.is_analyzing {
+            if let Some(estimate) = app.estimated_duration {
+                ui.label(format!("Estimated: ~{}s", estimate.as_secs_f64().ceil()));
+            }
             ui.spinner();
         }
 
@@ -162,17 +313,32 @@ pub fn draw_ui(app: &mut App, ctx: &Context, app_arc: Arc<Mutex<App>>) {
         ui.separator();
 
         // Show results if available
+        // Found from commit sizes when the model is unavailable; stored once `result`
+        // is no longer borrowed
+        let mut statistical_anomalies = None;
         if let Some(result) = &app.analysis_result {
+            let summary = result.summary();
             ui.heading("Analysis Results");
-            ui.label(format!("Total Commits: {}", result.commit_count));
+            ui.label(format!("Total Commits: {}", summary.commits));
+            if result.approximate {
+                ui.label("Totals estimated from a sample of the commits");
+            }
             ui.label(format!(
                 "Lines Added/Deleted: +{}/−{}",
-                result.total_lines_added, result.total_lines_deleted
+                summary.added, summary.deleted
             ));
             ui.label(format!(
                 "Average Commit Size: {:.1} lines",
-                result.average_commit_size
+                summary.avg_size
             ));
+            ui.label(format!("Contributors: {}", summary.contributors));
+            ui.label(format!("Active Span: {} days", summary.span_days));
+            if result.empty_commit_count > 0 {
+                ui.label(format!("Empty Commits: {}", result.empty_commit_count));
+            }
+            if let Some(language) = &result.primary_language {
+                ui.label(format!("Primarily {}", language));
+            }
 
             ui.heading("Top Contributors");
             for (author, count) in &result.top_contributors {
@@ -181,11 +347,23 @@ pub fn draw_ui(app: &mut App, ctx: &Context, app_arc: Arc<Mutex<App>>) {
 
             // Add Anomaly Detection section
             ui.heading("Anomaly Detection");
-            if ui.button("Detect Anomalies").clicked() && !app.is_analyzing {
+            if let Some(reason) = &app.ml_unavailable {
+                ui.label(
+                    egui::RichText::new(format!(
+                        "ML model unavailable, using commit size statistics instead ({})",
+                        reason
+                    ))
+                    .weak(),
+                );
+            }
+            let detect_clicked = ui.button("Detect Anomalies").clicked() && !app.is_analyzing;
+            if detect_clicked && app.ml_unavailable.is_some() {
+                statistical_anomalies = Some(StatisticalAnomalyDetector::default().analyze(result));
+            } else if detect_clicked {
                 let app_clone = app_arc.clone();
                 let result_clone = result.clone();
 
-                tokio::spawn(async move {
+                app.tasks.spawn(async move {
                     // Set analyzing flag
                     {
                         let mut app = app_clone.lock().unwrap();
@@ -200,7 +378,17 @@ pub fn draw_ui(app: &mut App, ctx: &Context, app_arc: Arc<Mutex<App>>) {
                                 app.is_analyzing = false;
                             }
                         }
-                        Err(e) => {
+                        Err(AnomalyError::MlUnavailable(reason)) => {
+                            if let Ok(mut app) = app_clone.lock() {
+                                app.ml_unavailable = Some(reason);
+                                app.update_anomalies(
+                                    StatisticalAnomalyDetector::default().analyze(&result_clone),
+                                );
+                                app.is_analyzing = false;
+                            }
+                        }
+                        #[cfg(feature = "bert")]
+                        Err(e @ AnomalyError::Model(_)) => {
                             if let Ok(mut app) = app_clone.lock() {
                                 app.error_message =
                                     Some(format!("Anomaly detection failed: {}", e));
@@ -239,25 +427,87 @@ pub fn draw_ui(app: &mut App, ctx: &Context, app_arc: Arc<Mutex<App>>) {
                 }
             }
         }
+        if let Some(anomalies) = statistical_anomalies {
+            app.update_anomalies(anomalies);
+        }
 
         // Show plot
+        if let Some(status) = app.plot_status() {
+            ui.horizontal(|ui| {
+                if app.is_plotting {
+                    ui.spinner();
+                }
+                ui.label(status);
+            });
+        }
         if let Some(texture) = &app.plot_texture {
-            ui.image(texture);
+            // The texture is rendered at the pixel ratio, so display it at its logical size
+            let (width, height) = crate::plotting::chart::PLOT_SIZE;
+            ui.image((texture.id(), egui::vec2(width as f32, height as f32)));
+
+            if ui.button("Save chart...").clicked() {
+                // The dialog stays open until the user picks a file, so show it from a
+                // task rather than holding the app lock (and freezing the UI) meanwhile
+                let dialog = rfd::AsyncFileDialog::new()
+                    .add_filter("PNG image", &["png"])
+                    .add_filter("SVG image", &["svg"])
+                    .set_file_name("commit_activity.png")
+                    .save_file();
+                let app_clone = app_arc.clone();
+                let ctx = ctx.clone();
+                app.tasks.spawn(async move {
+                    let Some(file) = dialog.await else {
+                        return;
+                    };
+                    let path = file.path().to_path_buf();
+                    // Render from a snapshot, so the lock is only held to store the outcome
+                    let Ok(snapshot) = app_clone.lock().map(|app| app.clone()) else {
+                        return;
+                    };
+                    // Rendering and writing the file block, so keep them off the runtime
+                    let target = path.clone();
+                    let saved = tokio::task::spawn_blocking(move || {
+                        snapshot.save_plot_as(&target).map_err(|e| e.to_string())
+                    })
+                    .await
+                    .unwrap_or_else(|e| Err(e.to_string()));
+                    if let Ok(mut app) = app_clone.lock() {
+                        match saved {
+                            Ok(()) => {
+                                app.saved_plot_path =
+                                    Some(std::fs::canonicalize(&path).unwrap_or(path))
+                            }
+                            Err(e) => {
+                                app.error_message = Some(format!("Failed to save chart: {}", e));
+                            }
+                        }
+                    }
+                    ctx.request_repaint();
+                });
+            }
+            if let Some(path) = &app.saved_plot_path {
+                ui.label(format!("Chart saved to {}", path.display()));
+            }
         }
 
         // Update plot if needed
         if app.update_needed {
             app.update_needed = false;
+            let generation = app.next_plot_generation();
+            app.is_plotting = true;
             let app_clone = app_arc.clone();
             let ctx = ctx.clone();
 
             // Drop the mutex guard before spawning the async task
             let app_data = app.clone();
-            tokio::spawn(async move {
-                if let Ok(plot_data) = crate::plotting::generate_plot_async(app_data).await {
+            let (width, height) = crate::plotting::chart::plot_dimensions(app.pixels_per_point);
+            let (width, height) = (width as usize, height as usize);
+            app.tasks.spawn(async move {
+                let mut rendered = None;
+                if let Ok((plot_data, source)) =
+                    crate::plotting::chart::generate_plot_with_source(app_data).await
+                {
                     // The plot data should be in RGBA format, where each pixel is 4 bytes
-                    let width = 640; // Fixed width
-                    let height = 480; // Fixed height
                     let expected_size = width * height * 4; // 4 bytes per pixel (RGBA)
 
                     if plot_data.len() == expected_size {
@@ -266,11 +516,7 @@ pub fn draw_ui(app: &mut App, ctx: &Context, app_arc: Arc<Mutex<App>>) {
                             egui::ColorImage::from_rgba_unmultiplied([width, height], &plot_data),
                             egui::TextureOptions::LINEAR,
                         );
-
-                        // Only lock the mutex when updating the texture
-                        if let Ok(mut app) = app_clone.lock() {
-                            app.plot_texture = Some(texture);
-                        }
+                        rendered = Some((texture, source));
                     } else {
                         eprintln!(
                             "Invalid plot data size: got {} bytes, expected {} bytes",
@@ -279,6 +525,18 @@ pub fn draw_ui(app: &mut App, ctx: &Context, app_arc: Arc<Mutex<App>>) {
                         );
                     }
                 }
+
+                // Only lock the mutex when updating the texture, and skip plots
+                // superseded by a newer request
+                if let Ok(mut app) = app_clone.lock() {
+                    if app.is_current_plot(generation) {
+                        app.is_plotting = false;
+                        if let Some((texture, source)) = rendered {
+                            app.plot_texture = Some(texture);
+                            app.last_plot_source = Some(source);
+                        }
+                    }
+                }
             });
         }
     });
@@ -287,6 +545,100 @@ pub fn draw_ui(app: &mut App, ctx: &Context, app_arc: Arc<Mutex<App>>) {
     ctx.request_repaint();
 }
 
+/// Whether the repository path field was just submitted with Enter
+///
+/// Kept free of egui types so the trigger condition can be unit tested.
+fn is_path_submitted(lost_focus: bool, enter_pressed: bool, is_analyzing: bool) -> bool {
+    lost_focus && enter_pressed && !is_analyzing
+}
+
+/// Text of the "Performance" section: timing, throughput and the analysis's own
+/// processing stats (chunk size, task count and so on), one per line
+fn performance_summary(
+    analysis_time: f64,
+    commits_per_second: Option<f64>,
+    processing_stats: &str,
+) -> String {
+    let mut lines = vec![format!("Analysis Time: {:.2}s", analysis_time)];
+    if let Some(commits_per_sec) = commits_per_second {
+        lines.push(format!("Commits/sec: {:.1}", commits_per_sec));
+    }
+    if !processing_stats.is_empty() {
+        lines.push(processing_stats.to_string());
+    }
+    lines.join("\n")
+}
+
+/// Start analyzing the repository at `app.repo_path` in the background
+///
+/// Progress and the final result are written back through `app_arc`.
+fn start_analysis(app: &mut App, app_arc: Arc<Mutex<App>>) {
+    app.is_analyzing = true;
+    app.error_message = None;
+    app.estimated_duration = None;
+    let repo_path = app.repo_path.clone();
+    let selected_branch = app.selected_branch.clone();
+    let selected_contributor = app.selected_contributor.clone();
+    let options = app.analysis_options.clone();
+
+    // Count commits up front so the user knows roughly how long to wait, calibrated
+    // with the rate measured by the last run when there is one
+    let estimate_app = app_arc.clone();
+    let estimate = estimate_analysis_duration(
+        repo_path.clone(),
+        selected_branch.clone(),
+        app.commits_per_second,
+    );
+    app.tasks.spawn(async move {
+        if let Ok(duration) = estimate.await {
+            if let Ok(mut app) = estimate_app.lock() {
+                if app.is_analyzing {
+                    app.estimated_duration = Some(duration);
+                }
+            }
+        }
+    });
+
+    let tasks = app.tasks.clone();
+    let task = app.tasks.spawn(async move {
+        let (tx, mut rx) = mpsc::channel(32);
+        let analyze_future = analyze_repo_with_options(
+            repo_path,
+            selected_branch,
+            selected_contributor,
+            options,
+            Some(tx),
+        );
+
+        // Spawn a task to handle progress updates
+        let progress_app = app_arc.clone();
+        tasks.spawn(async move {
+            while let Some(progress) = rx.recv().await {
+                if let Ok(mut app) = progress_app.lock() {
+                    app.update_progress(progress);
+                }
+            }
+        });
+
+        // Wait for analysis to complete
+        match analyze_future.await {
+            Ok(result) => {
+                if let Ok(mut app) = app_arc.lock() {
+                    app.update_with_result(result);
+                    app.is_analyzing = false;
+                }
+            }
+            Err(e) => {
+                if let Ok(mut app) = app_arc.lock() {
+                    app.error_message = Some(e.to_string());
+                    app.is_analyzing = false;
+                }
+            }
+        }
+    });
+    app.analysis_task = Some(task.abort_handle());
+}
+
 /// Handle changes in branch or contributor selection
 /// Updates the analysis results either from cache or by running a new analysis
 ///
@@ -299,21 +651,39 @@ fn handle_selection_change(app: &mut App, app_arc: Arc<Mutex<App>>) {
     {
         // Use cached result
         app.update_with_result(cached_result);
+    } else if let Some(filtered) = app
+        .get_cached_result(&app.selected_branch, "All")
+        .filter(|_| app.selected_contributor != "All")
+        .and_then(|all_result| all_result.filtered_by_author(&app.selected_contributor))
+    {
+        // Derive the contributor view from the full analysis instead of re-walking
+        app.update_with_result(filtered);
     } else {
         // No cache, perform analysis
         let repo_path = app.repo_path.clone();
         let selected_branch = app.selected_branch.clone();
         let selected_contributor = app.selected_contributor.clone();
+        let mut options = app.analysis_options.clone();
+        // Switching branch or contributor doesn't change the branch list
+        if !app.available_branches.is_empty() {
+            options.known_branches = Some(app.available_branches.clone());
+        }
         app.is_analyzing = true;
 
-        tokio::spawn(async move {
+        let tasks = app.tasks.clone();
+        let task = app.tasks.spawn(async move {
             let (tx, mut rx) = mpsc::channel(32);
-            let analyze_future =
-                analyze_repo_async(repo_path, selected_branch, selected_contributor, Some(tx));
+            let analyze_future = analyze_repo_with_options(
+                repo_path,
+                selected_branch,
+                selected_contributor,
+                options,
+                Some(tx),
+            );
 
             // Spawn a task to handle progress updates
             let progress_app = app_arc.clone();
-            tokio::spawn(async move {
+            tasks.spawn(async move {
                 while let Some(progress) = rx.recv().await {
                     if let Ok(mut app) = progress_app.lock() {
                         app.update_progress(progress);
@@ -337,5 +707,32 @@ fn handle_selection_change(app: &mut App, app_arc: Arc<Mutex<App>>) {
                 }
             }
         });
+        app.analysis_task = Some(task.abort_handle());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_path_submitted() {
+        assert!(is_path_submitted(true, true, false));
+        // Clicking elsewhere also drops focus, but shouldn't start an analysis
+        assert!(!is_path_submitted(true, false, false));
+        // Enter pressed in some other widget
+        assert!(!is_path_submitted(false, true, false));
+        // Don't start a second analysis while one is running
+        assert!(!is_path_submitted(true, true, true));
+    }
+
+    #[test]
+    fn test_performance_summary() {
+        assert_eq!(
+            performance_summary(1.234, Some(512.56), "Chunk size: 100\nTasks: 4"),
+            "Analysis Time: 1.23s\nCommits/sec: 512.6\nChunk size: 100\nTasks: 4"
+        );
+        // Nothing measured beyond the time
+        assert_eq!(performance_summary(0.5, None, ""), "Analysis Time: 0.50s");
     }
 }
@@ -21,9 +21,12 @@
 //! use std::sync::{Arc, Mutex};
 //! use eframe::NativeOptions;
 //!
+//! // Analysis runs in the background on a multi-threaded tokio runtime
+//! let runtime = tokio::runtime::Runtime::new().unwrap();
+//!
 //! // Create a new application instance
 //! let app = Arc::new(Mutex::new(GitStatsApp::default()));
-//! let app_wrapper = gitstats::app::AppWrapper { app };
+//! let app_wrapper = gitstats::app::AppWrapper::with_runtime(app, runtime.handle().clone());
 //!
 //! // Run the application with eframe
 //! eframe::run_native(
@@ -41,4 +44,7 @@ pub mod utils;
 
 // Re-export main types for convenience
 pub use app::App as GitStatsApp;
-pub use types::{AnalysisResult, CacheKey};
+pub use types::{
+    AccuracyMode, AnalysisDelta, AnalysisOptions, AnalysisResult, CacheKey, CommitActivity,
+    Parallelism, PartialStats, RefKind,
+};
@@ -1,16 +1,205 @@
 //! Git Statistics Visualization Tool
 //!
 //! A GUI application for analyzing and visualizing Git repository statistics.
+//!
+//! Running `gitstats chart --path <repo> --metric <name> --out <file>` (or passing
+//! `--no-gui` instead of `chart`) analyzes the repository and saves a single chart
+//! without opening a window. Adding `--progress-json` prints one JSON progress event
+//! per line to stderr while the analysis runs, and `--cache-file <path>` keeps results
+//! between runs so only commits made since the last run are analyzed.
 
 use eframe::egui;
+use git2::{Oid, Repository};
+use serde::Serialize;
+use std::error::Error;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tokio::runtime::Runtime;
 
+use gitstats::analysis::{branch_head, extend_result, CacheManager};
 use gitstats::app::{App, AppWrapper};
+use gitstats::plotting::chart::{metric_by_name, METRICS};
+use gitstats::types::{AnalysisResult, CacheKey, ProgressEstimate};
+
+/// Options for headless chart generation
+struct ChartArgs {
+    path: String,
+    branch: String,
+    metric: &'static str,
+    out: PathBuf,
+    /// Print progress to stderr as newline-delimited JSON
+    progress_json: bool,
+    /// File to load earlier results from and save the new one to
+    cache_file: Option<PathBuf>,
+}
+
+/// One line of `--progress-json` output
+#[derive(Serialize)]
+struct ProgressEvent<'a> {
+    #[serde(flatten)]
+    progress: &'a ProgressEstimate,
+    /// Percentage of commits processed so far
+    pct: f64,
+}
+
+/// Parse the arguments following `chart`
+fn parse_chart_args(args: &[String]) -> Result<ChartArgs, String> {
+    let mut chart_args = ChartArgs {
+        path: ".".to_string(),
+        branch: "main".to_string(),
+        metric: METRICS[0],
+        out: PathBuf::from("chart.png"),
+        progress_json: false,
+        cache_file: None,
+    };
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .cloned()
+                .ok_or_else(|| format!("Missing value for {}", arg))
+        };
+        match arg.as_str() {
+            "--path" => chart_args.path = value()?,
+            "--branch" => chart_args.branch = value()?,
+            "--out" => chart_args.out = PathBuf::from(value()?),
+            "--progress-json" => chart_args.progress_json = true,
+            "--cache-file" => chart_args.cache_file = Some(PathBuf::from(value()?)),
+            "--metric" => {
+                let name = value()?;
+                chart_args.metric = metric_by_name(&name).ok_or_else(|| {
+                    format!(
+                        "Unknown metric '{}' (expected one of: {})",
+                        name,
+                        METRICS.join(", ")
+                    )
+                })?;
+            }
+            other => return Err(format!("Unknown argument: {}", other)),
+        }
+    }
+
+    Ok(chart_args)
+}
+
+/// Run the app's analysis, printing progress events if `progress_json` is set
+async fn analyze(
+    app: &mut App,
+    progress_json: bool,
+) -> Result<AnalysisResult, Box<dyn Error + Send + Sync>> {
+    // Without --progress-json nobody watches progress, so the receiver is dropped
+    let (mut progress_rx, analysis) = app.analyze_repo();
+    let progress_printer = progress_json.then(|| {
+        tokio::spawn(async move {
+            while let Some(progress) = progress_rx.recv().await {
+                let event = ProgressEvent {
+                    pct: progress.percent_complete(),
+                    progress: &progress,
+                };
+                if let Ok(line) = serde_json::to_string(&event) {
+                    eprintln!("{}", line);
+                }
+            }
+        })
+    });
+    let result = analysis.await;
+    // The channel closes once analysis finishes, so this only waits for queued events
+    if let Some(printer) = progress_printer {
+        printer.await?;
+    }
+    Ok(result?)
+}
+
+/// Load the result cache, starting empty when the file doesn't exist yet
+fn load_cache(path: &Path) -> std::io::Result<CacheManager> {
+    match CacheManager::load_from_disk(path) {
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(CacheManager::new()),
+        loaded => loaded,
+    }
+}
+
+/// Analyze the app's repository, reusing and updating the results in `cache_file`
+///
+/// A cached result computed at the branch's current commit is used as-is. One computed
+/// at an ancestor is extended with just the commits made since; anything else, such
+/// as a result from before a force-push, is analyzed again from scratch.
+async fn analyze_with_cache(
+    app: &mut App,
+    cache_file: &Path,
+    progress_json: bool,
+) -> Result<AnalysisResult, Box<dyn Error + Send + Sync>> {
+    let repo = Repository::open(&app.repo_path)?;
+    let head = branch_head(&repo, &app.selected_branch)?;
+    let key = CacheKey {
+        branch: app.selected_branch.clone(),
+        contributor: app.selected_contributor.clone(),
+    };
+    let mut cache = load_cache(cache_file)?;
+
+    let cached = cache
+        .head(&key)
+        .map(str::to_string)
+        .zip(cache.get(&key).cloned());
+    let result = match cached {
+        Some((cached_head, cached)) if cached_head == head.to_string() => {
+            println!("Cache hit: reusing the analysis of {}", cached_head);
+            cached
+        }
+        Some((cached_head, cached))
+            if Oid::from_str(&cached_head)
+                .and_then(|base| repo.graph_descendant_of(head, base))
+                .unwrap_or(false) =>
+        {
+            app.analysis_options.since_commit = Some(cached_head);
+            let newer = analyze(app, progress_json).await?;
+            println!("Cache updated with {} new commits", newer.commit_count);
+            extend_result(cached, newer)
+        }
+        _ => analyze(app, progress_json).await?,
+    };
+
+    cache.store_at_head(key, head.to_string(), result.clone());
+    cache.save_to_disk(cache_file)?;
+    Ok(result)
+}
+
+/// Analyze a repository and save one chart to disk, without starting the GUI
+async fn run_chart(args: &[String]) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let args = parse_chart_args(args)?;
+    let mut app = App {
+        repo_path: args.path,
+        selected_branch: args.branch,
+        current_metric: args.metric.to_string(),
+        ..Default::default()
+    };
+
+    let result = match &args.cache_file {
+        Some(cache_file) => analyze_with_cache(&mut app, cache_file, args.progress_json).await?,
+        None => analyze(&mut app, args.progress_json).await?,
+    };
+    app.update_with_result(result);
+
+    app.save_plot_as(&args.out)?;
+    println!("Saved {} chart to {}", args.metric, args.out.display());
+    Ok(())
+}
 
 fn main() {
     // Initialize the Tokio runtime
     let rt = Runtime::new().unwrap();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if matches!(args.first().map(String::as_str), Some("chart" | "--no-gui")) {
+        if let Err(e) = rt.block_on(run_chart(&args[1..])) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     rt.block_on(async {
         // Initialize the GUI application with larger window size
         let options = eframe::NativeOptions {
@@ -30,10 +219,13 @@ fn main() {
                 cc.egui_ctx.set_fonts(fonts);
 
                 let app: Arc<Mutex<App>> = Arc::new(Mutex::new(App::default()));
-                Ok(Box::new(AppWrapper { app }) as Box<dyn eframe::App>)
+                Ok(Box::new(AppWrapper::new(app)) as Box<dyn eframe::App>)
             }),
         ) {
             eprintln!("Error running application: {}", e);
         }
     });
+
+    // Don't let diffs still running on blocking threads hold up exit
+    rt.shutdown_timeout(Duration::from_secs(1));
 }
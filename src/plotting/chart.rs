@@ -6,15 +6,23 @@ use plotters::coord::types::RangedCoordf64;
 use plotters::coord::Shift;
 use plotters::prelude::*;
 use plotters::style::text_anchor::{HPos, Pos, VPos};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::error::Error;
 use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::Mutex as TokioMutex;
 
+use super::styles::{contributor_colors, ChartStyle, ColorMode, LegendPosition};
 use crate::app::App;
+use crate::types::{
+    commit_size_histogram, commit_type_counts, daily_commit_counts, monthly_extension_churn,
+    weekly_author_commits, yearly_commit_counts, CommitActivity,
+};
 use crate::utils::aggregate_data;
+use chrono::{Datelike, NaiveDate};
 
 /// Custom error type for plot-related operations
 type PlotError = Box<dyn Error + Send + Sync>;
@@ -39,6 +47,16 @@ static PLOT_CACHE: Lazy<PlotCache> = Lazy::new(|| {
 struct PlotCacheKey {
     metric: String,
     use_log_scale: bool,
+    min_commit_size: usize,
+    /// Bit pattern of the device pixel ratio, since `f32` isn't `Hash`
+    pixels_per_point: u32,
+    supersample: u32,
+    grid_divisions: usize,
+    date_format: String,
+    font_family: String,
+    font_size: u32,
+    legend_position: LegendPosition,
+    color_mode: ColorMode,
     data_hash: u64,
 }
 
@@ -52,6 +70,15 @@ impl PlotCacheKey {
         Self {
             metric: app.current_metric.clone(),
             use_log_scale: app.use_log_scale,
+            min_commit_size: app.min_commit_size,
+            pixels_per_point: app.pixels_per_point.to_bits(),
+            supersample: app.supersample,
+            grid_divisions: app.grid_divisions,
+            date_format: app.date_format.clone(),
+            font_family: app.chart_style.font_family.clone(),
+            font_size: app.chart_style.font_size,
+            legend_position: app.legend_position,
+            color_mode: app.color_mode,
             data_hash: hasher.finish(),
         }
     }
@@ -65,36 +92,164 @@ where
     e.into()
 }
 
+/// Every chart that can be drawn, named as on the metric buttons
+pub const METRICS: &[&str] = &[
+    "Commits",
+    "Code Changes",
+    "Code Frequency",
+    "Code Area",
+    "Commit Types",
+    "Commits by Year",
+    "Size Histogram",
+    "Language Churn",
+    "Commit Size Distribution",
+    "Contributor Comparison",
+    "Calendar",
+    "Avg Commit Size",
+];
+
+/// Look up a chart by name, ignoring case and accepting `-` or `_` in place of spaces
+///
+/// This lets command-line users write `code-changes` for "Code Changes".
+pub fn metric_by_name(name: &str) -> Option<&'static str> {
+    let wanted = name.replace(['-', '_'], " ");
+    METRICS
+        .iter()
+        .copied()
+        .find(|metric| metric.eq_ignore_ascii_case(&wanted))
+}
+
+/// Unit shown on the y axis of a metric's chart
+pub fn y_axis_unit(metric: &str) -> &'static str {
+    match metric {
+        "Commits" | "Calendar" => "commits/day",
+        "Code Changes" | "Code Frequency" | "Code Area" | "Language Churn" => "lines",
+        "Commit Types" | "Commits by Year" | "Size Histogram" => "commits",
+        "Commit Size Distribution" | "Avg Commit Size" => "lines/commit",
+        "Contributor Comparison" => "commits/week",
+        _ => "",
+    }
+}
+
+/// Date format used for axis labels unless the user picks another one
+pub const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Reformat a `YYYY-MM-DD` date with a strftime-style `format`, e.g. `%d/%m/%Y`
+///
+/// Invalid format strings fall back to ISO dates, and dates that can't be parsed are
+/// returned unchanged.
+pub fn format_date(date: &str, format: &str) -> String {
+    use chrono::format::{Item, StrftimeItems};
+
+    let Ok(parsed) = chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d") else {
+        return date.to_string();
+    };
+    let valid =
+        !format.is_empty() && !StrftimeItems::new(format).any(|item| matches!(item, Item::Error));
+    let format = if valid { format } else { DEFAULT_DATE_FORMAT };
+    parsed.format(format).to_string()
+}
+
+/// Plot size in logical points; the bitmap is this times the device pixel ratio
+pub const PLOT_SIZE: (u32, u32) = (640, 480);
+
+/// Pixel dimensions of a plot rendered at the given device pixel ratio
+pub fn plot_dimensions(pixels_per_point: f32) -> (u32, u32) {
+    let scale = pixels_per_point as f64;
+    (scaled(PLOT_SIZE.0, scale), scaled(PLOT_SIZE.1, scale))
+}
+
+/// Scale a size given in logical points to pixels
+fn scaled(points: u32, scale: f64) -> u32 {
+    (points as f64 * scale).round() as u32
+}
+
+/// Where a generated plot came from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlotSource {
+    /// Reused from the plot cache
+    Cache,
+    /// Rendered from scratch
+    Rendered,
+}
+
+/// Distinguishes the scratch files of plots rendering at the same time
+static SCRATCH_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Scratch file a single plot is rendered to, removed when dropped
+struct ScratchFile(PathBuf);
+
+impl ScratchFile {
+    /// A PNG path next to `plot_path` that no other plot uses
+    fn new(plot_path: &str) -> Self {
+        let plot_path = Path::new(plot_path);
+        let stem = plot_path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or("plot");
+        let id = SCRATCH_COUNTER.fetch_add(1, Ordering::Relaxed);
+        Self(plot_path.with_file_name(format!("{}-{}.png", stem, id)))
+    }
+}
+
+impl Drop for ScratchFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
 /// Generate a plot asynchronously based on the current app state
 /// Returns a PNG image as bytes or an error
 pub async fn generate_plot_async(app: App) -> Result<PlotData, PlotError> {
+    generate_plot_with_source(app)
+        .await
+        .map(|(plot_data, _)| plot_data)
+}
+
+/// Generate a plot like [`generate_plot_async`], also reporting whether it was cached
+pub async fn generate_plot_with_source(app: App) -> Result<(PlotData, PlotSource), PlotError> {
     let cache_key = PlotCacheKey::new(&app);
 
     // Try to get from cache first
     if let Some((plot_data, timestamp)) = PLOT_CACHE.lock().await.get(&cache_key) {
         if timestamp.elapsed() < Duration::from_secs(300) {
             // 5 minutes
-            return Ok(plot_data.clone());
+            return Ok((plot_data.clone(), PlotSource::Cache));
         }
     }
 
     // Generate new plot in blocking task
     let plot_data = tokio::task::spawn_blocking(move || {
-        // Create a temporary file for the plot
-        let root = BitMapBackend::new(&app.plot_path, (640, 480)).into_drawing_area();
+        // Supersampled plots are drawn at a higher pixel ratio, so text and margins grow
+        // with the bitmap, then shrunk back to the display size
+        let mut app = app;
+        let size = plot_dimensions(app.pixels_per_point);
+        let factor = app.supersample.max(1);
+        app.pixels_per_point *= factor as f32;
+
+        // Concurrent plots each get their own file, which is removed however this ends
+        let scratch = ScratchFile::new(&app.plot_path);
+        let root = BitMapBackend::new(&scratch.0, plot_dimensions(app.pixels_per_point))
+            .into_drawing_area();
         root.fill(&BLACK.mix(0.95))?;
 
         generate_plot_internal(&app, &root)?;
         root.present()?;
 
         // Convert the plot to RGBA format
-        let img = image::open(&app.plot_path)?;
-        let rgba = img.into_rgba8();
+        let img = image::open(&scratch.0)?;
+        let mut rgba = img.into_rgba8();
+        if factor > 1 {
+            rgba = image::imageops::resize(
+                &rgba,
+                size.0,
+                size.1,
+                image::imageops::FilterType::Lanczos3,
+            );
+        }
+        app.color_mode.apply(&mut rgba);
         let pixels = rgba.as_raw().to_vec();
 
-        // Clean up the temporary file
-        let _ = std::fs::remove_file(&app.plot_path);
-
         Ok::<PlotData, PlotError>(pixels)
     })
     .await??;
@@ -105,19 +260,179 @@ pub async fn generate_plot_async(app: App) -> Result<PlotData, PlotError> {
         .await
         .put(cache_key, (plot_data.clone(), Instant::now()));
 
-    Ok(plot_data)
+    Ok((plot_data, PlotSource::Rendered))
+}
+
+/// Render the current plot to `path`, inferring the format from its extension
+///
+/// `.png` files are rendered as bitmaps at the app's pixel ratio and color mode, and
+/// `.svg` files as vector graphics in color; any other extension is rejected.
+pub fn save_plot(app: &App, path: &Path) -> Result<(), PlotError> {
+    let size = plot_dimensions(app.pixels_per_point);
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_ascii_lowercase);
+
+    match extension.as_deref() {
+        Some("png") => {
+            let root = BitMapBackend::new(path, size).into_drawing_area();
+            generate_plot_internal(app, &root)?;
+            root.present()?;
+            if app.color_mode != ColorMode::Color {
+                let mut rgba = image::open(path)?.into_rgba8();
+                app.color_mode.apply(&mut rgba);
+                rgba.save(path)?;
+            }
+        }
+        Some("svg") => {
+            let root = SVGBackend::new(path, size).into_drawing_area();
+            generate_plot_internal(app, &root)?;
+            root.present()?;
+        }
+        _ => {
+            return Err(format!(
+                "Unsupported chart format for {} (expected .png or .svg)",
+                path.display()
+            )
+            .into())
+        }
+    }
+
+    Ok(())
+}
+
+/// The app's commit activity without commits changing fewer than `app.min_commit_size`
+/// lines, which the charts leave out
+pub fn filtered_activity(app: &App) -> Vec<&CommitActivity> {
+    app.commit_activity
+        .iter()
+        .filter(|activity| activity.lines_added + activity.lines_deleted >= app.min_commit_size)
+        .collect()
+}
+
+/// Collect the `(date, added, deleted)` points to plot from the app's commit activity
+///
+/// Points are ordered by commit timestamp, so commits on the same day keep their order.
+///
+/// Commits changing fewer than `app.min_commit_size` lines are dropped here, so the
+/// filter only affects the charts and never the summary totals.
+pub fn plot_points(app: &App) -> Vec<(String, usize, usize)> {
+    let mut activities = filtered_activity(app);
+    activities.sort_by_key(|activity| activity.timestamp);
+    activities
+        .into_iter()
+        .map(|activity| {
+            (
+                activity.date.clone(),
+                activity.lines_added,
+                activity.lines_deleted,
+            )
+        })
+        .collect()
 }
 
 /// Internal function to generate the plot using plotters
 /// Handles the actual rendering of different plot types
-fn generate_plot_internal(
+fn generate_plot_internal<DB: DrawingBackend>(
     app: &App,
-    root_area: &DrawingArea<BitMapBackend, Shift>,
-) -> Result<(), PlotError> {
+    root_area: &DrawingArea<DB, Shift>,
+) -> Result<(), PlotError>
+where
+    DB::ErrorType: 'static,
+{
     root_area.fill(&BLACK.mix(0.95)).map_err(wrap_err)?;
+    let scale = app.pixels_per_point as f64;
+    let style = &app.chart_style;
+
+    // Commit types are categories rather than a timeline, so they get their own chart
+    if app.current_metric == "Commit Types" {
+        return draw_commit_types(
+            root_area,
+            &commit_type_counts(filtered_activity(app)),
+            scale,
+            style,
+        );
+    }
+
+    if app.current_metric == "Commits by Year" {
+        return draw_commits_by_year(
+            root_area,
+            &yearly_commit_counts(filtered_activity(app)),
+            scale,
+            style,
+        );
+    }
+
+    if app.current_metric == "Size Histogram" {
+        return draw_bar_chart(
+            root_area,
+            "Size Histogram",
+            &commit_size_histogram(filtered_activity(app)),
+            scale,
+            style,
+        );
+    }
+
+    if app.current_metric == "Contributor Comparison" {
+        return draw_contributor_comparison(
+            root_area,
+            &filtered_activity(app),
+            app.legend_position,
+            scale,
+            style,
+        );
+    }
+
+    if app.current_metric == "Language Churn" {
+        return draw_language_churn(
+            root_area,
+            &monthly_extension_churn(filtered_activity(app)),
+            app.legend_position,
+            scale,
+            style,
+        );
+    }
+
+    if app.current_metric == "Calendar" {
+        return draw_contribution_calendar(
+            root_area,
+            &daily_commit_counts(filtered_activity(app)),
+            scale,
+            style,
+        );
+    }
+
+    if app.current_metric == "Avg Commit Size" {
+        return draw_avg_commit_size(
+            root_area,
+            &daily_avg_commit_size(&plot_points(app)),
+            &app.date_format,
+            scale,
+            style,
+        );
+    }
+
+    // The distribution is taken over individual commits, before per-date aggregation
+    if app.current_metric == "Commit Size Distribution" {
+        let sizes: Vec<usize> = plot_points(app)
+            .iter()
+            .map(|(_, added, deleted)| added + deleted)
+            .collect();
+        return match commit_size_summary(&sizes) {
+            Some(summary) => draw_box_plot(root_area, summary, scale, style),
+            None => draw_no_data(root_area, scale, style),
+        };
+    }
 
     // Get aggregated data
-    let plot_data = aggregate_data(&app.commit_activity, 500);
+    let points = plot_points(app);
+    let plot_data = aggregate_data(&points, 500);
+
+    // An empty series would produce a degenerate 0..0 axis, so show a message instead
+    if plot_data.is_empty() {
+        return draw_no_data(root_area, scale, style);
+    }
 
     // Calculate range based on data type and adaptive scaling
     let (min_val, max_val) = match app.current_metric.as_str() {
@@ -131,7 +446,7 @@ fn generate_plot_internal(
                 .collect();
             calculate_adaptive_range(&commit_values)
         }
-        "Code Changes" | "Code Frequency" => {
+        "Code Changes" | "Code Frequency" | "Code Area" => {
             let added_values: Vec<f64> = plot_data
                 .iter()
                 .map(|(_, added, _)| *added as f64)
@@ -155,10 +470,10 @@ fn generate_plot_internal(
     let mut chart_builder = ChartBuilder::on(root_area)
         .caption(
             format!("{} Over Time", app.current_metric),
-            ("sans-serif", 30).into_font().color(&WHITE.mix(0.8)),
+            style.caption_font(scale).color(&WHITE.mix(0.8)),
         )
-        .margin(10)
-        .set_all_label_area_size(50)
+        .margin(scaled(10, scale))
+        .set_all_label_area_size(scaled(50, scale))
         .build_cartesian_2d(
             0f64..(plot_data.len() as f64),
             if app.use_log_scale {
@@ -173,17 +488,18 @@ fn generate_plot_internal(
 
     // Store the dates in a longer-lived variable
     let dates_clone = dates.clone();
+    let date_format = app.date_format.clone();
     let x_label_formatter = move |x: &f64| {
         let idx = *x as usize;
         if idx < dates_clone.len() {
             // Show fewer labels to prevent overlap
             if idx == 0
                 || idx == dates_clone.len() - 1
-                || (idx % (dates_clone.len() / 4).max(1) == 0
+                || (idx.is_multiple_of((dates_clone.len() / 4).max(1))
                     && idx > 0
                     && idx < dates_clone.len() - 1)
             {
-                dates_clone[idx].clone()
+                format_date(&dates_clone[idx], &date_format)
             } else {
                 String::new()
             }
@@ -195,13 +511,13 @@ fn generate_plot_internal(
     mesh.light_line_style(TRANSPARENT)
         .bold_line_style(WHITE.mix(0.2))
         .axis_style(WHITE.mix(0.8))
-        .y_desc(&app.current_metric)
-        .label_style(("sans-serif", 15).into_font().color(&WHITE.mix(0.8)))
+        .y_desc(y_axis_unit(&app.current_metric))
+        .label_style(style.label_font(scale).color(&WHITE.mix(0.8)))
         .x_label_formatter(&x_label_formatter)
         // Rotate x labels for better readability
         .x_label_style(
-            ("sans-serif", 15)
-                .into_font()
+            style
+                .label_font(scale)
                 .color(&WHITE.mix(0.8))
                 .transform(FontTransform::Rotate90)
                 .pos(Pos::new(HPos::Right, VPos::Center)),
@@ -225,7 +541,12 @@ fn generate_plot_internal(
     mesh.draw()?;
 
     // Draw grid and data
-    draw_grid(&mut chart_builder, plot_data.len() as f64).map_err(wrap_err)?;
+    draw_grid(
+        &mut chart_builder,
+        plot_data.len() as f64,
+        app.grid_divisions,
+    )
+    .map_err(wrap_err)?;
 
     match app.current_metric.as_str() {
         "Commits" => {
@@ -237,17 +558,632 @@ fn generate_plot_internal(
         "Code Frequency" => {
             draw_code_frequency(&mut chart_builder, &plot_data).map_err(wrap_err)?;
         }
+        "Code Area" => {
+            draw_code_area(&mut chart_builder, &plot_data).map_err(wrap_err)?;
+        }
         _ => {}
     }
 
     Ok(())
 }
 
-/// Draw grid lines with adaptive spacing based on data range
-fn draw_grid(
-    chart_builder: &mut ChartContext<BitMapBackend, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+/// Draw a centered "No data" message for repositories without any activity
+fn draw_no_data<DB: DrawingBackend>(
+    root_area: &DrawingArea<DB, Shift>,
+    scale: f64,
+    style: &ChartStyle,
+) -> Result<(), PlotError>
+where
+    DB::ErrorType: 'static,
+{
+    let (width, height) = root_area.dim_in_pixel();
+    let text_style = style
+        .caption_font(scale)
+        .color(&WHITE.mix(0.8))
+        .pos(Pos::new(HPos::Center, VPos::Center));
+    root_area
+        .draw_text(
+            "No data",
+            &text_style,
+            ((width / 2) as i32, (height / 2) as i32),
+        )
+        .map_err(wrap_err)?;
+    Ok(())
+}
+
+/// Draw a bar chart of commit counts per conventional-commit type, largest first
+fn draw_commit_types<DB: DrawingBackend>(
+    root_area: &DrawingArea<DB, Shift>,
+    type_counts: &HashMap<String, usize>,
+    scale: f64,
+    style: &ChartStyle,
+) -> Result<(), PlotError>
+where
+    DB::ErrorType: 'static,
+{
+    let mut counts: Vec<(String, usize)> = type_counts
+        .iter()
+        .map(|(name, count)| (name.clone(), *count))
+        .collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    draw_bar_chart(root_area, "Commit Types", &counts, scale, style)
+}
+
+/// Draw a bar chart of commit counts per year, oldest first
+fn draw_commits_by_year<DB: DrawingBackend>(
+    root_area: &DrawingArea<DB, Shift>,
+    commits_by_year: &BTreeMap<i32, usize>,
+    scale: f64,
+    style: &ChartStyle,
+) -> Result<(), PlotError>
+where
+    DB::ErrorType: 'static,
+{
+    let counts: Vec<(String, usize)> = commits_by_year
+        .iter()
+        .map(|(year, count)| (year.to_string(), *count))
+        .collect();
+    draw_bar_chart(root_area, "Commits by Year", &counts, scale, style)
+}
+
+/// Weeks shown on the "Calendar" chart, a year like GitHub's contribution graph
+const CALENDAR_WEEKS: i64 = 53;
+
+/// Calendar cell colours from fewest to most commits, after GitHub's dark theme
+const CALENDAR_LEVELS: [RGBColor; 4] = [
+    RGBColor(14, 68, 41),
+    RGBColor(0, 109, 50),
+    RGBColor(38, 166, 65),
+    RGBColor(57, 211, 83),
+];
+
+/// Draw a GitHub-style contribution calendar of the year up to the latest commit
+///
+/// Each column is a Sunday-to-Saturday week and each cell a day, shaded by its commit
+/// count relative to the busiest day shown.
+fn draw_contribution_calendar<DB: DrawingBackend>(
+    root_area: &DrawingArea<DB, Shift>,
+    daily_counts: &BTreeMap<NaiveDate, usize>,
+    scale: f64,
+    style: &ChartStyle,
+) -> Result<(), PlotError>
+where
+    DB::ErrorType: 'static,
+{
+    let Some(&last) = daily_counts.keys().next_back() else {
+        return draw_no_data(root_area, scale, style);
+    };
+    let last_week = last - chrono::Duration::days(last.weekday().num_days_from_sunday() as i64);
+    let first = last_week - chrono::Duration::weeks(CALENDAR_WEEKS - 1);
+    let max_count = daily_counts
+        .range(first..)
+        .map(|(_, count)| *count)
+        .max()
+        .unwrap_or(1);
+
+    // The top row is kept free for month labels
+    let mut chart = ChartBuilder::on(root_area)
+        .caption(
+            format!("Contributions {} to {}", first, last),
+            style.caption_font(scale).color(&WHITE.mix(0.8)),
+        )
+        .margin(scaled(10, scale))
+        .build_cartesian_2d(0f64..(CALENDAR_WEEKS as f64), 0f64..8f64)?;
+
+    // Label each week in which a month starts
+    let label_style = style.label_font(scale).color(&WHITE.mix(0.8));
+    chart.draw_series((0..CALENDAR_WEEKS).filter_map(|week| {
+        let week_start = first + chrono::Duration::weeks(week);
+        (week_start.day() <= 7 && week_start <= last).then(|| {
+            Text::new(
+                week_start.format("%b").to_string(),
+                (week as f64, 7.8),
+                label_style.clone(),
+            )
+        })
+    }))?;
+
+    let days = (0..CALENDAR_WEEKS * 7)
+        .map(|offset| (offset, first + chrono::Duration::days(offset)))
+        .take_while(|(_, date)| *date <= last);
+    chart.draw_series(days.map(|(offset, date)| {
+        let (week, weekday) = ((offset / 7) as f64, (offset % 7) as f64);
+        let count = daily_counts.get(&date).copied().unwrap_or(0);
+        let color = if count == 0 {
+            WHITE.mix(0.1)
+        } else {
+            let level = (count * CALENDAR_LEVELS.len()).div_ceil(max_count);
+            CALENDAR_LEVELS[level.clamp(1, CALENDAR_LEVELS.len()) - 1].to_rgba()
+        };
+        // Sunday is the top row
+        Rectangle::new(
+            [(week + 0.1, 6.9 - weekday), (week + 0.9, 6.1 - weekday)],
+            color.filled(),
+        )
+    }))?;
+
+    Ok(())
+}
+
+/// Number of contributors plotted on the "Contributor Comparison" chart
+const COMPARED_CONTRIBUTORS: usize = 5;
+
+/// Draw weekly commit counts of the most active contributors as one line each
+fn draw_contributor_comparison<DB: DrawingBackend>(
+    root_area: &DrawingArea<DB, Shift>,
+    activity: &[&CommitActivity],
+    legend_position: LegendPosition,
+    scale: f64,
+    style: &ChartStyle,
+) -> Result<(), PlotError>
+where
+    DB::ErrorType: 'static,
+{
+    let weekly = weekly_author_commits(activity.iter().copied());
+
+    let mut authors: Vec<(String, usize)> = weekly
+        .iter()
+        .map(|(author, weeks)| (author.clone(), weeks.values().sum()))
+        .collect();
+    authors.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    authors.truncate(COMPARED_CONTRIBUTORS);
+    let names: Vec<String> = authors.into_iter().map(|(author, _)| author).collect();
+
+    // Every author shares one x axis covering the weeks any of them was active
+    let weeks: Vec<(i32, u32)> = names
+        .iter()
+        .flat_map(|name| weekly[name].keys().copied())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    let Some(max_count) = names
+        .iter()
+        .flat_map(|name| weekly[name].values().copied())
+        .max()
+    else {
+        return draw_no_data(root_area, scale, style);
+    };
+
+    let mut chart_builder = ChartBuilder::on(root_area)
+        .caption(
+            "Contributor Comparison",
+            style.caption_font(scale).color(&WHITE.mix(0.8)),
+        )
+        .margin(scaled(10, scale))
+        .set_all_label_area_size(scaled(50, scale))
+        .build_cartesian_2d(0f64..(weeks.len() as f64), 0f64..(max_count as f64 * 1.1))?;
+
+    let week_labels = weeks.clone();
+    chart_builder
+        .configure_mesh()
+        .light_line_style(TRANSPARENT)
+        .bold_line_style(WHITE.mix(0.2))
+        .axis_style(WHITE.mix(0.8))
+        .y_desc(y_axis_unit("Contributor Comparison"))
+        .label_style(style.label_font(scale).color(&WHITE.mix(0.8)))
+        .x_labels(weeks.len().min(6))
+        .x_label_formatter(&|x| {
+            week_labels
+                .get(*x as usize)
+                .map(|(year, week)| format!("{}-W{:02}", year, week))
+                .unwrap_or_default()
+        })
+        .y_label_formatter(&|y| format!("{:.0}", y))
+        .draw()?;
+
+    let colors = contributor_colors(&names);
+    for name in &names {
+        let color = colors[name];
+        let counts = &weekly[name];
+        let points: Vec<(f64, f64)> = weeks
+            .iter()
+            .enumerate()
+            .map(|(i, week)| (i as f64, counts.get(week).copied().unwrap_or(0) as f64))
+            .collect();
+
+        chart_builder
+            .draw_series(LineSeries::new(points, color.stroke_width(2)))?
+            .label(name.as_str())
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
+    }
+
+    chart_builder
+        .configure_series_labels()
+        .position(legend_position.series_label_position())
+        .background_style(BLACK.mix(0.8))
+        .border_style(WHITE.mix(0.5))
+        .label_font(style.label_font(scale).color(&WHITE.mix(0.8)))
+        .draw()?;
+
+    Ok(())
+}
+
+/// Number of extensions stacked separately on the "Language Churn" chart; the rest
+/// are combined into one segment
+const CHURN_EXTENSIONS: usize = 6;
+
+/// Draw lines added per month as bars stacked by file extension
+///
+/// The extensions with the most added lines get a segment each, largest at the
+/// bottom, and the others are stacked on top as `other`.
+fn draw_language_churn<DB: DrawingBackend>(
+    root_area: &DrawingArea<DB, Shift>,
+    churn: &BTreeMap<String, BTreeMap<String, usize>>,
+    legend_position: LegendPosition,
+    scale: f64,
+    style: &ChartStyle,
+) -> Result<(), PlotError>
+where
+    DB::ErrorType: 'static,
+{
+    let mut totals: HashMap<&str, usize> = HashMap::new();
+    for (extension, added) in churn.values().flatten() {
+        *totals.entry(extension.as_str()).or_insert(0) += added;
+    }
+    let mut ranked: Vec<(&str, usize)> = totals.into_iter().collect();
+    ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+    let mut segments: Vec<String> = ranked
+        .iter()
+        .take(CHURN_EXTENSIONS)
+        .map(|(extension, _)| extension.to_string())
+        .collect();
+    let has_other = ranked.len() > CHURN_EXTENSIONS;
+    if has_other {
+        segments.push("other".to_string());
+    }
+
+    // Lines added per month for each segment, in `segments` order
+    let months: Vec<&String> = churn.keys().collect();
+    let stacks: Vec<Vec<usize>> = churn
+        .values()
+        .map(|by_extension| {
+            let mut stack = vec![0; segments.len()];
+            for (extension, added) in by_extension {
+                let index = segments
+                    .iter()
+                    .position(|segment| segment == extension)
+                    .unwrap_or(segments.len() - 1);
+                stack[index] += added;
+            }
+            stack
+        })
+        .collect();
+    let Some(max_total) = stacks
+        .iter()
+        .map(|stack| stack.iter().sum::<usize>())
+        .max()
+        .filter(|max| *max > 0)
+    else {
+        return draw_no_data(root_area, scale, style);
+    };
+
+    let mut chart_builder = ChartBuilder::on(root_area)
+        .caption(
+            "Language Churn",
+            style.caption_font(scale).color(&WHITE.mix(0.8)),
+        )
+        .margin(scaled(10, scale))
+        .set_all_label_area_size(scaled(50, scale))
+        .build_cartesian_2d(0f64..(months.len() as f64), 0f64..(max_total as f64 * 1.1))?;
+
+    chart_builder
+        .configure_mesh()
+        .light_line_style(TRANSPARENT)
+        .bold_line_style(WHITE.mix(0.2))
+        .axis_style(WHITE.mix(0.8))
+        .y_desc(y_axis_unit("Language Churn"))
+        .label_style(style.label_font(scale).color(&WHITE.mix(0.8)))
+        .x_labels(months.len().min(6))
+        .x_label_formatter(&|x| {
+            months
+                .get(*x as usize)
+                .map(|month| month.to_string())
+                .unwrap_or_default()
+        })
+        .y_label_formatter(&|y| format!("{:.0}", y))
+        .draw()?;
+
+    let colors = contributor_colors(&segments);
+    for (index, segment) in segments.iter().enumerate() {
+        let color = colors[segment];
+        let bars = stacks.iter().enumerate().filter_map(|(i, stack)| {
+            let bottom: usize = stack[..index].iter().sum();
+            let top = bottom + stack[index];
+            (top > bottom).then(|| {
+                Rectangle::new(
+                    [
+                        (i as f64 + 0.1, bottom as f64),
+                        (i as f64 + 0.9, top as f64),
+                    ],
+                    color.mix(0.8).filled(),
+                )
+            })
+        });
+        chart_builder
+            .draw_series(bars)?
+            .label(segment.as_str())
+            .legend(move |(x, y)| Rectangle::new([(x, y - 5), (x + 10, y + 5)], color.filled()));
+    }
+
+    chart_builder
+        .configure_series_labels()
+        .position(legend_position.series_label_position())
+        .background_style(BLACK.mix(0.8))
+        .border_style(WHITE.mix(0.5))
+        .label_font(style.label_font(scale).color(&WHITE.mix(0.8)))
+        .draw()?;
+
+    Ok(())
+}
+
+/// Draw one labelled bar per `(label, commits)` entry, in the given order
+fn draw_bar_chart<DB: DrawingBackend>(
+    root_area: &DrawingArea<DB, Shift>,
+    title: &str,
+    counts: &[(String, usize)],
+    scale: f64,
+    style: &ChartStyle,
+) -> Result<(), PlotError>
+where
+    DB::ErrorType: 'static,
+{
+    let Some(max_count) = counts
+        .iter()
+        .map(|(_, count)| *count)
+        .max()
+        .filter(|max| *max > 0)
+    else {
+        return draw_no_data(root_area, scale, style);
+    };
+
+    let mut chart_builder = ChartBuilder::on(root_area)
+        .caption(title, style.caption_font(scale).color(&WHITE.mix(0.8)))
+        .margin(scaled(10, scale))
+        .set_all_label_area_size(scaled(50, scale))
+        .build_cartesian_2d(0f64..(counts.len() as f64), 0f64..(max_count as f64 * 1.1))?;
+
+    chart_builder
+        .configure_mesh()
+        .light_line_style(TRANSPARENT)
+        .bold_line_style(WHITE.mix(0.2))
+        .axis_style(WHITE.mix(0.8))
+        .y_desc(y_axis_unit(title))
+        .label_style(style.label_font(scale).color(&WHITE.mix(0.8)))
+        // Bars are labelled individually below, so the numeric x axis is hidden
+        .x_label_formatter(&|_| String::new())
+        .y_label_formatter(&|y| format!("{:.0}", y))
+        .draw()?;
+
+    chart_builder.draw_series(counts.iter().enumerate().map(|(i, (_, count))| {
+        let x0 = i as f64 + 0.1;
+        let x1 = i as f64 + 0.9;
+        Rectangle::new(
+            [(x0, 0.0), (x1, *count as f64)],
+            RGBColor(135, 206, 250).mix(0.8).filled(),
+        )
+    }))?;
+
+    // Label each bar with its name and count
+    let label_style = style
+        .label_font(scale)
+        .color(&WHITE.mix(0.8))
+        .pos(Pos::new(HPos::Center, VPos::Bottom));
+    chart_builder.draw_series(counts.iter().enumerate().map(|(i, (name, count))| {
+        Text::new(
+            format!("{} ({})", name, count),
+            (i as f64 + 0.5, *count as f64),
+            label_style.clone(),
+        )
+    }))?;
+
+    Ok(())
+}
+
+/// Average lines changed per commit for each date, oldest first
+///
+/// Each entry is `(added + deleted) / commits` over that day's `(date, added, deleted)`
+/// points, so busy days with many small commits don't look like large changes.
+pub fn daily_avg_commit_size(points: &[(String, usize, usize)]) -> Vec<(String, f64)> {
+    let mut per_day: BTreeMap<&str, (usize, usize)> = BTreeMap::new();
+    for (date, added, deleted) in points {
+        let (lines, commits) = per_day.entry(date.as_str()).or_insert((0, 0));
+        *lines += added + deleted;
+        *commits += 1;
+    }
+    per_day
+        .into_iter()
+        .map(|(date, (lines, commits))| (date.to_string(), lines as f64 / commits as f64))
+        .collect()
+}
+
+/// Draw the average commit size per day as a line with a marker on each day
+fn draw_avg_commit_size<DB: DrawingBackend>(
+    root_area: &DrawingArea<DB, Shift>,
+    averages: &[(String, f64)],
+    date_format: &str,
+    scale: f64,
+    style: &ChartStyle,
+) -> Result<(), PlotError>
+where
+    DB::ErrorType: 'static,
+{
+    if averages.is_empty() {
+        return draw_no_data(root_area, scale, style);
+    }
+
+    let values: Vec<f64> = averages.iter().map(|(_, avg)| *avg).collect();
+    let (_, max_val) = calculate_adaptive_range(&values);
+
+    let mut chart_builder = ChartBuilder::on(root_area)
+        .caption(
+            "Avg Commit Size Over Time",
+            style.caption_font(scale).color(&WHITE.mix(0.8)),
+        )
+        .margin(scaled(10, scale))
+        .set_all_label_area_size(scaled(50, scale))
+        .build_cartesian_2d(
+            -0.5f64..(averages.len() as f64 - 0.5),
+            0f64..max_val.max(1.0),
+        )?;
+
+    // Only whole positions are days; ticks in between stay unlabelled
+    let x_label_formatter = |x: &f64| {
+        if x.fract() != 0.0 || *x < 0.0 {
+            return String::new();
+        }
+        let idx = *x as usize;
+        let step = (averages.len() / 4).max(1);
+        match averages.get(idx) {
+            Some((date, _)) if idx.is_multiple_of(step) || idx == averages.len() - 1 => {
+                format_date(date, date_format)
+            }
+            _ => String::new(),
+        }
+    };
+
+    chart_builder
+        .configure_mesh()
+        .light_line_style(TRANSPARENT)
+        .bold_line_style(WHITE.mix(0.2))
+        .axis_style(WHITE.mix(0.8))
+        .y_desc(y_axis_unit("Avg Commit Size"))
+        .label_style(style.label_font(scale).color(&WHITE.mix(0.8)))
+        .x_label_formatter(&x_label_formatter)
+        .x_label_style(
+            style
+                .label_font(scale)
+                .color(&WHITE.mix(0.8))
+                .transform(FontTransform::Rotate90)
+                .pos(Pos::new(HPos::Right, VPos::Center)),
+        )
+        .y_label_formatter(&|y| format!("{:.0}", y))
+        .draw()?;
+
+    let line_color = RGBColor(135, 206, 250);
+    let series: Vec<(f64, f64)> = values
+        .iter()
+        .enumerate()
+        .map(|(i, avg)| (i as f64, *avg))
+        .collect();
+    chart_builder.draw_series(LineSeries::new(
+        series.clone(),
+        line_color.stroke_width(scaled(2, scale)),
+    ))?;
+    chart_builder.draw_series(
+        series
+            .into_iter()
+            .map(|point| Circle::new(point, scaled(3, scale), line_color.filled())),
+    )?;
+
+    Ok(())
+}
+
+/// Five-number summary `[min, Q1, median, Q3, max]` of per-commit sizes
+///
+/// Quartiles are linearly interpolated between the closest ranks. Returns `None`
+/// when there are no commits.
+pub fn commit_size_summary(sizes: &[usize]) -> Option<[f64; 5]> {
+    if sizes.is_empty() {
+        return None;
+    }
+
+    let mut sorted: Vec<f64> = sizes.iter().map(|&size| size as f64).collect();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+
+    let quantile = |p: f64| {
+        let pos = p * (sorted.len() - 1) as f64;
+        let lower = pos.floor() as usize;
+        let upper = pos.ceil() as usize;
+        sorted[lower] + (sorted[upper] - sorted[lower]) * (pos - lower as f64)
+    };
+
+    Some([
+        sorted[0],
+        quantile(0.25),
+        quantile(0.5),
+        quantile(0.75),
+        sorted[sorted.len() - 1],
+    ])
+}
+
+/// Draw a box plot of commit sizes from a `[min, Q1, median, Q3, max]` summary
+fn draw_box_plot<DB: DrawingBackend>(
+    root_area: &DrawingArea<DB, Shift>,
+    summary: [f64; 5],
+    scale: f64,
+    style: &ChartStyle,
+) -> Result<(), PlotError>
+where
+    DB::ErrorType: 'static,
+{
+    let [min, q1, median, q3, max] = summary;
+
+    let mut chart_builder = ChartBuilder::on(root_area)
+        .caption(
+            "Commit Size Distribution",
+            style.caption_font(scale).color(&WHITE.mix(0.8)),
+        )
+        .margin(scaled(10, scale))
+        .set_all_label_area_size(scaled(50, scale))
+        .build_cartesian_2d(0f64..1f64, 0f64..(max * 1.1).max(1.0))?;
+
+    chart_builder
+        .configure_mesh()
+        .light_line_style(TRANSPARENT)
+        .bold_line_style(WHITE.mix(0.2))
+        .axis_style(WHITE.mix(0.8))
+        .y_desc(y_axis_unit("Commit Size Distribution"))
+        .label_style(style.label_font(scale).color(&WHITE.mix(0.8)))
+        .x_label_formatter(&|_| String::new())
+        .y_label_formatter(&|y| {
+            if y.abs() >= 1_000_000.0 {
+                format!("{:.1}M", y / 1_000_000.0)
+            } else if y.abs() >= 1_000.0 {
+                format!("{:.1}K", y / 1_000.0)
+            } else {
+                format!("{:.0}", y)
+            }
+        })
+        .draw()?;
+
+    let line_style = ShapeStyle::from(&RGBColor(135, 206, 250)).stroke_width(2);
+
+    // Interquartile box
+    chart_builder.draw_series(std::iter::once(Rectangle::new(
+        [(0.3, q1), (0.7, q3)],
+        RGBColor(100, 149, 237).mix(0.4).filled(),
+    )))?;
+    chart_builder.draw_series(std::iter::once(Rectangle::new(
+        [(0.3, q1), (0.7, q3)],
+        line_style,
+    )))?;
+
+    // Median, whiskers and their end caps
+    let segments = [
+        [(0.3, median), (0.7, median)],
+        [(0.5, min), (0.5, q1)],
+        [(0.5, q3), (0.5, max)],
+        [(0.4, min), (0.6, min)],
+        [(0.4, max), (0.6, max)],
+    ];
+    chart_builder.draw_series(
+        segments
+            .iter()
+            .map(|segment| PathElement::new(segment.to_vec(), line_style)),
+    )?;
+
+    Ok(())
+}
+
+/// Draw roughly `divisions` horizontal grid lines at a nice interval for the data range
+fn draw_grid<DB: DrawingBackend>(
+    chart_builder: &mut ChartContext<DB, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
     x_max: f64,
-) -> Result<(), PlotError> {
+    divisions: usize,
+) -> Result<(), PlotError>
+where
+    DB::ErrorType: 'static,
+{
     let grid_style = ShapeStyle::from(&WHITE.mix(0.15)).stroke_width(1);
     let major_grid_style = ShapeStyle::from(&WHITE.mix(0.25)).stroke_width(2);
 
@@ -257,20 +1193,7 @@ fn draw_grid(
     let y_max = y_range.end;
     let y_span = y_max - y_min;
 
-    // Calculate nice grid intervals
-    let y_interval = if y_span > 1_000_000.0 {
-        100_000.0
-    } else if y_span > 100_000.0 {
-        10_000.0
-    } else if y_span > 10_000.0 {
-        1_000.0
-    } else if y_span > 1_000.0 {
-        100.0
-    } else if y_span > 100.0 {
-        10.0
-    } else {
-        1.0
-    };
+    let y_interval = nice_interval(y_span, divisions);
 
     // Draw both major and minor grid lines
     let steps = (y_span / y_interval).ceil() as i32;
@@ -304,12 +1227,34 @@ fn draw_grid(
     Ok(())
 }
 
+/// Pick a grid interval of 1, 2 or 5 times a power of ten that splits `span` into at
+/// most about `divisions` steps
+///
+/// Falls back to 1 for an empty span or zero divisions.
+fn nice_interval(span: f64, divisions: usize) -> f64 {
+    if span.is_nan() || span <= 0.0 || divisions == 0 {
+        return 1.0;
+    }
+    let raw = span / divisions as f64;
+    let magnitude = 10f64.powf(raw.log10().floor());
+    let step = match raw / magnitude {
+        r if r <= 1.0 => 1.0,
+        r if r <= 2.0 => 2.0,
+        r if r <= 5.0 => 5.0,
+        _ => 10.0,
+    };
+    step * magnitude
+}
+
 /// Draw code changes plot showing additions and deletions over time
 /// Uses smoothed line series with different colors for additions and deletions
-fn draw_code_changes(
-    chart_builder: &mut ChartContext<BitMapBackend, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+fn draw_code_changes<DB: DrawingBackend>(
+    chart_builder: &mut ChartContext<DB, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
     plot_data: &[(String, usize, usize)],
-) -> Result<(), PlotError> {
+) -> Result<(), PlotError>
+where
+    DB::ErrorType: 'static,
+{
     // Smooth the data using moving average
     let window_size = if plot_data.len() < 1000 { 3 } else { 2 };
     let mut smoothed_additions: Vec<(f64, f64)> = Vec::new();
@@ -353,10 +1298,13 @@ fn draw_code_changes(
 
 /// Draw commit frequency plot with smoothed line series
 /// Includes glow effect for better visualization
-fn draw_commits(
-    chart_builder: &mut ChartContext<BitMapBackend, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+fn draw_commits<DB: DrawingBackend>(
+    chart_builder: &mut ChartContext<DB, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
     plot_data: &[(String, usize, usize)],
-) -> Result<(), PlotError> {
+) -> Result<(), PlotError>
+where
+    DB::ErrorType: 'static,
+{
     // Calculate commit counts
     let mut commit_counts = HashMap::new();
     for (date, _, _) in plot_data {
@@ -409,10 +1357,13 @@ fn draw_commits(
 }
 
 /// Draw code frequency plot with stacked bars for additions and deletions
-fn draw_code_frequency(
-    chart_builder: &mut ChartContext<BitMapBackend, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+fn draw_code_frequency<DB: DrawingBackend>(
+    chart_builder: &mut ChartContext<DB, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
     plot_data: &[(String, usize, usize)],
-) -> Result<(), PlotError> {
+) -> Result<(), PlotError>
+where
+    DB::ErrorType: 'static,
+{
     let bar_width = 0.8;
 
     // Draw additions (positive bars)
@@ -444,6 +1395,37 @@ fn draw_code_frequency(
     Ok(())
 }
 
+/// Draw additions and deletions as filled areas above and below the zero line
+fn draw_code_area<DB: DrawingBackend>(
+    chart_builder: &mut ChartContext<DB, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+    plot_data: &[(String, usize, usize)],
+) -> Result<(), PlotError>
+where
+    DB::ErrorType: 'static,
+{
+    let additions = plot_data
+        .iter()
+        .enumerate()
+        .map(|(i, (_, added, _))| (i as f64, *added as f64));
+    chart_builder
+        .draw_series(AreaSeries::new(additions, 0.0, GREEN.mix(0.4)).border_style(GREEN.mix(0.8)))?
+        .label("Additions")
+        .legend(move |(x, y)| {
+            Rectangle::new([(x, y - 5), (x + 20, y + 5)], GREEN.mix(0.4).filled())
+        });
+
+    let deletions = plot_data
+        .iter()
+        .enumerate()
+        .map(|(i, (_, _, deleted))| (i as f64, -(*deleted as f64)));
+    chart_builder
+        .draw_series(AreaSeries::new(deletions, 0.0, RED.mix(0.4)).border_style(RED.mix(0.8)))?
+        .label("Deletions")
+        .legend(move |(x, y)| Rectangle::new([(x, y - 5), (x + 20, y + 5)], RED.mix(0.4).filled()));
+
+    Ok(())
+}
+
 /// Calculate adaptive range for plot axis with outlier handling
 /// Returns (min, max) tuple with adjusted ranges to handle extreme values
 fn calculate_adaptive_range(values: &[f64]) -> (f64, f64) {
@@ -470,3 +1452,51 @@ fn calculate_adaptive_range(values: &[f64]) -> (f64, f64) {
 
     (0.0, display_max)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_y_axis_unit() {
+        assert_eq!(y_axis_unit("Commits"), "commits/day");
+        assert_eq!(y_axis_unit("Code Changes"), "lines");
+        assert_eq!(y_axis_unit("Code Frequency"), "lines");
+        assert_eq!(y_axis_unit("Commit Types"), "commits");
+        assert_eq!(y_axis_unit("Commits by Year"), "commits");
+        assert_eq!(y_axis_unit("Size Histogram"), "commits");
+        assert_eq!(y_axis_unit("Language Churn"), "lines");
+        assert_eq!(y_axis_unit("Commit Size Distribution"), "lines/commit");
+        assert_eq!(y_axis_unit("Contributor Comparison"), "commits/week");
+        assert_eq!(y_axis_unit("Calendar"), "commits/day");
+        assert_eq!(y_axis_unit("Avg Commit Size"), "lines/commit");
+        assert!(METRICS.iter().all(|metric| !y_axis_unit(metric).is_empty()));
+    }
+
+    #[test]
+    fn test_format_date() {
+        assert_eq!(format_date("2024-03-07", "%m/%d"), "03/07");
+        assert_eq!(format_date("2024-03-07", "%d/%m/%Y"), "07/03/2024");
+        assert_eq!(format_date("2024-03-07", DEFAULT_DATE_FORMAT), "2024-03-07");
+        // Invalid formats fall back to ISO dates
+        assert_eq!(format_date("2024-03-07", "%Q"), "2024-03-07");
+        assert_eq!(format_date("2024-03-07", ""), "2024-03-07");
+        assert_eq!(format_date("unknown", "%m/%d"), "unknown");
+    }
+
+    #[test]
+    fn test_nice_interval() {
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-9 * b;
+        assert!(close(nice_interval(100.0, 10), 10.0));
+        assert!(close(nice_interval(100.0, 5), 20.0));
+        assert!(close(nice_interval(73.0, 10), 10.0));
+        assert!(close(nice_interval(30.0, 10), 5.0));
+        assert!(close(nice_interval(1_500.0, 4), 500.0));
+        assert!(close(nice_interval(2_000_000.0, 10), 200_000.0));
+        assert!(close(nice_interval(0.8, 4), 0.2));
+        // Degenerate ranges fall back to a unit interval
+        assert_eq!(nice_interval(0.0, 10), 1.0);
+        assert_eq!(nice_interval(f64::NAN, 10), 1.0);
+        assert_eq!(nice_interval(100.0, 0), 1.0);
+    }
+}
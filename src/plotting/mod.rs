@@ -2,4 +2,4 @@ pub mod chart;
 mod styles;
 
 pub use chart::generate_plot_async;
-pub use styles::{ChartStyle, ChartTheme};
+pub use styles::{contributor_colors, ChartStyle, ChartTheme, ColorMode, LegendPosition};
@@ -1,3 +1,7 @@
+use plotters::chart::SeriesLabelPosition;
+use plotters::style::{Color, FontDesc, HSLColor, IntoFont, RGBColor};
+use std::collections::{HashMap, HashSet};
+
 /// Chart theme configuration
 pub struct ChartTheme {
     pub background_color: plotters::style::RGBAColor,
@@ -18,9 +22,13 @@ impl Default for ChartTheme {
 }
 
 /// Chart style configuration
+#[derive(Clone, Debug, PartialEq)]
 pub struct ChartStyle {
     pub line_width: u32,
+    /// Size of axis and bar labels; captions are drawn at twice this size
     pub font_size: u32,
+    /// Font family for all chart text, e.g. `"serif"` or an installed font's name
+    pub font_family: String,
     pub margin: u32,
     pub label_area_size: u32,
 }
@@ -30,8 +38,223 @@ impl Default for ChartStyle {
         Self {
             line_width: 2,
             font_size: 15,
+            font_family: "sans-serif".to_string(),
             margin: 10,
             label_area_size: 50,
         }
     }
 }
+
+impl ChartStyle {
+    /// Font for chart captions at a device pixel ratio of `scale`
+    pub fn caption_font(&self, scale: f64) -> FontDesc<'_> {
+        (
+            self.font_family.as_str(),
+            2.0 * self.font_size as f64 * scale,
+        )
+            .into_font()
+    }
+
+    /// Font for axis and bar labels at a device pixel ratio of `scale`
+    pub fn label_font(&self, scale: f64) -> FontDesc<'_> {
+        (self.font_family.as_str(), self.font_size as f64 * scale).into_font()
+    }
+}
+
+/// Corner of the chart the series legend is drawn in
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum LegendPosition {
+    UpperLeft,
+    #[default]
+    UpperRight,
+    LowerLeft,
+    LowerRight,
+}
+
+impl LegendPosition {
+    /// Every position, in the order they are offered in the UI
+    pub const ALL: [LegendPosition; 4] = [
+        LegendPosition::UpperLeft,
+        LegendPosition::UpperRight,
+        LegendPosition::LowerLeft,
+        LegendPosition::LowerRight,
+    ];
+
+    /// Name shown in the UI
+    pub fn label(self) -> &'static str {
+        match self {
+            LegendPosition::UpperLeft => "Upper Left",
+            LegendPosition::UpperRight => "Upper Right",
+            LegendPosition::LowerLeft => "Lower Left",
+            LegendPosition::LowerRight => "Lower Right",
+        }
+    }
+
+    /// The matching plotters position
+    pub fn series_label_position(self) -> SeriesLabelPosition {
+        match self {
+            LegendPosition::UpperLeft => SeriesLabelPosition::UpperLeft,
+            LegendPosition::UpperRight => SeriesLabelPosition::UpperRight,
+            LegendPosition::LowerLeft => SeriesLabelPosition::LowerLeft,
+            LegendPosition::LowerRight => SeriesLabelPosition::LowerRight,
+        }
+    }
+}
+
+/// Color space of rendered bitmap charts
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ColorMode {
+    #[default]
+    Color,
+    /// Shades of gray by luminance, e.g. for print
+    Grayscale,
+}
+
+impl ColorMode {
+    /// Every mode, in the order they are offered in the UI
+    pub const ALL: [ColorMode; 2] = [ColorMode::Color, ColorMode::Grayscale];
+
+    /// Name shown in the UI
+    pub fn label(self) -> &'static str {
+        match self {
+            ColorMode::Color => "Color",
+            ColorMode::Grayscale => "Grayscale",
+        }
+    }
+
+    /// Convert RGBA pixels in place; alpha is left untouched
+    pub fn apply(self, rgba: &mut [u8]) {
+        if self == ColorMode::Color {
+            return;
+        }
+        for pixel in rgba.chunks_exact_mut(4) {
+            // Rec. 709 luma weights
+            let luma =
+                0.2126 * pixel[0] as f64 + 0.7152 * pixel[1] as f64 + 0.0722 * pixel[2] as f64;
+            let gray = luma.round() as u8;
+            pixel[..3].fill(gray);
+        }
+    }
+}
+
+/// Number of evenly spaced hues contributor colors are picked from
+const HUE_SLOTS: u64 = 360;
+
+/// Smallest hue separation, in slots, probing insists on however many names there are
+const MIN_HUE_GAP: u64 = 10;
+
+/// Slots skipped when a name's hue is too close to a taken one
+///
+/// It is coprime with `HUE_SLOTS`, so probing reaches every hue, and close to the
+/// golden angle, so the probed hue is far from the taken one.
+const HUE_PROBE_STEP: u64 = 137;
+
+/// FNV-1a hash of `name`, which unlike `DefaultHasher` is the same on every build
+fn stable_hash(name: &str) -> u64 {
+    name.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Distance between two hue slots around the color wheel
+fn hue_distance(a: u64, b: u64) -> u64 {
+    let d = a.abs_diff(b);
+    d.min(HUE_SLOTS - d)
+}
+
+/// Hue slot of each distinct name, see [`contributor_colors`]
+fn contributor_hues(names: &[String]) -> HashMap<String, u64> {
+    let distinct: Vec<&String> = {
+        let mut seen = HashSet::new();
+        names.iter().filter(|name| seen.insert(*name)).collect()
+    };
+    // Placing names one by one always finds a hue this far from the others, since each
+    // placed name rules out less than twice the gap
+    let gap = (HUE_SLOTS / 2 / distinct.len().max(1) as u64).max(MIN_HUE_GAP);
+
+    let mut hues = HashMap::new();
+    for name in distinct {
+        let start = stable_hash(name) % HUE_SLOTS;
+        let nearest = |slot: u64| {
+            hues.values()
+                .map(|&taken| hue_distance(slot, taken))
+                .min()
+                .unwrap_or(HUE_SLOTS)
+        };
+        let probed = (0..HUE_SLOTS).map(|i| (start + i * HUE_PROBE_STEP) % HUE_SLOTS);
+        // With too many names for the minimum gap, take the hue furthest from the rest
+        let slot = probed
+            .clone()
+            .find(|&slot| nearest(slot) >= gap)
+            .or_else(|| probed.max_by_key(|&slot| nearest(slot)))
+            .unwrap_or(start);
+        hues.insert(name.clone(), slot);
+    }
+    hues
+}
+
+/// Assign each contributor a distinct color for multi-series charts
+///
+/// The hue is derived from a hash of the name, so a contributor keeps their color
+/// across charts and lists. When a name's hue is too close to that of a name earlier in
+/// `names`, it probes for one further away: hues are kept at least 180° divided by the
+/// number of names apart, or 10° once there are more than 18 names, as far as the color
+/// wheel allows. Only such names change color between lists, so pass the most
+/// prominent names first. Repeated names keep the color of their first occurrence.
+pub fn contributor_colors(names: &[String]) -> HashMap<String, RGBColor> {
+    contributor_hues(names)
+        .into_iter()
+        .map(|(name, slot)| {
+            let (r, g, b) = HSLColor(slot as f64 / HUE_SLOTS as f64, 0.7, 0.6).rgb();
+            (name, RGBColor(r, g, b))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_contributor_colors() {
+        let names: Vec<String> = (0..12).map(|i| format!("Author {}", i)).collect();
+
+        let colors = contributor_colors(&names);
+        assert_eq!(colors, contributor_colors(&names));
+        assert_eq!(colors.len(), names.len());
+
+        let hues: Vec<u64> = contributor_hues(&names).into_values().collect();
+        let min_separation = hues
+            .iter()
+            .enumerate()
+            .flat_map(|(i, &a)| hues[i + 1..].iter().map(move |&b| hue_distance(a, b)))
+            .min()
+            .unwrap();
+        assert!(
+            min_separation >= 180 / 12,
+            "hues only {}° apart",
+            min_separation
+        );
+    }
+
+    #[test]
+    fn test_contributor_colors_stable_across_lists() {
+        let to_names = |names: &[&str]| names.iter().map(|name| name.to_string()).collect();
+        let first: Vec<String> = to_names(&["Alice", "Bob"]);
+        let second: Vec<String> = to_names(&["Bob", "Judy", "Alice"]);
+
+        let (first, second) = (contributor_colors(&first), contributor_colors(&second));
+        assert_eq!(second["Alice"], first["Alice"]);
+        assert_eq!(second["Bob"], first["Bob"]);
+    }
+
+    #[test]
+    fn test_grayscale_color_mode() {
+        let mut pixels = vec![255, 0, 0, 255, 10, 200, 30, 128];
+        ColorMode::Color.apply(&mut pixels);
+        assert_eq!(pixels, vec![255, 0, 0, 255, 10, 200, 30, 128]);
+
+        ColorMode::Grayscale.apply(&mut pixels);
+        assert_eq!(pixels, vec![54, 54, 54, 255, 147, 147, 147, 128]);
+    }
+}
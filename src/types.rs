@@ -3,13 +3,15 @@
 //! This module contains the common types used throughout the application for
 //! representing Git repository analysis results and caching.
 
-use std::collections::HashMap;
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 /// A key used for caching analysis results based on branch and contributor.
 ///
 /// This struct is used as a key in the cache to store analysis results for specific
 /// combinations of branch and contributor names.
-#[derive(Clone, Hash, Eq, PartialEq)]
+#[derive(Clone, Hash, Eq, PartialEq, Serialize, Deserialize)]
 pub struct CacheKey {
     /// The name of the Git branch
     pub branch: String,
@@ -17,11 +19,467 @@ pub struct CacheKey {
     pub contributor: String,
 }
 
+/// How precisely line changes are counted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AccuracyMode {
+    /// Minimal patience diffs, with merge commits counted against their largest parent diff
+    #[default]
+    Fast,
+    /// Match `git log --numstat --diff-merges=first-parent` exactly: git's default diff
+    /// algorithm, rename detection, whitespace always counted, and merge commits diffed
+    /// against their first parent only
+    GitNumstat,
+}
+
+/// How commit chunks are processed in parallel.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Parallelism {
+    /// Tokio `spawn_blocking` tasks, limited to a share of the CPUs by a semaphore
+    #[default]
+    Tokio,
+    /// Rayon's work-stealing thread pool, which doesn't compete with tokio's blocking
+    /// pool for threads
+    Rayon,
+}
+
+/// Kind of git reference offered for analysis.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RefKind {
+    /// A local branch
+    Branch,
+    /// A lightweight or annotated tag
+    Tag,
+}
+
+/// Options controlling how commits are diffed and counted during analysis.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnalysisOptions {
+    /// Ignore whitespace-only changes when counting lines (like `git log -w`).
+    /// Off by default to match `git log --numstat`.
+    pub ignore_whitespace: bool,
+    /// Don't count lines whose only change is their line ending (CRLF vs LF). This uses
+    /// git's ignore-whitespace-at-eol, so other trailing whitespace changes are ignored too.
+    pub normalize_line_endings: bool,
+    /// Authors to leave out of every statistic, e.g. bots. An author is excluded when
+    /// any entry is a case-insensitive substring of their name or email.
+    pub exclude_authors: Vec<String>,
+    /// Paths whose changes are left out of line counts, e.g. `vendor` or `node_modules`.
+    /// Each entry matches whole path components anywhere in a file's path.
+    pub exclude_paths: Vec<String>,
+    /// Analyze only this subdirectory, e.g. `packages/foo` in a monorepo, as if it were
+    /// the repository root. Commits that don't change anything under it are left out.
+    pub scope_path: Option<String>,
+    /// How precisely line changes are counted
+    pub accuracy: AccuracyMode,
+    /// Diffs touching more files than this are counted from the diff's overall stats
+    /// instead of hunk by hunk, which is cheaper for huge generated commits. Rename
+    /// detection doesn't apply to such diffs. Path exclusions still do, though with
+    /// exclusions set each remaining file is diffed to count its lines.
+    pub stat_only_threshold: Option<usize>,
+    /// Count every diff from its overall stats, as [`Self::stat_only_threshold`] does
+    /// for huge diffs, for roughly twice the throughput. Line totals match the
+    /// hunk-by-hunk count in `Fast` mode, but rename detection doesn't apply, and no
+    /// per-file data is kept, so the per-extension breakdown, primary language and file
+    /// hotspots stay empty. Path exclusions still apply, but diff each remaining file,
+    /// which gives up most of the speedup.
+    pub fast_stats: bool,
+    /// Files whose old or new version is larger than this many bytes, e.g. minified
+    /// bundles, are counted like binary files: their lines aren't diffed or counted.
+    /// Counted in [`AnalysisResult::large_files_skipped`].
+    pub max_file_size_bytes: Option<u64>,
+    /// Attach running totals and each finished chunk's activity to progress updates,
+    /// so a caller can show partial results while the analysis is still running
+    pub stream_partials: bool,
+    /// Only analyze commits made on or after this date (UTC)
+    pub since: Option<NaiveDate>,
+    /// Only analyze commits made on or before this date (UTC)
+    pub until: Option<NaiveDate>,
+    /// Only analyze commits not reachable from this commit (a hex ID), like
+    /// `git log <since_commit>..<branch>`. Used to extend an earlier result with the
+    /// commits made since.
+    pub since_commit: Option<String>,
+    /// Branch list the caller already has. When set it is returned as-is in
+    /// `available_branches` and the repository's branches are not enumerated again.
+    pub known_branches: Option<Vec<String>>,
+    /// Also credit people named in `Co-authored-by:` trailers in the contributor counts
+    ///
+    /// Only `top_contributors` includes the co-authors. Every other per-author statistic
+    /// (activity, first commit dates, weekly and monthly counts, contributor filtering)
+    /// still attributes each commit to its author alone.
+    pub count_coauthors: bool,
+    /// Detect renamed files, so [`AnalysisResult::file_hotspots`] counts a file's
+    /// commits from before and after a rename under its latest path. In `Fast` mode this
+    /// also counts a renamed file's changed lines rather than all of its lines, like
+    /// `GitNumstat` mode does, at the cost of slower diffs.
+    pub follow_renames: bool,
+    /// Follow only the first parent of merge commits, like `git log --first-parent`,
+    /// so commits reachable only through merged branches are left out
+    pub first_parent: bool,
+    /// Walk history oldest commit first, like `git log --reverse`, so commits are
+    /// processed and streamed in chronological order. `commit_activity` then keeps the
+    /// walk order instead of being sorted by date, commit time and commit ID, so commits
+    /// made in the same second stay in history order.
+    pub oldest_first: bool,
+    /// Treat author names that differ only in case or whitespace as one contributor,
+    /// e.g. "John Smith" and "john  smith", counting them under the most common spelling
+    pub fuzzy_dedup: bool,
+    /// Leave root commits (those without parents) out of line totals and activity.
+    /// The initial commit often imports a whole codebase, and its line spike would
+    /// dwarf everything else. It still counts towards commit and contributor totals.
+    pub skip_initial_commit: bool,
+    /// List tags after the branches in [`AnalysisResult::available_branches`], so a
+    /// tag can be selected and analyzed like a branch
+    pub include_tags: bool,
+    /// Thread pool used to diff commits; both produce identical results
+    pub parallelism: Parallelism,
+    /// Record an activity entry per commit. On by default; turning it off saves memory
+    /// on very large repositories when only the totals are needed, but leaves
+    /// `commit_activity` and every statistic derived from it empty.
+    pub collect_activity: bool,
+    /// Leave empty commits (see [`AnalysisResult::empty_commit_count`]) out of commit
+    /// and contributor totals and activity. They are still counted as empty commits.
+    pub exclude_empty_commits: bool,
+    /// Count only commits that change at least one code file, leaving out commits that
+    /// only touch docs or config, e.g. to measure engineering velocity
+    pub code_only: bool,
+    /// Extensions (without the dot, matched case-insensitively) that `code_only` treats
+    /// as docs or config rather than code. Files without an extension count as code.
+    pub non_code_extensions: Vec<String>,
+    /// Analyze only this fraction (between 0 and 1) of the commits for quick estimates
+    /// on huge repositories. Commits are picked by their ID, so the same ones are
+    /// sampled every run. Commit and line totals are scaled up to the whole history;
+    /// activity and everything derived from it cover only the sample. Results are
+    /// marked [`AnalysisResult::approximate`]. Values outside (0, 1) analyze every
+    /// commit.
+    pub sample: Option<f64>,
+    /// Replace contributor names with stable pseudonyms (`Contributor 1`, `Contributor 2`,
+    /// ... in descending commit count, ties by name) everywhere in the result, for
+    /// sharing stats publicly. Contributor emails are left out entirely.
+    pub anonymize: bool,
+}
+
+/// Extensions treated as docs or config rather than code unless overridden with
+/// [`AnalysisOptions::non_code_extensions`]
+pub const DEFAULT_NON_CODE_EXTENSIONS: &[&str] = &[
+    "md", "markdown", "rst", "adoc", "txt", "yaml", "yml", "json", "toml", "ini", "cfg", "conf",
+    "xml", "lock",
+];
+
+impl Default for AnalysisOptions {
+    fn default() -> Self {
+        Self {
+            ignore_whitespace: false,
+            normalize_line_endings: false,
+            exclude_authors: Vec::new(),
+            exclude_paths: Vec::new(),
+            scope_path: None,
+            accuracy: AccuracyMode::default(),
+            stat_only_threshold: None,
+            fast_stats: false,
+            max_file_size_bytes: None,
+            stream_partials: false,
+            since: None,
+            until: None,
+            since_commit: None,
+            known_branches: None,
+            count_coauthors: false,
+            follow_renames: false,
+            first_parent: false,
+            oldest_first: false,
+            fuzzy_dedup: false,
+            skip_initial_commit: false,
+            include_tags: false,
+            parallelism: Parallelism::default(),
+            collect_activity: true,
+            exclude_empty_commits: false,
+            code_only: false,
+            non_code_extensions: DEFAULT_NON_CODE_EXTENSIONS
+                .iter()
+                .map(|extension| extension.to_string())
+                .collect(),
+            sample: None,
+            anonymize: false,
+        }
+    }
+}
+
+impl AnalysisOptions {
+    /// Start building options from the defaults
+    pub fn builder() -> AnalysisOptionsBuilder {
+        AnalysisOptionsBuilder::default()
+    }
+}
+
+/// Fluent builder for [`AnalysisOptions`]; every setting not set keeps its default
+#[derive(Clone, Debug, Default)]
+pub struct AnalysisOptionsBuilder {
+    options: AnalysisOptions,
+}
+
+impl AnalysisOptionsBuilder {
+    /// Ignore whitespace-only changes when counting lines
+    pub fn ignore_whitespace(mut self, ignore_whitespace: bool) -> Self {
+        self.options.ignore_whitespace = ignore_whitespace;
+        self
+    }
+
+    /// Don't count lines whose only change is their line ending
+    pub fn normalize_line_endings(mut self, normalize_line_endings: bool) -> Self {
+        self.options.normalize_line_endings = normalize_line_endings;
+        self
+    }
+
+    /// Set how precisely line changes are counted
+    pub fn accuracy(mut self, accuracy: AccuracyMode) -> Self {
+        self.options.accuracy = accuracy;
+        self
+    }
+
+    /// Attach partial results to progress updates
+    pub fn stream_partials(mut self, stream_partials: bool) -> Self {
+        self.options.stream_partials = stream_partials;
+        self
+    }
+
+    /// Credit people named in `Co-authored-by:` trailers in the contributor counts only,
+    /// see [`AnalysisOptions::count_coauthors`]
+    pub fn count_coauthors(mut self, count_coauthors: bool) -> Self {
+        self.options.count_coauthors = count_coauthors;
+        self
+    }
+
+    /// Follow files across renames when counting hotspots
+    pub fn follow_renames(mut self, follow_renames: bool) -> Self {
+        self.options.follow_renames = follow_renames;
+        self
+    }
+
+    /// Follow only the first parent of merge commits
+    pub fn first_parent(mut self, first_parent: bool) -> Self {
+        self.options.first_parent = first_parent;
+        self
+    }
+
+    /// Walk history oldest commit first
+    pub fn oldest_first(mut self, oldest_first: bool) -> Self {
+        self.options.oldest_first = oldest_first;
+        self
+    }
+
+    /// Merge author names that differ only in case or whitespace
+    pub fn fuzzy_dedup(mut self, fuzzy_dedup: bool) -> Self {
+        self.options.fuzzy_dedup = fuzzy_dedup;
+        self
+    }
+
+    /// Leave root commits out of line totals and activity
+    pub fn skip_initial_commit(mut self, skip_initial_commit: bool) -> Self {
+        self.options.skip_initial_commit = skip_initial_commit;
+        self
+    }
+
+    /// List tags alongside branches
+    pub fn include_tags(mut self, include_tags: bool) -> Self {
+        self.options.include_tags = include_tags;
+        self
+    }
+
+    /// Record a per-commit activity entry, or keep only the totals
+    pub fn collect_activity(mut self, collect_activity: bool) -> Self {
+        self.options.collect_activity = collect_activity;
+        self
+    }
+
+    /// Leave commits that change nothing out of the totals
+    pub fn exclude_empty_commits(mut self, exclude_empty_commits: bool) -> Self {
+        self.options.exclude_empty_commits = exclude_empty_commits;
+        self
+    }
+
+    /// Count only commits that change at least one code file
+    pub fn code_only(mut self, code_only: bool) -> Self {
+        self.options.code_only = code_only;
+        self
+    }
+
+    /// Replace the extensions `code_only` treats as docs or config
+    pub fn non_code_extensions<I, S>(mut self, extensions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.options.non_code_extensions = extensions.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Choose the thread pool used to diff commits
+    pub fn parallelism(mut self, parallelism: Parallelism) -> Self {
+        self.options.parallelism = parallelism;
+        self
+    }
+
+    /// Leave out authors matching any of these case-insensitive substrings
+    pub fn exclude_authors<I, S>(mut self, authors: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.options.exclude_authors = authors.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Leave changes under these path components out of line counts
+    pub fn exclude_paths<I, S>(mut self, paths: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.options.exclude_paths = paths.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Analyze only the given subdirectory, as if it were the repository root
+    pub fn scope_path(mut self, path: impl Into<String>) -> Self {
+        self.options.scope_path = Some(path.into());
+        self
+    }
+
+    /// Only analyze commits made on or after this date (UTC)
+    pub fn since(mut self, since: NaiveDate) -> Self {
+        self.options.since = Some(since);
+        self
+    }
+
+    /// Only analyze commits made on or before this date (UTC)
+    pub fn until(mut self, until: NaiveDate) -> Self {
+        self.options.until = Some(until);
+        self
+    }
+
+    /// Only analyze commits not reachable from this commit
+    pub fn since_commit(mut self, commit: impl Into<String>) -> Self {
+        self.options.since_commit = Some(commit.into());
+        self
+    }
+
+    /// Count diffs touching more than `threshold` files from their overall stats
+    pub fn stat_only_threshold(mut self, threshold: usize) -> Self {
+        self.options.stat_only_threshold = Some(threshold);
+        self
+    }
+
+    /// Count every diff from its overall stats instead of hunk by hunk, see
+    /// [`AnalysisOptions::fast_stats`]
+    pub fn fast_stats(mut self, fast_stats: bool) -> Self {
+        self.options.fast_stats = fast_stats;
+        self
+    }
+
+    /// Don't diff files larger than `bytes`, counting them like binary files
+    pub fn max_file_size_bytes(mut self, bytes: u64) -> Self {
+        self.options.max_file_size_bytes = Some(bytes);
+        self
+    }
+
+    /// Analyze only the given fraction of commits and scale the totals up
+    pub fn sample(mut self, rate: f64) -> Self {
+        self.options.sample = Some(rate);
+        self
+    }
+
+    /// Replace contributor names with pseudonyms ordered by commit count
+    pub fn anonymize(mut self, anonymize: bool) -> Self {
+        self.options.anonymize = anonymize;
+        self
+    }
+
+    /// Reuse a branch list the caller already has instead of enumerating branches again
+    pub fn known_branches(mut self, branches: Vec<String>) -> Self {
+        self.options.known_branches = Some(branches);
+        self
+    }
+
+    /// Finish building the options
+    pub fn build(self) -> AnalysisOptions {
+        self.options
+    }
+}
+
+/// A single commit's entry in the activity timeline.
+#[derive(Clone, Debug, Default, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub struct CommitActivity {
+    /// Commit date formatted as `YYYY-MM-DD`
+    pub date: String,
+    /// Commit time in seconds since the Unix epoch, which orders commits within a day
+    pub timestamp: i64,
+    /// Hex ID of the commit, empty for entries not read from a repository
+    pub oid: String,
+    /// Lines added by the commit
+    pub lines_added: usize,
+    /// Lines deleted by the commit
+    pub lines_deleted: usize,
+    /// Name of the commit author
+    pub author: String,
+    /// Conventional-commit type from the message prefix (e.g. `feat`, `fix`), if any
+    pub commit_type: Option<String>,
+    /// Whether the commit reverts an earlier one, as written by `git revert`
+    pub is_revert: bool,
+    /// Whether the commit changes nothing, see [`AnalysisResult::empty_commit_count`]
+    pub is_empty: bool,
+    /// Lines added and deleted per file extension (lowercase, without the dot)
+    pub lines_by_extension: BTreeMap<String, (usize, usize)>,
+    /// Paths of the files the commit changed, after any rename
+    pub files: Vec<String>,
+    /// Files the commit renamed as `(old path, new path)`, only detected with
+    /// [`AnalysisOptions::follow_renames`]
+    pub renames: Vec<(String, String)>,
+}
+
+impl CommitActivity {
+    /// Create an activity entry for a commit
+    ///
+    /// The timestamp is midnight UTC of `date`, or 0 if it isn't a `YYYY-MM-DD` date.
+    pub fn new(date: &str, lines_added: usize, lines_deleted: usize, author: &str) -> Self {
+        let timestamp = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .ok()
+            .and_then(|day| day.and_hms_opt(0, 0, 0))
+            .map_or(0, |midnight| midnight.and_utc().timestamp());
+        Self {
+            date: date.to_string(),
+            timestamp,
+            oid: String::new(),
+            lines_added,
+            lines_deleted,
+            author: author.to_string(),
+            commit_type: None,
+            is_revert: false,
+            is_empty: false,
+            lines_by_extension: BTreeMap::new(),
+            files: Vec::new(),
+            renames: Vec::new(),
+        }
+    }
+}
+
+/// How contributor names are combined when merging results from several repositories.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MergeStrategy {
+    /// Treat identical names as the same person and sum their counts
+    #[default]
+    Combine,
+    /// Label every contributor with their repository, e.g. `"Alice (billing)"`
+    KeepSeparate,
+}
+
 /// The result of analyzing a Git repository.
 ///
 /// This struct contains all the statistics and metrics collected from analyzing
 /// a Git repository, including commit counts, line changes, and contributor information.
-#[derive(Clone, Debug, Default)]
+///
+/// Results serialize to JSON for on-disk caching. The contributor ratios and weekly
+/// counts are left out, since JSON can't hold infinite ratios or tuple keys; rebuild
+/// them from `commit_activity` after deserializing.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct AnalysisResult {
     /// Total number of commits analyzed
     pub commit_count: usize,
@@ -31,30 +489,144 @@ pub struct AnalysisResult {
     pub total_lines_deleted: usize,
     /// List of top contributors and their commit counts
     pub top_contributors: Vec<(String, usize)>,
-    /// Chronological list of commit activity (date, lines added, lines deleted)
-    pub commit_activity: Vec<(String, usize, usize)>,
+    /// Commit count of every contributor, not only the top ones, so merged results can
+    /// rank contributors who were outside either side's top list
+    pub contributor_commit_counts: HashMap<String, usize>,
+    /// Chronological list of per-commit activity, sorted by date, commit time and commit ID
+    pub commit_activity: Vec<CommitActivity>,
     /// Average number of lines changed per commit
     pub average_commit_size: f64,
     /// Commit frequency by time period (e.g., by week)
     pub commit_frequency: HashMap<String, usize>,
+    /// Commits per month (`YYYY-MM`) for each of the top contributors
+    /// (author -> month -> count). Other authors aren't tracked, to bound memory.
+    pub author_commit_frequency: HashMap<String, HashMap<String, usize>>,
     /// List of top contributors sorted by lines of code
     pub top_contributors_by_lines: Vec<(String, usize)>,
     /// List of available branches in the repository
     pub available_branches: Vec<String>,
+    /// Branch that was analyzed, or [`DETACHED_HEAD`] when the requested branch
+    /// couldn't be resolved and a detached HEAD was analyzed instead
+    ///
+    /// [`DETACHED_HEAD`]: crate::analysis::DETACHED_HEAD
+    pub selected_branch: String,
     /// Time taken to analyze the repository (in seconds)
     pub elapsed_time: f64,
     /// Detailed processing statistics
     pub processing_stats: String,
+    /// Number of commits per conventional-commit type (`feat`, `fix`, `docs`, ...).
+    /// Commits without a conventional prefix are not counted.
+    pub commit_type_counts: HashMap<String, usize>,
+    /// Number of commits per calendar year (UTC), in ascending year order
+    pub commits_by_year: BTreeMap<i32, usize>,
+    /// Number of commits per size bucket, by lines added plus deleted, on a log scale:
+    /// `0`, `1-10`, `11-100`, `101-1000` and `1000+` (more than 1000 lines). Every
+    /// bucket is listed, smallest first, even when empty.
+    pub commit_size_histogram: Vec<(String, usize)>,
+    /// Date of each contributor's earliest commit (author -> `YYYY-MM-DD`)
+    pub author_first_commit: HashMap<String, String>,
+    /// Primary email of each contributor (author -> email). When an author committed
+    /// under several addresses, the one used most often is kept.
+    pub contributor_emails: HashMap<String, String>,
+    /// Lines added divided by lines deleted for each contributor. Authors who never
+    /// deleted a line report `f64::INFINITY`.
+    #[serde(skip)]
+    pub contributor_add_delete_ratio: HashMap<String, f64>,
+    /// Commits per ISO week for each contributor (author -> `(iso_year, iso_week)` -> count)
+    #[serde(skip)]
+    pub weekly_commits_by_author: HashMap<String, BTreeMap<(i32, u32), usize>>,
+    /// Commits left out of every statistic because their tree or diff couldn't be read,
+    /// e.g. due to missing objects in a corrupt repository
+    pub skipped_commits: usize,
+    /// Number of commits that revert an earlier commit
+    pub revert_count: usize,
+    /// Changed files whose lines weren't counted because they exceed
+    /// [`AnalysisOptions::max_file_size_bytes`], once per commit changing them
+    pub large_files_skipped: usize,
+    /// Commits that change nothing, e.g. made with `git commit --allow-empty` or merges
+    /// that bring in nothing new: no lines added or deleted and the same tree as their
+    /// first parent. Counted even when [`AnalysisOptions::exclude_empty_commits`] is set.
+    pub empty_commit_count: usize,
+    /// Lines added and deleted per file extension (lowercase, without the dot)
+    pub lines_by_extension: BTreeMap<String, (usize, usize)>,
+    /// Extension with the most changed lines, e.g. `"rs"`, taken as the main language
+    pub primary_language: Option<String>,
+    /// Files changed by the most commits as `(path, commits)`, most first and ties in
+    /// path order. With [`AnalysisOptions::follow_renames`], commits from before a
+    /// rename count under the file's latest path.
+    pub file_hotspots: Vec<(String, usize)>,
+    /// Whether the totals are estimated from a sample of the commits, see
+    /// [`AnalysisOptions::sample`]
+    pub approximate: bool,
+    /// URL of the repository's `origin` remote, if it has one
+    pub remote_url: Option<String>,
+    /// Number of commits per parent count: 0 for root commits, 1 for regular commits,
+    /// 2 for merges and more for octopus merges. Views from
+    /// [`AnalysisResult::filtered_by_author`] keep the whole result's distribution.
+    pub parent_count_distribution: HashMap<usize, usize>,
+}
+
+/// Change between two analysis snapshots, as returned by [`AnalysisResult::diff`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AnalysisDelta {
+    /// Change in the number of commits
+    pub commit_count: i64,
+    /// Change in total lines added
+    pub lines_added: i64,
+    /// Change in total lines deleted
+    pub lines_deleted: i64,
+    /// Contributors present now but not in the previous snapshot, sorted by name
+    pub new_contributors: Vec<String>,
+}
+
+/// Headline numbers of an analysis, as returned by [`AnalysisResult::summary`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Summary {
+    /// Number of commits analyzed
+    pub commits: usize,
+    /// Total lines added
+    pub added: usize,
+    /// Total lines deleted
+    pub deleted: usize,
+    /// Average number of lines changed per commit
+    pub avg_size: f64,
+    /// Number of distinct contributors
+    pub contributors: usize,
+    /// Days between the first and last commit in the activity timeline
+    pub span_days: i64,
+}
+
+/// Statistics accumulated so far by an analysis that is still running.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PartialStats {
+    /// Commits counted so far
+    pub commit_count: usize,
+    /// Lines added so far
+    pub lines_added: usize,
+    /// Lines deleted so far
+    pub lines_deleted: usize,
+    /// Activity entries from the chunk that just finished, not the running total
+    pub activity: Vec<CommitActivity>,
 }
 
 /// Progress estimation for long-running operations
-#[derive(Debug, Clone)]
+///
+/// Serializes with `total_commits` and `processed_commits` as `total` and `processed`,
+/// and without the partial results.
+#[derive(Debug, Clone, Serialize)]
 pub struct ProgressEstimate {
+    #[serde(rename = "total")]
     pub total_commits: usize,
+    #[serde(rename = "processed")]
     pub processed_commits: usize,
     pub estimated_total_time: f64,
     pub elapsed_time: f64,
     pub commits_per_second: f64,
+    /// Running totals, present when [`AnalysisOptions::stream_partials`] is set
+    #[serde(skip)]
+    pub partial: Option<PartialStats>,
+    /// Date (`YYYY-MM-DD`) of the last commit in the chunk that just finished
+    pub current_date: Option<String>,
 }
 
 impl ProgressEstimate {
@@ -75,3 +647,922 @@ impl ProgressEstimate {
         }
     }
 }
+
+impl AnalysisResult {
+    /// Derive a contributor-scoped view of this result without re-walking the repository
+    ///
+    /// This is only meaningful on a result analyzed for "All" contributors, since it
+    /// filters the stored per-commit activity down to the given author. Returns `None`
+    /// when the activity can't stand in for the commits (see
+    /// [`AnalysisResult::has_complete_activity`]), since the view would under-report.
+    pub fn filtered_by_author(&self, author: &str) -> Option<AnalysisResult> {
+        if !self.has_complete_activity() {
+            return None;
+        }
+        let commit_activity: Vec<CommitActivity> = self
+            .commit_activity
+            .iter()
+            .filter(|activity| activity.author == author)
+            .cloned()
+            .collect();
+
+        let commit_count = commit_activity.len();
+        let total_lines_added = commit_activity.iter().map(|a| a.lines_added).sum();
+        let total_lines_deleted = commit_activity.iter().map(|a| a.lines_deleted).sum();
+        let average_commit_size = if commit_count > 0 {
+            (total_lines_added + total_lines_deleted) as f64 / commit_count as f64
+        } else {
+            0.0
+        };
+        let top_contributors = if commit_count > 0 {
+            vec![(author.to_string(), commit_count)]
+        } else {
+            Vec::new()
+        };
+        let lines_by_extension = extension_line_counts(&commit_activity);
+
+        Some(AnalysisResult {
+            commit_count,
+            total_lines_added,
+            total_lines_deleted,
+            contributor_commit_counts: top_contributors.iter().cloned().collect(),
+            top_contributors: top_contributors.clone(),
+            commit_frequency: monthly_commit_frequency(&commit_activity),
+            author_commit_frequency: author_commit_frequency(&commit_activity, &top_contributors),
+            commit_type_counts: commit_type_counts(&commit_activity),
+            revert_count: revert_count(&commit_activity),
+            empty_commit_count: commit_activity.iter().filter(|a| a.is_empty).count(),
+            primary_language: primary_language(&lines_by_extension),
+            file_hotspots: file_hotspots(&commit_activity),
+            lines_by_extension,
+            commits_by_year: yearly_commit_counts(&commit_activity),
+            commit_size_histogram: commit_size_histogram(&commit_activity),
+            contributor_add_delete_ratio: add_delete_ratios(&commit_activity),
+            weekly_commits_by_author: weekly_author_commits(&commit_activity),
+            commit_activity,
+            average_commit_size,
+            top_contributors_by_lines: top_contributors,
+            available_branches: self.available_branches.clone(),
+            selected_branch: self.selected_branch.clone(),
+            elapsed_time: self.elapsed_time,
+            processing_stats: self.processing_stats.clone(),
+            skipped_commits: self.skipped_commits,
+            large_files_skipped: self.large_files_skipped,
+            approximate: self.approximate,
+            remote_url: self.remote_url.clone(),
+            parent_count_distribution: self.parent_count_distribution.clone(),
+            author_first_commit: self
+                .author_first_commit
+                .iter()
+                .filter(|(name, _)| name.as_str() == author)
+                .map(|(name, date)| (name.clone(), date.clone()))
+                .collect(),
+            contributor_emails: self
+                .contributor_emails
+                .iter()
+                .filter(|(name, _)| name.as_str() == author)
+                .map(|(name, email)| (name.clone(), email.clone()))
+                .collect(),
+        })
+    }
+
+    /// Whether every counted commit has an exact activity entry, so statistics can be
+    /// rebuilt from `commit_activity` alone
+    ///
+    /// Not the case for sampled results, whose activity is never scaled up, nor when
+    /// [`AnalysisOptions::collect_activity`] was off or
+    /// [`AnalysisOptions::skip_initial_commit`] left root commits out of the activity.
+    pub fn has_complete_activity(&self) -> bool {
+        !self.approximate && self.commit_activity.len() == self.commit_count
+    }
+
+    /// Combine another result into this one, e.g. from another repository or from
+    /// commits made since this result was computed
+    ///
+    /// Counts and line totals are summed, activity is concatenated and re-sorted by date
+    /// and commit, and every statistic derived from activity is rebuilt. Contributor
+    /// rankings are rebuilt from the summed per-contributor commit counts, keeping the
+    /// earliest first-commit date and this result's email for authors on both sides. The branch
+    /// list gains `other`'s new branches; timing and processing stats are left as they
+    /// are.
+    pub fn merge(&mut self, other: &AnalysisResult) {
+        self.commit_count += other.commit_count;
+        self.total_lines_added += other.total_lines_added;
+        self.total_lines_deleted += other.total_lines_deleted;
+        self.skipped_commits += other.skipped_commits;
+        self.large_files_skipped += other.large_files_skipped;
+        self.empty_commit_count += other.empty_commit_count;
+        self.approximate |= other.approximate;
+        for (parents, count) in &other.parent_count_distribution {
+            *self.parent_count_distribution.entry(*parents).or_insert(0) += count;
+        }
+        if self.selected_branch.is_empty() {
+            self.selected_branch = other.selected_branch.clone();
+        }
+        if self.remote_url.is_none() {
+            self.remote_url = other.remote_url.clone();
+        }
+        for branch in &other.available_branches {
+            if !self.available_branches.contains(branch) {
+                self.available_branches.push(branch.clone());
+            }
+        }
+
+        for (author, date) in &other.author_first_commit {
+            let first = self
+                .author_first_commit
+                .entry(author.clone())
+                .or_insert_with(|| date.clone());
+            if *date < *first {
+                *first = date.clone();
+            }
+        }
+        for (author, email) in &other.contributor_emails {
+            self.contributor_emails
+                .entry(author.clone())
+                .or_insert_with(|| email.clone());
+        }
+
+        for (author, count) in &other.contributor_commit_counts {
+            *self
+                .contributor_commit_counts
+                .entry(author.clone())
+                .or_insert(0) += count;
+        }
+        let mut top_contributors: Vec<(String, usize)> = self
+            .contributor_commit_counts
+            .iter()
+            .map(|(author, count)| (author.clone(), *count))
+            .collect();
+        top_contributors.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        top_contributors.truncate(5);
+
+        self.commit_activity
+            .extend(other.commit_activity.iter().cloned());
+        sort_activity(&mut self.commit_activity);
+        let activity = &self.commit_activity;
+        self.commit_frequency = monthly_commit_frequency(activity);
+        self.author_commit_frequency = author_commit_frequency(activity, &top_contributors);
+        self.commit_type_counts = commit_type_counts(activity);
+        self.revert_count = revert_count(activity);
+        self.lines_by_extension = extension_line_counts(activity);
+        self.primary_language = primary_language(&self.lines_by_extension);
+        self.file_hotspots = file_hotspots(activity);
+        self.commits_by_year = yearly_commit_counts(activity);
+        self.commit_size_histogram = commit_size_histogram(activity);
+        self.contributor_add_delete_ratio = add_delete_ratios(activity);
+        self.weekly_commits_by_author = weekly_author_commits(activity);
+
+        self.average_commit_size = if self.commit_count > 0 {
+            (self.total_lines_added + self.total_lines_deleted) as f64 / self.commit_count as f64
+        } else {
+            0.0
+        };
+        self.top_contributors = top_contributors.clone();
+        self.top_contributors_by_lines = top_contributors;
+    }
+
+    /// Compare this result against an earlier snapshot of the same repository
+    pub fn diff(&self, previous: &AnalysisResult) -> AnalysisDelta {
+        let previous_contributors = previous.contributor_names();
+        let mut new_contributors: Vec<String> = self
+            .contributor_names()
+            .into_iter()
+            .filter(|name| !previous_contributors.contains(name))
+            .collect();
+        new_contributors.sort();
+
+        AnalysisDelta {
+            commit_count: self.commit_count as i64 - previous.commit_count as i64,
+            lines_added: self.total_lines_added as i64 - previous.total_lines_added as i64,
+            lines_deleted: self.total_lines_deleted as i64 - previous.total_lines_deleted as i64,
+            new_contributors,
+        }
+    }
+
+    /// Collect the headline numbers of this result into a single [`Summary`]
+    ///
+    /// `span_days` is measured between the earliest and latest activity dates, and is 0
+    /// when there is at most one dated commit.
+    pub fn summary(&self) -> Summary {
+        let dates: Vec<NaiveDate> = self
+            .commit_activity
+            .iter()
+            .filter_map(|entry| NaiveDate::parse_from_str(&entry.date, "%Y-%m-%d").ok())
+            .collect();
+        let span_days = match (dates.iter().min(), dates.iter().max()) {
+            (Some(first), Some(last)) => (*last - *first).num_days(),
+            _ => 0,
+        };
+
+        Summary {
+            commits: self.commit_count,
+            added: self.total_lines_added,
+            deleted: self.total_lines_deleted,
+            avg_size: self.average_commit_size,
+            contributors: self.contributor_names().len(),
+            span_days,
+        }
+    }
+
+    /// Lines added and deleted per ISO week, keyed by `(iso_year, iso_week)`
+    ///
+    /// Weeks follow ISO 8601, so the first days of January can belong to the last week
+    /// of the previous year. Entries whose date isn't `YYYY-MM-DD` are skipped.
+    pub fn activity_by_iso_week(&self) -> BTreeMap<(i32, u32), (usize, usize)> {
+        let mut weeks = BTreeMap::new();
+        for entry in &self.commit_activity {
+            let Some(week) = iso_week(&entry.date) else {
+                continue;
+            };
+            let (added, deleted) = weeks.entry(week).or_insert((0, 0));
+            *added += entry.lines_added;
+            *deleted += entry.lines_deleted;
+        }
+        weeks
+    }
+
+    /// The `n` authors with the most commits dated between `since` and `until`, inclusive
+    ///
+    /// Ranks by commit count, then by name, from the stored activity, so a window can be
+    /// looked at without re-analyzing. Entries whose date isn't `YYYY-MM-DD` are skipped.
+    pub fn top_contributors_in_range(
+        &self,
+        since: NaiveDate,
+        until: NaiveDate,
+        n: usize,
+    ) -> Vec<(String, usize)> {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for entry in &self.commit_activity {
+            let Ok(date) = NaiveDate::parse_from_str(&entry.date, "%Y-%m-%d") else {
+                continue;
+            };
+            if (since..=until).contains(&date) {
+                *counts.entry(entry.author.as_str()).or_insert(0) += 1;
+            }
+        }
+
+        let mut ranked: Vec<(String, usize)> = counts
+            .into_iter()
+            .map(|(author, count)| (author.to_string(), count))
+            .collect();
+        ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        ranked.truncate(n);
+        ranked
+    }
+
+    /// Hex IDs of the commits dated `date` (`YYYY-MM-DD`, UTC), in activity order
+    ///
+    /// Lets a chart point link to the commits behind it. Entries without an ID, i.e.
+    /// not read from a repository, are skipped.
+    pub fn commits_on(&self, date: &str) -> Vec<String> {
+        self.commit_activity
+            .iter()
+            .filter(|entry| entry.date == date && !entry.oid.is_empty())
+            .map(|entry| entry.oid.clone())
+            .collect()
+    }
+
+    /// Names of every known contributor, not just the top ones
+    fn contributor_names(&self) -> HashSet<String> {
+        self.author_first_commit
+            .keys()
+            .chain(self.top_contributors.iter().map(|(name, _)| name))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Count commits per month (`YYYY-MM`) from activity entries
+///
+/// Entries whose date doesn't start with a `YYYY-MM` month are counted under `"unknown"`.
+pub(crate) fn monthly_commit_frequency(activity: &[CommitActivity]) -> HashMap<String, usize> {
+    let mut commit_frequency = HashMap::new();
+    for entry in activity {
+        *commit_frequency.entry(commit_month(entry)).or_insert(0) += 1;
+    }
+    commit_frequency
+}
+
+/// Count commits per month for each of the given top contributors
+///
+/// Months are keyed as in [`monthly_commit_frequency`]. Other authors are left out, so
+/// the map stays small on repositories with thousands of contributors.
+pub(crate) fn author_commit_frequency(
+    activity: &[CommitActivity],
+    top_contributors: &[(String, usize)],
+) -> HashMap<String, HashMap<String, usize>> {
+    let mut frequency: HashMap<String, HashMap<String, usize>> = HashMap::new();
+    for entry in activity {
+        if !top_contributors
+            .iter()
+            .any(|(author, _)| *author == entry.author)
+        {
+            continue;
+        }
+        *frequency
+            .entry(entry.author.clone())
+            .or_default()
+            .entry(commit_month(entry))
+            .or_insert(0) += 1;
+    }
+    frequency
+}
+
+/// Month (`YYYY-MM`) of an activity entry, or `"unknown"` if its date has none
+fn commit_month(entry: &CommitActivity) -> String {
+    match entry.date.get(..7) {
+        Some(month) if is_year_month(month) => month.to_string(),
+        _ => "unknown".to_string(),
+    }
+}
+
+/// Check that a string has the shape `YYYY-MM`
+fn is_year_month(month: &str) -> bool {
+    month.len() == 7
+        && month
+            .char_indices()
+            .all(|(i, c)| if i == 4 { c == '-' } else { c.is_ascii_digit() })
+}
+
+/// Count commits per day from activity entries, skipping entries without a valid date
+pub(crate) fn daily_commit_counts<'a>(
+    activity: impl IntoIterator<Item = &'a CommitActivity>,
+) -> BTreeMap<NaiveDate, usize> {
+    let mut counts = BTreeMap::new();
+    for entry in activity {
+        if let Ok(date) = NaiveDate::parse_from_str(&entry.date, "%Y-%m-%d") {
+            *counts.entry(date).or_insert(0) += 1;
+        }
+    }
+    counts
+}
+
+/// Count commits per year from activity entries, skipping entries without a valid year
+pub(crate) fn yearly_commit_counts<'a>(
+    activity: impl IntoIterator<Item = &'a CommitActivity>,
+) -> BTreeMap<i32, usize> {
+    let mut counts = BTreeMap::new();
+    for entry in activity {
+        if let Some(year) = entry
+            .date
+            .get(..4)
+            .and_then(|year| year.parse::<i32>().ok())
+        {
+            *counts.entry(year).or_insert(0) += 1;
+        }
+    }
+    counts
+}
+
+/// Largest size (lines added plus deleted) and label of each commit size bucket but
+/// the last, which takes every larger commit
+const COMMIT_SIZE_BUCKETS: [(usize, &str); 4] =
+    [(0, "0"), (10, "1-10"), (100, "11-100"), (1000, "101-1000")];
+
+/// Count commits per logarithmic size bucket from activity entries
+pub(crate) fn commit_size_histogram<'a>(
+    activity: impl IntoIterator<Item = &'a CommitActivity>,
+) -> Vec<(String, usize)> {
+    let mut counts = [0; COMMIT_SIZE_BUCKETS.len() + 1];
+    for entry in activity {
+        let size = entry.lines_added + entry.lines_deleted;
+        let bucket = COMMIT_SIZE_BUCKETS
+            .iter()
+            .position(|(max, _)| size <= *max)
+            .unwrap_or(COMMIT_SIZE_BUCKETS.len());
+        counts[bucket] += 1;
+    }
+    COMMIT_SIZE_BUCKETS
+        .iter()
+        .map(|(_, label)| *label)
+        .chain(["1000+"])
+        .zip(counts)
+        .map(|(label, count)| (label.to_string(), count))
+        .collect()
+}
+
+/// Count commits per conventional-commit type from activity entries
+pub(crate) fn commit_type_counts<'a>(
+    activity: impl IntoIterator<Item = &'a CommitActivity>,
+) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    for commit_type in activity
+        .into_iter()
+        .filter_map(|entry| entry.commit_type.as_ref())
+    {
+        *counts.entry(commit_type.clone()).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Count the reverting commits among activity entries
+pub(crate) fn revert_count(activity: &[CommitActivity]) -> usize {
+    activity.iter().filter(|entry| entry.is_revert).count()
+}
+
+/// Sort activity entries by date, then by commit time, then by commit ID
+///
+/// Parallel chunks finish in any order, so without a fixed tie-break commits from the
+/// same day could come out differently from one run to the next. Commit time comes
+/// before the ID so same-day commits stay chronological, which [`file_hotspots`]
+/// relies on to follow renames.
+pub(crate) fn sort_activity(activity: &mut [CommitActivity]) {
+    activity.sort_by(|a, b| (&a.date, a.timestamp, &a.oid).cmp(&(&b.date, b.timestamp, &b.oid)));
+}
+
+/// Sum the lines added and deleted per file extension over activity entries
+pub(crate) fn extension_line_counts(
+    activity: &[CommitActivity],
+) -> BTreeMap<String, (usize, usize)> {
+    let mut counts = BTreeMap::new();
+    for (extension, (added, deleted)) in activity.iter().flat_map(|entry| &entry.lines_by_extension)
+    {
+        let total = counts.entry(extension.clone()).or_insert((0, 0));
+        total.0 += added;
+        total.1 += deleted;
+    }
+    counts
+}
+
+/// Lines added per file extension for each month (`YYYY-MM` -> extension -> lines)
+///
+/// Months without a change to a file with an extension are left out.
+pub(crate) fn monthly_extension_churn<'a>(
+    activity: impl IntoIterator<Item = &'a CommitActivity>,
+) -> BTreeMap<String, BTreeMap<String, usize>> {
+    let mut churn: BTreeMap<String, BTreeMap<String, usize>> = BTreeMap::new();
+    for entry in activity {
+        for (extension, (added, _)) in &entry.lines_by_extension {
+            *churn
+                .entry(commit_month(entry))
+                .or_default()
+                .entry(extension.clone())
+                .or_insert(0) += added;
+        }
+    }
+    churn
+}
+
+/// Number of files listed in [`AnalysisResult::file_hotspots`]
+const HOTSPOT_COUNT: usize = 10;
+
+/// Count the commits changing each file, keeping the most changed ones
+///
+/// `activity` must be in chronological order: it is walked newest first so that each
+/// rename maps the old path, as used by the commits before it, to the file's latest
+/// path. A path reused by a new file after a rename is therefore counted separately.
+pub(crate) fn file_hotspots(activity: &[CommitActivity]) -> Vec<(String, usize)> {
+    let mut latest_paths: HashMap<&str, &str> = HashMap::new();
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for entry in activity.iter().rev() {
+        for file in &entry.files {
+            let path = latest_paths
+                .get(file.as_str())
+                .copied()
+                .unwrap_or(file.as_str());
+            *counts.entry(path).or_insert(0) += 1;
+        }
+        for (old, new) in &entry.renames {
+            let path = latest_paths
+                .get(new.as_str())
+                .copied()
+                .unwrap_or(new.as_str());
+            latest_paths.insert(old, path);
+        }
+    }
+
+    let mut hotspots: Vec<(String, usize)> = counts
+        .into_iter()
+        .map(|(path, count)| (path.to_string(), count))
+        .collect();
+    hotspots.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    hotspots.truncate(HOTSPOT_COUNT);
+    hotspots
+}
+
+/// Extension with the most lines added and deleted, the first alphabetically on ties
+pub(crate) fn primary_language(
+    lines_by_extension: &BTreeMap<String, (usize, usize)>,
+) -> Option<String> {
+    lines_by_extension
+        .iter()
+        .filter(|(_, (added, deleted))| added + deleted > 0)
+        .max_by(|a, b| {
+            let lines = |(added, deleted): &(usize, usize)| added + deleted;
+            lines(a.1).cmp(&lines(b.1)).then_with(|| b.0.cmp(a.0))
+        })
+        .map(|(extension, _)| extension.clone())
+}
+
+/// ISO `(year, week)` of a `YYYY-MM-DD` date
+fn iso_week(date: &str) -> Option<(i32, u32)> {
+    let week = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?.iso_week();
+    Some((week.year(), week.week()))
+}
+
+/// Count commits per ISO week for each author, skipping entries without a valid date
+pub(crate) fn weekly_author_commits<'a>(
+    activity: impl IntoIterator<Item = &'a CommitActivity>,
+) -> HashMap<String, BTreeMap<(i32, u32), usize>> {
+    let mut weekly: HashMap<String, BTreeMap<(i32, u32), usize>> = HashMap::new();
+    for entry in activity {
+        if let Some(week) = iso_week(&entry.date) {
+            *weekly
+                .entry(entry.author.clone())
+                .or_default()
+                .entry(week)
+                .or_insert(0) += 1;
+        }
+    }
+    weekly
+}
+
+/// Ratio of lines added to lines deleted per author from activity entries
+///
+/// An author with no deletions gets `f64::INFINITY`, or `0.0` if they changed nothing.
+pub(crate) fn add_delete_ratios(activity: &[CommitActivity]) -> HashMap<String, f64> {
+    let mut totals: HashMap<&str, (usize, usize)> = HashMap::new();
+    for entry in activity {
+        let (added, deleted) = totals.entry(entry.author.as_str()).or_default();
+        *added += entry.lines_added;
+        *deleted += entry.lines_deleted;
+    }
+
+    totals
+        .into_iter()
+        .map(|(author, (added, deleted))| {
+            let ratio = match (added, deleted) {
+                (0, 0) => 0.0,
+                (_, 0) => f64::INFINITY,
+                _ => added as f64 / deleted as f64,
+            };
+            (author.to_string(), ratio)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_monthly_extension_churn() {
+        let entry = |date: &str, extensions: &[(&str, usize)]| CommitActivity {
+            lines_by_extension: extensions
+                .iter()
+                .map(|(extension, added)| (extension.to_string(), (*added, 1)))
+                .collect(),
+            ..CommitActivity::new(date, 0, 0, "Alice")
+        };
+        let activity = vec![
+            entry("2023-01-05", &[("rs", 10), ("md", 3)]),
+            entry("2023-01-20", &[("rs", 5)]),
+            entry("2023-02-01", &[("md", 2)]),
+            entry("2023-03-01", &[]),
+        ];
+
+        let churn = monthly_extension_churn(&activity);
+
+        assert_eq!(
+            churn,
+            BTreeMap::from([
+                (
+                    "2023-01".to_string(),
+                    BTreeMap::from([("md".to_string(), 3), ("rs".to_string(), 15)])
+                ),
+                (
+                    "2023-02".to_string(),
+                    BTreeMap::from([("md".to_string(), 2)])
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_sort_activity_same_day_by_time() {
+        let entry = |timestamp: i64, oid: &str| CommitActivity {
+            timestamp,
+            oid: oid.to_string(),
+            ..CommitActivity::new("2023-01-01", 1, 0, "Alice")
+        };
+        let mut activity = vec![entry(300, "aaa"), entry(100, "ccc"), entry(200, "bbb")];
+
+        sort_activity(&mut activity);
+
+        let oids: Vec<&str> = activity.iter().map(|a| a.oid.as_str()).collect();
+        assert_eq!(oids, vec!["ccc", "bbb", "aaa"]);
+    }
+
+    #[test]
+    fn test_merge_into_empty() {
+        let other = AnalysisResult {
+            commit_count: 2,
+            total_lines_added: 30,
+            total_lines_deleted: 10,
+            top_contributors: vec![("Alice".to_string(), 2)],
+            contributor_commit_counts: HashMap::from([("Alice".to_string(), 2)]),
+            commit_activity: vec![
+                CommitActivity::new("2023-03-01", 10, 10, "Alice"),
+                CommitActivity::new("2023-01-01", 20, 0, "Alice"),
+            ],
+            author_first_commit: HashMap::from([("Alice".to_string(), "2023-01-01".to_string())]),
+            selected_branch: "main".to_string(),
+            available_branches: vec!["main".to_string()],
+            ..Default::default()
+        };
+
+        let mut merged = AnalysisResult::default();
+        merged.merge(&other);
+
+        assert_eq!(merged.commit_count, 2);
+        assert_eq!(merged.total_lines_added, 30);
+        assert_eq!(merged.average_commit_size, 20.0);
+        assert_eq!(merged.top_contributors, other.top_contributors);
+        assert_eq!(merged.selected_branch, "main");
+        assert_eq!(merged.commit_activity[0].date, "2023-01-01");
+        assert_eq!(merged.commit_frequency.len(), 2);
+
+        // Merging an empty result changes nothing
+        merged.merge(&AnalysisResult::default());
+        assert_eq!(merged.commit_count, 2);
+        assert_eq!(merged.commit_activity.len(), 2);
+        assert_eq!(merged.average_commit_size, 20.0);
+    }
+
+    #[test]
+    fn test_merge_overlapping_contributors() {
+        let mut merged = AnalysisResult {
+            commit_count: 3,
+            total_lines_added: 30,
+            top_contributors: vec![("Alice".to_string(), 2), ("Bob".to_string(), 1)],
+            contributor_commit_counts: HashMap::from([
+                ("Alice".to_string(), 2),
+                ("Bob".to_string(), 1),
+            ]),
+            commit_activity: vec![
+                CommitActivity::new("2023-02-01", 10, 0, "Alice"),
+                CommitActivity::new("2023-02-02", 10, 0, "Alice"),
+                CommitActivity::new("2023-02-03", 10, 0, "Bob"),
+            ],
+            author_first_commit: HashMap::from([
+                ("Alice".to_string(), "2023-02-01".to_string()),
+                ("Bob".to_string(), "2023-02-03".to_string()),
+            ]),
+            contributor_emails: HashMap::from([(
+                "Alice".to_string(),
+                "alice@work.example".to_string(),
+            )]),
+            ..Default::default()
+        };
+        let other = AnalysisResult {
+            commit_count: 2,
+            total_lines_added: 10,
+            total_lines_deleted: 20,
+            top_contributors: vec![("Bob".to_string(), 1), ("Carol".to_string(), 1)],
+            contributor_commit_counts: HashMap::from([
+                ("Bob".to_string(), 1),
+                ("Carol".to_string(), 1),
+            ]),
+            commit_activity: vec![
+                CommitActivity::new("2023-01-15", 5, 10, "Bob"),
+                CommitActivity::new("2023-03-01", 5, 10, "Carol"),
+            ],
+            author_first_commit: HashMap::from([
+                ("Bob".to_string(), "2023-01-15".to_string()),
+                ("Carol".to_string(), "2023-03-01".to_string()),
+            ]),
+            contributor_emails: HashMap::from([
+                ("Alice".to_string(), "alice@home.example".to_string()),
+                ("Carol".to_string(), "carol@example.com".to_string()),
+            ]),
+            ..Default::default()
+        };
+
+        merged.merge(&other);
+
+        assert_eq!(merged.commit_count, 5);
+        assert_eq!(merged.average_commit_size, 60.0 / 5.0);
+        assert_eq!(
+            merged.top_contributors,
+            vec![
+                ("Alice".to_string(), 2),
+                ("Bob".to_string(), 2),
+                ("Carol".to_string(), 1),
+            ]
+        );
+        assert_eq!(merged.author_first_commit["Bob"], "2023-01-15");
+        assert_eq!(merged.contributor_emails["Alice"], "alice@work.example");
+        assert_eq!(merged.contributor_emails["Carol"], "carol@example.com");
+        let dates: Vec<&str> = merged
+            .commit_activity
+            .iter()
+            .map(|a| a.date.as_str())
+            .collect();
+        assert_eq!(
+            dates,
+            vec![
+                "2023-01-15",
+                "2023-02-01",
+                "2023-02-02",
+                "2023-02-03",
+                "2023-03-01"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merge_ranks_contributors_outside_top_lists() {
+        // Zed is sixth on both sides, so in neither top five, but first once combined
+        let result_with = |authors: &[&str]| {
+            let mut counts: HashMap<String, usize> = authors
+                .iter()
+                .map(|author| (author.to_string(), 10))
+                .collect();
+            counts.insert("Zed".to_string(), 9);
+            let mut top_contributors: Vec<(String, usize)> =
+                counts.iter().map(|(a, c)| (a.clone(), *c)).collect();
+            top_contributors.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+            top_contributors.truncate(5);
+            AnalysisResult {
+                commit_count: counts.values().sum(),
+                top_contributors,
+                contributor_commit_counts: counts,
+                ..Default::default()
+            }
+        };
+        let mut merged = result_with(&["A", "B", "C", "D", "E"]);
+        let other = result_with(&["F", "G", "H", "I", "J"]);
+        assert!(!merged.top_contributors.iter().any(|(a, _)| a == "Zed"));
+        assert!(!other.top_contributors.iter().any(|(a, _)| a == "Zed"));
+
+        merged.merge(&other);
+
+        assert_eq!(merged.top_contributors[0], ("Zed".to_string(), 18));
+        assert_eq!(merged.top_contributors.len(), 5);
+        assert_eq!(merged.contributor_commit_counts.len(), 11);
+    }
+
+    #[test]
+    fn test_analysis_diff() {
+        let previous = AnalysisResult {
+            commit_count: 10,
+            total_lines_added: 500,
+            total_lines_deleted: 200,
+            top_contributors: vec![("Alice".to_string(), 10)],
+            ..Default::default()
+        };
+        let current = AnalysisResult {
+            commit_count: 14,
+            total_lines_added: 650,
+            total_lines_deleted: 150,
+            top_contributors: vec![
+                ("Alice".to_string(), 11),
+                ("Carol".to_string(), 2),
+                ("Bob".to_string(), 1),
+            ],
+            ..Default::default()
+        };
+
+        let delta = current.diff(&previous);
+
+        assert_eq!(
+            delta,
+            AnalysisDelta {
+                commit_count: 4,
+                lines_added: 150,
+                lines_deleted: -50,
+                new_contributors: vec!["Bob".to_string(), "Carol".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn test_top_contributors_in_range() {
+        let commit_activity = vec![
+            CommitActivity::new("2024-01-03", 1, 0, "Alice"),
+            CommitActivity::new("2024-01-10", 1, 0, "Alice"),
+            CommitActivity::new("2024-01-20", 1, 0, "Alice"),
+            CommitActivity::new("2024-02-01", 1, 0, "Bob"),
+            CommitActivity::new("2024-02-29", 1, 0, "Bob"),
+            CommitActivity::new("2024-02-15", 1, 0, "Carol"),
+            CommitActivity::new("2024-02-16", 1, 0, "Alice"),
+            CommitActivity::new("not a date", 1, 0, "Carol"),
+        ];
+        let result = AnalysisResult {
+            commit_activity,
+            top_contributors: vec![
+                ("Alice".to_string(), 4),
+                ("Bob".to_string(), 2),
+                ("Carol".to_string(), 2),
+            ],
+            ..Default::default()
+        };
+
+        let date = |s| NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+        assert_eq!(
+            result.top_contributors_in_range(date("2024-02-01"), date("2024-02-29"), 10),
+            vec![
+                ("Bob".to_string(), 2),
+                ("Alice".to_string(), 1),
+                ("Carol".to_string(), 1),
+            ]
+        );
+        assert_eq!(
+            result.top_contributors_in_range(date("2024-02-01"), date("2024-02-29"), 1),
+            vec![("Bob".to_string(), 2)]
+        );
+        assert_eq!(
+            result.top_contributors_in_range(date("2024-01-01"), date("2024-02-29"), 1),
+            vec![("Alice".to_string(), 4)]
+        );
+    }
+
+    #[test]
+    fn test_summary() {
+        let result = AnalysisResult {
+            commit_count: 3,
+            total_lines_added: 30,
+            total_lines_deleted: 6,
+            average_commit_size: 12.0,
+            top_contributors: vec![("Alice".to_string(), 2), ("Bob".to_string(), 1)],
+            contributor_commit_counts: HashMap::from([
+                ("Alice".to_string(), 2),
+                ("Bob".to_string(), 1),
+            ]),
+            commit_activity: vec![
+                CommitActivity::new("2024-01-31", 10, 2, "Alice"),
+                CommitActivity::new("2024-01-01", 10, 2, "Bob"),
+                CommitActivity::new("2024-03-01", 10, 2, "Alice"),
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            result.summary(),
+            Summary {
+                commits: 3,
+                added: 30,
+                deleted: 6,
+                avg_size: 12.0,
+                contributors: 2,
+                span_days: 60,
+            }
+        );
+        assert_eq!(AnalysisResult::default().summary().span_days, 0);
+    }
+
+    #[test]
+    fn test_monthly_commit_frequency_malformed_dates() {
+        let activity = vec![
+            CommitActivity::new("2023-01-15", 1, 0, "Alice"),
+            CommitActivity::new("2023-01-20", 1, 0, "Alice"),
+            CommitActivity::new("Unknown", 1, 0, "Bob"),
+            CommitActivity::new("2023", 1, 0, "Bob"),
+            CommitActivity::new("", 1, 0, "Bob"),
+            CommitActivity::new("2023-0é-01", 1, 0, "Bob"),
+        ];
+
+        let frequency = monthly_commit_frequency(&activity);
+
+        assert_eq!(frequency.len(), 2);
+        assert_eq!(frequency["2023-01"], 2);
+        assert_eq!(frequency["unknown"], 4);
+    }
+
+    #[test]
+    fn test_add_delete_ratios() {
+        let activity = vec![
+            CommitActivity::new("2023-01-01", 40, 0, "Adder"),
+            CommitActivity::new("2023-01-02", 10, 0, "Adder"),
+            CommitActivity::new("2023-01-01", 30, 10, "Refactorer"),
+            CommitActivity::new("2023-01-03", 0, 20, "Refactorer"),
+        ];
+
+        let ratios = add_delete_ratios(&activity);
+
+        assert_eq!(ratios.len(), 2);
+        assert_eq!(ratios["Adder"], f64::INFINITY);
+        assert_eq!(ratios["Refactorer"], 1.0);
+    }
+
+    #[test]
+    fn test_activity_by_iso_week_year_boundary() {
+        let result = AnalysisResult {
+            commit_activity: vec![
+                CommitActivity::new("2020-12-31", 1, 2, "Alice"),
+                // Friday 1 January 2021 is still in week 53 of 2020
+                CommitActivity::new("2021-01-01", 10, 20, "Bob"),
+                CommitActivity::new("2021-01-04", 100, 200, "Alice"),
+                CommitActivity::new("Unknown", 1000, 1000, "Carol"),
+            ],
+            ..Default::default()
+        };
+
+        let weeks: Vec<((i32, u32), (usize, usize))> =
+            result.activity_by_iso_week().into_iter().collect();
+
+        assert_eq!(weeks, vec![((2020, 53), (11, 22)), ((2021, 1), (100, 200))]);
+    }
+}
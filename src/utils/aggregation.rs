@@ -1,4 +1,6 @@
 /// Aggregate data points to reduce visual noise and improve performance
+///
+/// `data` is expected in chronological order; each bucket is labelled with its first date.
 pub fn aggregate_data(
     data: &[(String, usize, usize)],
     target_points: usize,
@@ -0,0 +1,2353 @@
+/// Analysis tests for the GitStats library.
+/// Builds small fixture repositories with controlled authors, dates and
+/// contents, then checks the statistics reported by the analysis functions.
+use git2::{Repository, Signature, Time};
+use gitstats::analysis::{
+    analyze_all_branches, analyze_repo_async, analyze_repo_with_options, commit_diff_stats,
+    estimate_analysis_duration, estimate_commit_count, estimated_duration, get_available_refs,
+    list_contributors, working_tree_stats, DEFAULT_COMMITS_PER_SECOND, DETACHED_HEAD,
+};
+use gitstats::types::{
+    AccuracyMode, AnalysisOptions, AnalysisResult, MergeStrategy, Parallelism, ProgressEstimate,
+    RefKind,
+};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use tempfile::TempDir;
+
+/// Unix timestamp for 2023-01-01 00:00:00 UTC
+const JAN_1_2023: i64 = 1_672_531_200;
+/// Seconds in one day
+const DAY: i64 = 86_400;
+
+/// Create an empty repository in a temporary directory
+fn init_repo() -> (TempDir, Repository) {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = Repository::init(temp_dir.path()).unwrap();
+    (temp_dir, repo)
+}
+
+/// Write `content` to `file_name` and commit it on HEAD
+///
+/// # Arguments
+/// * `repo` - Repository to commit into
+/// * `author` - Author name (the email is derived from it)
+/// * `time` - Commit timestamp in seconds since the epoch
+/// * `file_name` - Path of the file relative to the repository root
+/// * `content` - New content of the file
+/// * `message` - Commit message
+fn commit_file(
+    repo: &Repository,
+    author: &str,
+    time: i64,
+    file_name: &str,
+    content: &str,
+    message: &str,
+) -> git2::Oid {
+    let workdir = repo.workdir().unwrap();
+    let file_path = workdir.join(file_name);
+    if let Some(parent) = file_path.parent() {
+        fs::create_dir_all(parent).unwrap();
+    }
+    fs::write(&file_path, content).unwrap();
+
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new(file_name)).unwrap();
+    index.write().unwrap();
+    let tree_id = index.write_tree().unwrap();
+    let tree = repo.find_tree(tree_id).unwrap();
+
+    let email = format!("{}@example.com", author.to_lowercase().replace(' ', "."));
+    let signature = Signature::new(author, &email, &Time::new(time, 0)).unwrap();
+    let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+    let parents: Vec<&git2::Commit> = parent.iter().collect();
+
+    repo.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        message,
+        &tree,
+        &parents,
+    )
+    .unwrap()
+}
+
+/// Analyze the whole repository on its current HEAD for all contributors
+async fn analyze_all(temp_dir: &TempDir) -> gitstats::AnalysisResult {
+    analyze_repo_async(
+        temp_dir.path().to_str().unwrap().to_string(),
+        "main".to_string(),
+        "All".to_string(),
+        None,
+    )
+    .await
+    .unwrap()
+}
+
+/// Test that each author's earliest commit date is recorded
+#[tokio::test]
+async fn test_author_first_commit() {
+    let (temp_dir, repo) = init_repo();
+    commit_file(&repo, "Alice", JAN_1_2023, "a.txt", "a\n", "Add a");
+    commit_file(&repo, "Bob", JAN_1_2023 + 5 * DAY, "b.txt", "b\n", "Add b");
+    commit_file(
+        &repo,
+        "Alice",
+        JAN_1_2023 + 9 * DAY,
+        "a.txt",
+        "a\na\n",
+        "More a",
+    );
+
+    let result = analyze_all(&temp_dir).await;
+
+    assert_eq!(result.author_first_commit.len(), 2);
+    assert_eq!(result.author_first_commit["Alice"], "2023-01-01");
+    assert_eq!(result.author_first_commit["Bob"], "2023-01-06");
+}
+
+/// Test that analyzing two repositories together sums their statistics
+#[tokio::test]
+async fn test_analyze_repos_merges_totals() {
+    let (first_dir, first_repo) = init_repo();
+    commit_file(&first_repo, "Alice", JAN_1_2023, "a.txt", "1\n2\n", "Add a");
+    commit_file(
+        &first_repo,
+        "Bob",
+        JAN_1_2023 + DAY,
+        "b.txt",
+        "1\n",
+        "Add b",
+    );
+
+    let (second_dir, second_repo) = init_repo();
+    commit_file(
+        &second_repo,
+        "Alice",
+        JAN_1_2023,
+        "c.txt",
+        "1\n2\n3\n",
+        "Add c",
+    );
+
+    let first = analyze_all(&first_dir).await;
+    let second = analyze_all(&second_dir).await;
+
+    let merged = gitstats::analysis::analyze_repos(
+        vec![
+            first_dir.path().to_str().unwrap().to_string(),
+            second_dir.path().to_str().unwrap().to_string(),
+        ],
+        "main".to_string(),
+        "All".to_string(),
+        MergeStrategy::Combine,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        merged.commit_count,
+        first.commit_count + second.commit_count
+    );
+    assert_eq!(
+        merged.total_lines_added,
+        first.total_lines_added + second.total_lines_added
+    );
+    assert_eq!(merged.commit_activity.len(), 3);
+    assert_eq!(merged.top_contributors[0], ("Alice".to_string(), 2));
+}
+
+/// Test that filtering a cached "All" result matches a fresh filtered analysis
+#[tokio::test]
+async fn test_filtered_by_author_matches_analysis() {
+    let (temp_dir, repo) = init_repo();
+    commit_file(&repo, "Alice", JAN_1_2023, "a.txt", "1\n2\n", "Add a");
+    commit_file(&repo, "Bob", JAN_1_2023 + DAY, "b.txt", "1\n", "Add b");
+    commit_file(
+        &repo,
+        "Alice",
+        JAN_1_2023 + 40 * DAY,
+        "a.txt",
+        "1\n",
+        "Trim a",
+    );
+
+    let all = analyze_all(&temp_dir).await;
+    let fresh = analyze_repo_async(
+        temp_dir.path().to_str().unwrap().to_string(),
+        "main".to_string(),
+        "Alice".to_string(),
+        None,
+    )
+    .await
+    .unwrap();
+
+    let derived = all.filtered_by_author("Alice").unwrap();
+
+    assert_eq!(derived.commit_count, fresh.commit_count);
+    assert_eq!(derived.total_lines_added, fresh.total_lines_added);
+    assert_eq!(derived.total_lines_deleted, fresh.total_lines_deleted);
+    assert_eq!(derived.top_contributors, fresh.top_contributors);
+    assert_eq!(derived.commit_frequency, fresh.commit_frequency);
+
+    let mut derived_activity = derived.commit_activity.clone();
+    let mut fresh_activity = fresh.commit_activity.clone();
+    derived_activity.sort_by(|a, b| a.date.cmp(&b.date));
+    fresh_activity.sort_by(|a, b| a.date.cmp(&b.date));
+    assert_eq!(derived_activity, fresh_activity);
+}
+
+/// Test that a result whose activity doesn't cover every commit can't be filtered
+#[tokio::test]
+async fn test_filtered_by_author_incomplete_activity() {
+    let (temp_dir, repo) = init_repo();
+    commit_file(&repo, "Alice", JAN_1_2023, "a.txt", "1\n2\n", "Add a");
+    commit_file(&repo, "Alice", JAN_1_2023 + DAY, "a.txt", "1\n", "Trim a");
+
+    let analyze = |options: AnalysisOptions| {
+        analyze_repo_with_options(
+            temp_dir.path().to_str().unwrap().to_string(),
+            "main".to_string(),
+            "All".to_string(),
+            options,
+            None,
+        )
+    };
+    let skipped = analyze(AnalysisOptions::builder().skip_initial_commit(true).build())
+        .await
+        .unwrap();
+    let totals_only = analyze(AnalysisOptions::builder().collect_activity(false).build())
+        .await
+        .unwrap();
+
+    // The initial commit is counted but has no activity entry
+    assert_eq!(skipped.commit_count, 2);
+    assert!(skipped.filtered_by_author("Alice").is_none());
+    assert!(totals_only.filtered_by_author("Alice").is_none());
+    assert!(analyze_all(&temp_dir)
+        .await
+        .filtered_by_author("Alice")
+        .is_some());
+}
+
+/// Test that whitespace-only changes count by default and are ignored on request
+#[tokio::test]
+async fn test_ignore_whitespace_option() {
+    let (temp_dir, repo) = init_repo();
+    commit_file(&repo, "Alice", JAN_1_2023, "a.txt", "a\nb\n", "Add a");
+    commit_file(
+        &repo,
+        "Alice",
+        JAN_1_2023 + DAY,
+        "a.txt",
+        "a  \nb\n",
+        "Spaces",
+    );
+    let path = temp_dir.path().to_str().unwrap().to_string();
+
+    let counted = analyze_all(&temp_dir).await;
+    assert_eq!(counted.total_lines_added, 3);
+    assert_eq!(counted.total_lines_deleted, 1);
+
+    let options = AnalysisOptions {
+        ignore_whitespace: true,
+        ..Default::default()
+    };
+    let ignored =
+        analyze_repo_with_options(path, "main".to_string(), "All".to_string(), options, None)
+            .await
+            .unwrap();
+    assert_eq!(ignored.total_lines_added, 2);
+    assert_eq!(ignored.total_lines_deleted, 0);
+}
+
+/// Test that converting a file to CRLF line endings counts no lines when normalized
+#[tokio::test]
+async fn test_normalize_line_endings_option() {
+    let (temp_dir, repo) = init_repo();
+    commit_file(&repo, "Alice", JAN_1_2023, "a.txt", "a\nb\n", "Add a");
+    commit_file(
+        &repo,
+        "Alice",
+        JAN_1_2023 + DAY,
+        "a.txt",
+        "a\r\nb\r\n",
+        "CRLF",
+    );
+    let path = temp_dir.path().to_str().unwrap().to_string();
+
+    let counted = analyze_all(&temp_dir).await;
+    assert_eq!(counted.total_lines_added, 4);
+    assert_eq!(counted.total_lines_deleted, 2);
+
+    let options = AnalysisOptions {
+        normalize_line_endings: true,
+        ..Default::default()
+    };
+    let normalized =
+        analyze_repo_with_options(path, "main".to_string(), "All".to_string(), options, None)
+            .await
+            .unwrap();
+    assert_eq!(normalized.total_lines_added, 2);
+    assert_eq!(normalized.total_lines_deleted, 0);
+}
+
+/// Test that the only author of a repository owns every surviving line
+#[tokio::test]
+async fn test_blame_ownership_sole_author() {
+    let (temp_dir, repo) = init_repo();
+    commit_file(&repo, "Alice", JAN_1_2023, "a.txt", "1\n2\n3\n", "Add a");
+    commit_file(
+        &repo,
+        "Alice",
+        JAN_1_2023 + DAY,
+        "a.txt",
+        "1\n3\n",
+        "Drop 2",
+    );
+    commit_file(
+        &repo,
+        "Alice",
+        JAN_1_2023 + DAY,
+        "src/b.rs",
+        "fn b() {}\n",
+        "Add b",
+    );
+
+    let ownership = gitstats::analysis::blame_ownership(
+        temp_dir.path().to_str().unwrap().to_string(),
+        "main".to_string(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(ownership.len(), 1);
+    assert_eq!(ownership["Alice"], 3);
+}
+
+/// Test that blame ownership of a tag is taken from the tagged tree, not HEAD
+#[tokio::test]
+async fn test_blame_ownership_tag() {
+    let (temp_dir, repo) = init_repo();
+    let first = commit_file(&repo, "Alice", JAN_1_2023, "a.txt", "1\n2\n", "First");
+    repo.tag_lightweight("v1.0", repo.find_commit(first).unwrap().as_object(), false)
+        .unwrap();
+    commit_file(
+        &repo,
+        "Bob",
+        JAN_1_2023 + DAY,
+        "a.txt",
+        "1\n2\n3\n",
+        "Second",
+    );
+
+    let ownership = gitstats::analysis::blame_ownership(
+        temp_dir.path().to_str().unwrap().to_string(),
+        "v1.0".to_string(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(ownership, HashMap::from([("Alice".to_string(), 2)]));
+}
+
+/// Test that excluded authors are left out of counts and rankings
+#[tokio::test]
+async fn test_exclude_authors() {
+    let (temp_dir, repo) = init_repo();
+    commit_file(&repo, "Alice", JAN_1_2023, "a.txt", "1\n", "Add a");
+    commit_file(
+        &repo,
+        "dependabot[bot]",
+        JAN_1_2023 + DAY,
+        "Cargo.lock",
+        "1\n2\n3\n",
+        "Bump",
+    );
+    commit_file(
+        &repo,
+        "dependabot[bot]",
+        JAN_1_2023 + 2 * DAY,
+        "Cargo.lock",
+        "1\n2\n",
+        "Bump",
+    );
+
+    let options = AnalysisOptions {
+        exclude_authors: vec!["dependabot".to_string()],
+        ..Default::default()
+    };
+    let result = analyze_repo_with_options(
+        temp_dir.path().to_str().unwrap().to_string(),
+        "main".to_string(),
+        "All".to_string(),
+        options,
+        None,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(result.commit_count, 1);
+    assert_eq!(result.total_lines_added, 1);
+    assert_eq!(result.top_contributors, vec![("Alice".to_string(), 1)]);
+    assert!(!result.author_first_commit.contains_key("dependabot[bot]"));
+}
+
+/// Test that streamed partial counts grow monotonically up to the final totals
+#[tokio::test]
+async fn test_stream_partial_results() {
+    let (temp_dir, repo) = init_repo();
+    let mut content = String::new();
+    for i in 0..250 {
+        content.push_str(&format!("{}\n", i));
+        commit_file(
+            &repo,
+            "Alice",
+            JAN_1_2023 + i * DAY,
+            "a.txt",
+            &content,
+            "Add line",
+        );
+    }
+
+    let (tx, mut rx) = tokio::sync::mpsc::channel::<ProgressEstimate>(32);
+    let collector = tokio::spawn(async move {
+        let mut partials = Vec::new();
+        while let Some(progress) = rx.recv().await {
+            if let Some(partial) = progress.partial {
+                partials.push(partial);
+            }
+        }
+        partials
+    });
+
+    let options = AnalysisOptions {
+        stream_partials: true,
+        ..Default::default()
+    };
+    let result = analyze_repo_with_options(
+        temp_dir.path().to_str().unwrap().to_string(),
+        "main".to_string(),
+        "All".to_string(),
+        options,
+        Some(tx),
+    )
+    .await
+    .unwrap();
+    let partials = collector.await.unwrap();
+
+    assert!(partials.len() >= 2);
+    assert!(partials
+        .windows(2)
+        .all(|pair| pair[0].commit_count <= pair[1].commit_count
+            && pair[0].lines_added <= pair[1].lines_added));
+
+    let last = partials.last().unwrap();
+    assert_eq!(last.commit_count, result.commit_count);
+    assert_eq!(last.lines_added, result.total_lines_added);
+    let streamed_activity: usize = partials.iter().map(|p| p.activity.len()).sum();
+    assert_eq!(streamed_activity, result.commit_activity.len());
+}
+
+/// Test that conventional-commit prefixes are counted per type
+#[tokio::test]
+async fn test_commit_type_counts() {
+    let (temp_dir, repo) = init_repo();
+    commit_file(&repo, "Alice", JAN_1_2023, "a.txt", "1\n", "feat: add a");
+    commit_file(&repo, "Alice", JAN_1_2023 + DAY, "a.txt", "2\n", "fix: a");
+    commit_file(
+        &repo,
+        "Bob",
+        JAN_1_2023 + 2 * DAY,
+        "a.txt",
+        "3\n",
+        "fix(a): again",
+    );
+    commit_file(&repo, "Bob", JAN_1_2023 + 3 * DAY, "b.txt", "1\n", "Add b");
+
+    let result = analyze_all(&temp_dir).await;
+
+    assert_eq!(result.commit_type_counts.len(), 2);
+    assert_eq!(result.commit_type_counts["feat"], 1);
+    assert_eq!(result.commit_type_counts["fix"], 2);
+    assert_eq!(
+        result.filtered_by_author("Bob").unwrap().commit_type_counts["fix"],
+        1
+    );
+}
+
+/// Test that only commits inside the since/until range are analyzed
+#[tokio::test]
+async fn test_date_range_filter() {
+    let (temp_dir, repo) = init_repo();
+    commit_file(&repo, "Alice", JAN_1_2023, "a.txt", "1\n", "Add a");
+    commit_file(
+        &repo,
+        "Alice",
+        JAN_1_2023 + 10 * DAY,
+        "a.txt",
+        "1\n2\n",
+        "Grow a",
+    );
+    commit_file(&repo, "Bob", JAN_1_2023 + 20 * DAY, "b.txt", "1\n", "Add b");
+
+    let options = AnalysisOptions {
+        since: chrono::NaiveDate::from_ymd_opt(2023, 1, 5),
+        until: chrono::NaiveDate::from_ymd_opt(2023, 1, 11),
+        ..Default::default()
+    };
+    let result = analyze_repo_with_options(
+        temp_dir.path().to_str().unwrap().to_string(),
+        "main".to_string(),
+        "All".to_string(),
+        options,
+        None,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(result.commit_count, 1);
+    assert_eq!(result.commit_activity[0].date, "2023-01-11");
+    assert_eq!(result.total_lines_added, 1);
+}
+
+/// Test that a known branch list is reused instead of enumerating branches again
+#[tokio::test]
+async fn test_known_branches_skip_enumeration() {
+    let (temp_dir, repo) = init_repo();
+    commit_file(&repo, "Alice", JAN_1_2023, "a.txt", "1\n", "Add a");
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    repo.branch("feature", &head, false).unwrap();
+
+    let enumerated = analyze_all(&temp_dir).await;
+    assert!(enumerated
+        .available_branches
+        .contains(&"feature".to_string()));
+
+    // A stale list proves the repository was not asked for its branches
+    let options = AnalysisOptions {
+        known_branches: Some(vec!["cached".to_string()]),
+        ..Default::default()
+    };
+    let reused = analyze_repo_with_options(
+        temp_dir.path().to_str().unwrap().to_string(),
+        "main".to_string(),
+        "Alice".to_string(),
+        options,
+        None,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(reused.available_branches, vec!["cached".to_string()]);
+    assert_eq!(reused.commit_count, 1);
+}
+
+/// Test that a missing path and a plain directory produce distinct, readable errors
+#[tokio::test]
+async fn test_invalid_repository_errors() {
+    let temp_dir = TempDir::new().unwrap();
+    let missing = temp_dir.path().join("missing");
+
+    let error = analyze_repo_async(
+        missing.to_str().unwrap().to_string(),
+        "main".to_string(),
+        "All".to_string(),
+        None,
+    )
+    .await
+    .unwrap_err();
+    assert!(error.message().starts_with("Path does not exist"));
+
+    let error = analyze_repo_async(
+        temp_dir.path().to_str().unwrap().to_string(),
+        "main".to_string(),
+        "All".to_string(),
+        None,
+    )
+    .await
+    .unwrap_err();
+    assert!(error.message().starts_with("Not a git repository"));
+}
+
+/// Test that co-authors named in trailers are credited when enabled
+#[tokio::test]
+async fn test_count_coauthors() {
+    let (temp_dir, repo) = init_repo();
+    commit_file(
+        &repo,
+        "Alice",
+        JAN_1_2023,
+        "a.txt",
+        "1\n",
+        "Pair on a\n\nCo-authored-by: Bob <bob@example.com>\nCo-authored-by: Carol <carol@example.com>\n",
+    );
+
+    let without = analyze_all(&temp_dir).await;
+    assert_eq!(without.top_contributors, vec![("Alice".to_string(), 1)]);
+
+    let options = AnalysisOptions {
+        count_coauthors: true,
+        ..Default::default()
+    };
+    let result = analyze_repo_with_options(
+        temp_dir.path().to_str().unwrap().to_string(),
+        "main".to_string(),
+        "All".to_string(),
+        options,
+        None,
+    )
+    .await
+    .unwrap();
+
+    let mut credited = result.top_contributors.clone();
+    credited.sort();
+    assert_eq!(
+        credited,
+        vec![
+            ("Alice".to_string(), 1),
+            ("Bob".to_string(), 1),
+            ("Carol".to_string(), 1),
+        ]
+    );
+    assert_eq!(result.commit_count, 1);
+
+    // Co-authors are only credited in the contributor counts; the commit itself still
+    // belongs to its author alone
+    assert_eq!(result.commit_activity[0].author, "Alice");
+    assert!(!result.author_first_commit.contains_key("Bob"));
+    assert_eq!(result.filtered_by_author("Bob").unwrap().commit_count, 0);
+}
+
+/// Test that first-parent mode skips commits only reachable through a merged branch
+#[tokio::test]
+async fn test_first_parent_history() {
+    let (temp_dir, repo) = init_repo();
+    let base = commit_file(&repo, "Alice", JAN_1_2023, "a.txt", "1\n", "Base");
+    let main_ref = repo.head().unwrap().name().unwrap().to_string();
+
+    // One commit on a feature branch, one on the mainline
+    repo.branch("feature", &repo.find_commit(base).unwrap(), false)
+        .unwrap();
+    repo.set_head("refs/heads/feature").unwrap();
+    let feature = commit_file(&repo, "Bob", JAN_1_2023 + DAY, "b.txt", "1\n", "Feature");
+    repo.set_head(&main_ref).unwrap();
+    repo.checkout_head(Some(git2::build::CheckoutBuilder::new().force()))
+        .unwrap();
+    let main = commit_file(&repo, "Alice", JAN_1_2023 + 2 * DAY, "c.txt", "1\n", "Main");
+
+    // Merge the feature branch by bringing its file into the mainline tree
+    fs::write(temp_dir.path().join("b.txt"), "1\n").unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new("b.txt")).unwrap();
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let signature = Signature::new(
+        "Alice",
+        "alice@example.com",
+        &Time::new(JAN_1_2023 + 3 * DAY, 0),
+    )
+    .unwrap();
+    repo.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        "Merge feature",
+        &tree,
+        &[
+            &repo.find_commit(main).unwrap(),
+            &repo.find_commit(feature).unwrap(),
+        ],
+    )
+    .unwrap();
+
+    let full = analyze_all(&temp_dir).await;
+    assert_eq!(full.commit_count, 4);
+
+    let options = AnalysisOptions {
+        first_parent: true,
+        ..Default::default()
+    };
+    let mainline = analyze_repo_with_options(
+        temp_dir.path().to_str().unwrap().to_string(),
+        "main".to_string(),
+        "All".to_string(),
+        options,
+        None,
+    )
+    .await
+    .unwrap();
+    assert_eq!(mainline.commit_count, 3);
+    assert!(mainline.commit_activity.iter().all(|a| a.author == "Alice"));
+}
+
+/// Test that commits are counted per year from back-dated timestamps
+#[tokio::test]
+async fn test_commits_by_year() {
+    let (temp_dir, repo) = init_repo();
+    commit_file(
+        &repo,
+        "Alice",
+        JAN_1_2023 - 200 * DAY,
+        "a.txt",
+        "1\n",
+        "Old",
+    );
+    commit_file(
+        &repo,
+        "Alice",
+        JAN_1_2023 - DAY,
+        "a.txt",
+        "2\n",
+        "Last of 2022",
+    );
+    commit_file(&repo, "Bob", JAN_1_2023, "b.txt", "1\n", "New year");
+
+    let result = analyze_all(&temp_dir).await;
+
+    let years: Vec<(i32, usize)> = result.commits_by_year.into_iter().collect();
+    assert_eq!(years, vec![(2022, 2), (2023, 1)]);
+}
+
+/// Test that each contributor's email is captured alongside their name
+#[tokio::test]
+async fn test_contributor_emails() {
+    let (temp_dir, repo) = init_repo();
+    commit_file(&repo, "Alice Smith", JAN_1_2023, "a.txt", "1\n", "Add a");
+    commit_file(&repo, "Bob", JAN_1_2023 + DAY, "b.txt", "1\n", "Add b");
+
+    let result = analyze_all(&temp_dir).await;
+
+    assert_eq!(result.contributor_emails.len(), 2);
+    assert_eq!(
+        result.contributor_emails["Alice Smith"],
+        "alice.smith@example.com"
+    );
+    assert_eq!(result.contributor_emails["Bob"], "bob@example.com");
+}
+
+/// Test that changes under excluded paths are left out of line totals
+#[tokio::test]
+async fn test_exclude_paths() {
+    let (temp_dir, repo) = init_repo();
+    commit_file(
+        &repo,
+        "Alice",
+        JAN_1_2023,
+        "src/main.rs",
+        "1\n2\n",
+        "Add main",
+    );
+    commit_file(
+        &repo,
+        "Alice",
+        JAN_1_2023 + DAY,
+        "vendor/dep/lib.rs",
+        "1\n2\n3\n4\n5\n",
+        "Vendor dep",
+    );
+
+    let options = AnalysisOptions {
+        exclude_paths: vec!["vendor".to_string()],
+        ..Default::default()
+    };
+    let result = analyze_repo_with_options(
+        temp_dir.path().to_str().unwrap().to_string(),
+        "main".to_string(),
+        "All".to_string(),
+        options,
+        None,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(analyze_all(&temp_dir).await.total_lines_added, 7);
+    assert_eq!(result.total_lines_added, 2);
+    assert_eq!(result.commit_count, 2);
+}
+
+/// Test that excluded paths stay out of the totals of diffs counted from their stats
+#[tokio::test]
+async fn test_stat_only_threshold_exclude_paths() {
+    let (temp_dir, repo) = init_repo();
+    commit_file(
+        &repo,
+        "Alice",
+        JAN_1_2023,
+        "src/main.rs",
+        "1\n2\n",
+        "Add main",
+    );
+    commit_file(
+        &repo,
+        "Alice",
+        JAN_1_2023 + DAY,
+        "vendor/dep/lib.rs",
+        "1\n2\n3\n4\n5\n",
+        "Vendor dep",
+    );
+
+    let options = AnalysisOptions::builder()
+        .stat_only_threshold(0)
+        .exclude_paths(vec!["vendor".to_string()])
+        .build();
+    let result = analyze_repo_with_options(
+        temp_dir.path().to_str().unwrap().to_string(),
+        "main".to_string(),
+        "All".to_string(),
+        options,
+        None,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(result.total_lines_added, 2);
+    assert_eq!(result.commit_count, 2);
+}
+
+/// Test that casing variants of one name collapse into a single contributor
+#[tokio::test]
+async fn test_fuzzy_dedup_authors() {
+    let (temp_dir, repo) = init_repo();
+    commit_file(&repo, "John Smith", JAN_1_2023, "a.txt", "1\n", "One");
+    commit_file(&repo, "john smith", JAN_1_2023 + DAY, "a.txt", "2\n", "Two");
+    commit_file(
+        &repo,
+        "JOHN  SMITH",
+        JAN_1_2023 + 2 * DAY,
+        "a.txt",
+        "3\n",
+        "Three",
+    );
+    commit_file(
+        &repo,
+        "John Smith",
+        JAN_1_2023 + 3 * DAY,
+        "a.txt",
+        "4\n",
+        "Four",
+    );
+
+    assert_eq!(analyze_all(&temp_dir).await.top_contributors.len(), 3);
+
+    let options = AnalysisOptions {
+        fuzzy_dedup: true,
+        ..Default::default()
+    };
+    let result = analyze_repo_with_options(
+        temp_dir.path().to_str().unwrap().to_string(),
+        "main".to_string(),
+        "All".to_string(),
+        options,
+        None,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(result.top_contributors, vec![("John Smith".to_string(), 4)]);
+    assert_eq!(result.author_first_commit["John Smith"], "2023-01-01");
+}
+
+/// Test that commits whose tree is missing are counted as skipped, not as zero-line activity
+#[tokio::test]
+async fn test_skipped_unreadable_commits() {
+    let (temp_dir, repo) = init_repo();
+    commit_file(&repo, "Alice", JAN_1_2023, "a.txt", "one\n", "first");
+    let broken = commit_file(&repo, "Bob", JAN_1_2023 + DAY, "b.txt", "two\n", "second");
+
+    // Delete the loose tree object of the latest commit to simulate corruption
+    let tree_id = repo.find_commit(broken).unwrap().tree_id().to_string();
+    let object_path = temp_dir
+        .path()
+        .join(".git/objects")
+        .join(&tree_id[..2])
+        .join(&tree_id[2..]);
+    fs::remove_file(object_path).unwrap();
+
+    let result = analyze_all(&temp_dir).await;
+
+    assert_eq!(result.skipped_commits, 1);
+    assert_eq!(result.commit_count, 1);
+    assert_eq!(result.commit_activity.len(), 1);
+    assert_eq!(result.commit_activity[0].author, "Alice");
+    assert_eq!(result.total_lines_added, 1);
+}
+
+/// Test that the diff-free contributor listing agrees with a full analysis
+#[tokio::test]
+async fn test_list_contributors_matches_analysis() {
+    let (temp_dir, repo) = init_repo();
+    let authors = ["Alice", "Bob", "Alice", "Carol", "Alice", "Bob"];
+    for (i, author) in authors.iter().enumerate() {
+        commit_file(
+            &repo,
+            author,
+            JAN_1_2023 + i as i64 * DAY,
+            "file.txt",
+            &format!("line {}\n", i),
+            "update",
+        );
+    }
+
+    let contributors = list_contributors(
+        temp_dir.path().to_str().unwrap().to_string(),
+        "main".to_string(),
+    )
+    .await
+    .unwrap();
+    let result = analyze_all(&temp_dir).await;
+
+    assert_eq!(
+        contributors,
+        vec![
+            ("Alice".to_string(), 3),
+            ("Bob".to_string(), 2),
+            ("Carol".to_string(), 1),
+        ]
+    );
+    assert_eq!(contributors, result.top_contributors);
+}
+
+/// Test that the initial commit's lines can be left out of the totals
+#[tokio::test]
+async fn test_skip_initial_commit() {
+    let (temp_dir, repo) = init_repo();
+    let import: String = (0..1000).map(|i| format!("line {}\n", i)).collect();
+    commit_file(&repo, "Alice", JAN_1_2023, "a.txt", &import, "Import");
+    commit_file(&repo, "Bob", JAN_1_2023 + DAY, "b.txt", "1\n2\n", "Add b");
+
+    let options = AnalysisOptions {
+        skip_initial_commit: true,
+        ..Default::default()
+    };
+    let result = analyze_repo_with_options(
+        temp_dir.path().to_str().unwrap().to_string(),
+        "main".to_string(),
+        "All".to_string(),
+        options,
+        None,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(analyze_all(&temp_dir).await.total_lines_added, 1002);
+    assert_eq!(result.total_lines_added, 2);
+    assert_eq!(result.commit_activity.len(), 1);
+    assert_eq!(result.commit_activity[0].author, "Bob");
+    // The import still counts as a commit by its author
+    assert_eq!(result.commit_count, 2);
+    assert!(result.top_contributors.contains(&("Alice".to_string(), 1)));
+}
+
+/// Test that tags can be listed alongside branches and analyzed like a branch
+#[tokio::test]
+async fn test_analyze_tag() {
+    let (temp_dir, repo) = init_repo();
+    let first = commit_file(&repo, "Alice", JAN_1_2023, "a.txt", "1\n", "First");
+    let signature =
+        Signature::new("Alice", "alice@example.com", &Time::new(JAN_1_2023, 0)).unwrap();
+    repo.tag(
+        "v1.0",
+        repo.find_commit(first).unwrap().as_object(),
+        &signature,
+        "Release 1.0",
+        false,
+    )
+    .unwrap();
+    commit_file(&repo, "Bob", JAN_1_2023 + DAY, "a.txt", "1\n2\n", "Second");
+
+    let refs = get_available_refs(&repo, true).await.unwrap();
+    assert!(refs.contains(&("v1.0".to_string(), RefKind::Tag)));
+    assert!(!get_available_refs(&repo, false)
+        .await
+        .unwrap()
+        .iter()
+        .any(|(_, kind)| *kind == RefKind::Tag));
+
+    let result = analyze_repo_async(
+        temp_dir.path().to_str().unwrap().to_string(),
+        "v1.0".to_string(),
+        "All".to_string(),
+        None,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(result.commit_count, 1);
+    assert_eq!(result.top_contributors, vec![("Alice".to_string(), 1)]);
+}
+
+/// Test that progress updates report the date of the commits being processed
+#[tokio::test]
+async fn test_progress_current_date() {
+    let (temp_dir, repo) = init_repo();
+    for i in 0..5 {
+        commit_file(
+            &repo,
+            "Alice",
+            JAN_1_2023 + i * DAY,
+            "a.txt",
+            &format!("{}\n", i),
+            "Update",
+        );
+    }
+
+    let (tx, mut rx) = tokio::sync::mpsc::channel::<ProgressEstimate>(32);
+    let collector = tokio::spawn(async move {
+        let mut dates = Vec::new();
+        while let Some(progress) = rx.recv().await {
+            dates.extend(progress.current_date);
+        }
+        dates
+    });
+
+    analyze_repo_async(
+        temp_dir.path().to_str().unwrap().to_string(),
+        "main".to_string(),
+        "All".to_string(),
+        Some(tx),
+    )
+    .await
+    .unwrap();
+    let dates = collector.await.unwrap();
+
+    assert!(!dates.is_empty());
+    assert!(dates
+        .iter()
+        .all(|date| date.as_str() >= "2023-01-01" && date.as_str() <= "2023-01-05"));
+}
+
+/// Test that the rayon processing path produces the same statistics as the tokio path
+#[tokio::test]
+async fn test_rayon_matches_tokio() {
+    let (temp_dir, repo) = init_repo();
+    let mut content = String::new();
+    for i in 0..250 {
+        content.push_str(&format!("{}\n", i));
+        let author = if i % 3 == 0 { "Bob" } else { "Alice" };
+        commit_file(
+            &repo,
+            author,
+            JAN_1_2023 + i * DAY,
+            "a.txt",
+            &content,
+            "Add line",
+        );
+    }
+
+    let analyze = |parallelism, progress_tx| {
+        analyze_repo_with_options(
+            temp_dir.path().to_str().unwrap().to_string(),
+            "main".to_string(),
+            "All".to_string(),
+            AnalysisOptions {
+                parallelism,
+                ..Default::default()
+            },
+            progress_tx,
+        )
+    };
+
+    // Progress is sent from rayon's worker threads, outside the async runtime
+    let (tx, mut rx) = tokio::sync::mpsc::channel::<ProgressEstimate>(32);
+    let collector = tokio::spawn(async move {
+        let mut processed = 0;
+        while let Some(progress) = rx.recv().await {
+            processed = processed.max(progress.processed_commits);
+        }
+        processed
+    });
+
+    let tokio_result = analyze(Parallelism::Tokio, None).await.unwrap();
+    let rayon_result = analyze(Parallelism::Rayon, Some(tx)).await.unwrap();
+
+    assert_eq!(collector.await.unwrap(), 250);
+    assert_eq!(rayon_result.commit_count, 250);
+    assert_eq!(rayon_result.commit_count, tokio_result.commit_count);
+    assert_eq!(
+        rayon_result.total_lines_added,
+        tokio_result.total_lines_added
+    );
+    assert_eq!(
+        rayon_result.total_lines_deleted,
+        tokio_result.total_lines_deleted
+    );
+    assert_eq!(rayon_result.top_contributors, tokio_result.top_contributors);
+    assert_eq!(rayon_result.commit_activity, tokio_result.commit_activity);
+    assert_eq!(
+        rayon_result.author_first_commit,
+        tokio_result.author_first_commit
+    );
+}
+
+/// Test that commits made on the same day keep their own timestamps
+#[tokio::test]
+async fn test_same_day_commit_timestamps() {
+    let (temp_dir, repo) = init_repo();
+    commit_file(&repo, "Alice", JAN_1_2023 + 3600, "a.txt", "1\n", "Morning");
+    commit_file(
+        &repo,
+        "Alice",
+        JAN_1_2023 + 7200,
+        "a.txt",
+        "1\n2\n",
+        "Later",
+    );
+
+    let result = analyze_repo_async(
+        temp_dir.path().to_str().unwrap().to_string(),
+        "main".to_string(),
+        "All".to_string(),
+        None,
+    )
+    .await
+    .unwrap();
+
+    let mut timestamps: Vec<i64> = result
+        .commit_activity
+        .iter()
+        .map(|activity| activity.timestamp)
+        .collect();
+    timestamps.sort();
+    assert_eq!(timestamps, vec![JAN_1_2023 + 3600, JAN_1_2023 + 7200]);
+    assert!(result
+        .commit_activity
+        .iter()
+        .all(|activity| activity.date == "2023-01-01"));
+}
+
+/// Test that options built with the builder are applied by the analysis
+#[tokio::test]
+async fn test_analysis_options_builder() {
+    let (temp_dir, repo) = init_repo();
+    commit_file(&repo, "Alice", JAN_1_2023, "a.txt", "1\n", "Add a");
+    commit_file(&repo, "Bob", JAN_1_2023 + 5 * DAY, "a.txt", "1\n2\n", "Bob");
+    commit_file(
+        &repo,
+        "dependabot[bot]",
+        JAN_1_2023 + 6 * DAY,
+        "Cargo.lock",
+        "1\n",
+        "Bump",
+    );
+
+    let options = AnalysisOptions::builder()
+        .since(chrono::NaiveDate::from_ymd_opt(2023, 1, 3).unwrap())
+        .exclude_authors(["dependabot"])
+        .parallelism(Parallelism::Rayon)
+        .build();
+    assert_eq!(
+        options,
+        AnalysisOptions {
+            since: chrono::NaiveDate::from_ymd_opt(2023, 1, 3),
+            exclude_authors: vec!["dependabot".to_string()],
+            parallelism: Parallelism::Rayon,
+            ..Default::default()
+        }
+    );
+
+    let result = analyze_repo_with_options(
+        temp_dir.path().to_str().unwrap().to_string(),
+        "main".to_string(),
+        "All".to_string(),
+        options,
+        None,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(result.commit_count, 1);
+    assert_eq!(result.top_contributors, vec![("Bob".to_string(), 1)]);
+}
+
+/// Test that commits over the stat-only threshold are counted from diff stats with
+/// the same totals as a full hunk walk
+#[tokio::test]
+async fn test_stat_only_threshold() {
+    let (temp_dir, repo) = init_repo();
+    commit_file(&repo, "Alice", JAN_1_2023, "README", "gitstats\n", "Init");
+
+    // One commit touching many files, like a regenerated lockfile or data dump
+    let mut index = repo.index().unwrap();
+    for i in 0..50 {
+        let name = format!("data/{i}.txt");
+        let file_path = temp_dir.path().join(&name);
+        fs::create_dir_all(file_path.parent().unwrap()).unwrap();
+        fs::write(&file_path, "a\nb\nc\n".repeat(i + 1)).unwrap();
+        index.add_path(Path::new(&name)).unwrap();
+    }
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let signature =
+        Signature::new("Bob", "bob@example.com", &Time::new(JAN_1_2023 + DAY, 0)).unwrap();
+    let parent = repo.head().unwrap().peel_to_commit().unwrap();
+    repo.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        "Dump data",
+        &tree,
+        &[&parent],
+    )
+    .unwrap();
+
+    let analyze = |options: AnalysisOptions| {
+        analyze_repo_with_options(
+            temp_dir.path().to_str().unwrap().to_string(),
+            "main".to_string(),
+            "All".to_string(),
+            options,
+            None,
+        )
+    };
+    let walked = analyze(AnalysisOptions::default()).await.unwrap();
+    let stat_only = analyze(AnalysisOptions::builder().stat_only_threshold(10).build())
+        .await
+        .unwrap();
+
+    // 3 lines per repetition, 1 + 2 + ... + 50 repetitions, plus the README
+    assert_eq!(walked.total_lines_added, 3 * 1275 + 1);
+    assert_eq!(stat_only.total_lines_added, walked.total_lines_added);
+    assert_eq!(stat_only.total_lines_deleted, walked.total_lines_deleted);
+    // The stats carry no per-file breakdown, so only the line counts can match
+    let lines = |result: &gitstats::AnalysisResult| {
+        result
+            .commit_activity
+            .iter()
+            .map(|a| (a.date.clone(), a.lines_added, a.lines_deleted))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(lines(&stat_only), lines(&walked));
+    assert!(stat_only.lines_by_extension.is_empty());
+}
+
+/// Test that a scoped analysis only reflects commits under the scope directory
+#[tokio::test]
+async fn test_scope_path() {
+    let (temp_dir, repo) = init_repo();
+    commit_file(
+        &repo,
+        "Alice",
+        JAN_1_2023,
+        "packages/foo/a.rs",
+        "1\n2\n",
+        "Foo",
+    );
+    commit_file(
+        &repo,
+        "Bob",
+        JAN_1_2023 + DAY,
+        "packages/bar/b.rs",
+        "1\n2\n3\n",
+        "Bar",
+    );
+    commit_file(
+        &repo,
+        "Alice",
+        JAN_1_2023 + 2 * DAY,
+        "packages/foo/a.rs",
+        "1\n",
+        "Trim foo",
+    );
+    commit_file(
+        &repo,
+        "Carol",
+        JAN_1_2023 + 3 * DAY,
+        "README",
+        "docs\n",
+        "Docs",
+    );
+
+    let result = analyze_repo_with_options(
+        temp_dir.path().to_str().unwrap().to_string(),
+        "main".to_string(),
+        "All".to_string(),
+        AnalysisOptions::builder()
+            .scope_path("packages/foo/")
+            .build(),
+        None,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(result.commit_count, 2);
+    assert_eq!(result.total_lines_added, 2);
+    assert_eq!(result.total_lines_deleted, 1);
+    assert_eq!(result.top_contributors, vec![("Alice".to_string(), 2)]);
+}
+
+/// Test that every branch is analyzed and keyed by name
+#[tokio::test]
+async fn test_analyze_all_branches() {
+    let (temp_dir, repo) = init_repo();
+    commit_file(&repo, "Alice", JAN_1_2023, "a.txt", "1\n", "Add a");
+    // The initial branch name depends on the local git config
+    let default_branch = repo.head().unwrap().shorthand().unwrap().to_string();
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    repo.branch("develop", &head, false).unwrap();
+    repo.set_head("refs/heads/develop").unwrap();
+    commit_file(&repo, "Bob", JAN_1_2023 + DAY, "b.txt", "1\n2\n", "Add b");
+    commit_file(&repo, "Bob", JAN_1_2023 + 2 * DAY, "b.txt", "1\n", "Trim b");
+
+    let results = analyze_all_branches(
+        temp_dir.path().to_str().unwrap().to_string(),
+        "All".to_string(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[&default_branch].commit_count, 1);
+    assert_eq!(results["develop"].commit_count, 3);
+    assert_eq!(results["develop"].total_lines_added, 3);
+}
+
+/// Test that commits written by `git revert` are counted as reverts
+#[tokio::test]
+async fn test_revert_count() {
+    let (temp_dir, repo) = init_repo();
+    commit_file(&repo, "Alice", JAN_1_2023, "a.txt", "1\n", "Add a");
+    let added = commit_file(&repo, "Alice", JAN_1_2023 + DAY, "a.txt", "1\n2\n", "Add 2");
+    commit_file(
+        &repo,
+        "Bob",
+        JAN_1_2023 + 2 * DAY,
+        "a.txt",
+        "1\n",
+        &format!("Revert \"Add 2\"\n\nThis reverts commit {}.", added),
+    );
+
+    let result = analyze_all(&temp_dir).await;
+
+    assert_eq!(result.commit_count, 3);
+    assert_eq!(result.revert_count, 1);
+    assert_eq!(result.filtered_by_author("Bob").unwrap().revert_count, 1);
+    assert_eq!(result.filtered_by_author("Alice").unwrap().revert_count, 0);
+}
+
+/// Test that the extension with the most changed lines is reported as the main language
+#[tokio::test]
+async fn test_primary_language() {
+    let (temp_dir, repo) = init_repo();
+    commit_file(
+        &repo,
+        "Alice",
+        JAN_1_2023,
+        "main.rs",
+        "fn main() {}\n\n\n",
+        "Add main",
+    );
+    commit_file(
+        &repo,
+        "Alice",
+        JAN_1_2023 + DAY,
+        "lib.RS",
+        "mod a;\nmod b;\n",
+        "Add lib",
+    );
+    commit_file(
+        &repo,
+        "Bob",
+        JAN_1_2023 + 2 * DAY,
+        "README.md",
+        "# Demo\n",
+        "Add readme",
+    );
+    commit_file(
+        &repo,
+        "Bob",
+        JAN_1_2023 + 3 * DAY,
+        "Makefile",
+        "a\nb\nc\nd\ne\nf\n",
+        "Add make",
+    );
+
+    let result = analyze_all(&temp_dir).await;
+
+    assert_eq!(result.lines_by_extension["rs"], (5, 0));
+    assert_eq!(result.lines_by_extension["md"], (1, 0));
+    assert_eq!(result.lines_by_extension.len(), 2);
+    assert_eq!(result.primary_language.as_deref(), Some("rs"));
+    assert_eq!(
+        result
+            .filtered_by_author("Bob")
+            .unwrap()
+            .primary_language
+            .as_deref(),
+        Some("md")
+    );
+}
+
+/// Test that contributors with equal commit counts are ranked alphabetically
+#[tokio::test]
+async fn test_top_contributors_tie_break() {
+    let (temp_dir, repo) = init_repo();
+    commit_file(&repo, "Carol", JAN_1_2023, "c.txt", "c\n", "Add c");
+    commit_file(&repo, "Alice", JAN_1_2023 + DAY, "a.txt", "a\n", "Add a");
+    commit_file(&repo, "Bob", JAN_1_2023 + 2 * DAY, "b.txt", "b\n", "Add b");
+
+    let result = analyze_all(&temp_dir).await;
+
+    assert_eq!(
+        result.top_contributors,
+        vec![
+            ("Alice".to_string(), 1),
+            ("Bob".to_string(), 1),
+            ("Carol".to_string(), 1)
+        ]
+    );
+}
+
+/// Test that a detached HEAD is analyzed and reported as the synthetic detached branch
+#[tokio::test]
+async fn test_detached_head() {
+    let (temp_dir, repo) = init_repo();
+    let first = commit_file(&repo, "Alice", JAN_1_2023, "a.txt", "a\n", "Add a");
+    commit_file(&repo, "Alice", JAN_1_2023 + DAY, "a.txt", "b\n", "Edit a");
+    let branch = repo.head().unwrap().shorthand().unwrap().to_string();
+    repo.set_head_detached(first).unwrap();
+
+    // "main" doesn't exist, so the analysis falls back to the detached HEAD
+    let detached = analyze_all(&temp_dir).await;
+    assert_eq!(detached.commit_count, 1);
+    assert_eq!(detached.selected_branch, DETACHED_HEAD);
+    assert_eq!(
+        detached.available_branches,
+        vec![DETACHED_HEAD.to_string(), branch.clone()]
+    );
+
+    let named = analyze_repo_async(
+        temp_dir.path().to_str().unwrap().to_string(),
+        branch.clone(),
+        "All".to_string(),
+        None,
+    )
+    .await
+    .unwrap();
+    assert_eq!(named.commit_count, 2);
+    assert_eq!(named.selected_branch, branch);
+}
+
+/// Test that commits are counted per month for each author separately
+#[tokio::test]
+async fn test_author_commit_frequency() {
+    let (temp_dir, repo) = init_repo();
+    commit_file(&repo, "Alice", JAN_1_2023, "a.txt", "a\n", "Add a");
+    commit_file(
+        &repo,
+        "Alice",
+        JAN_1_2023 + 7 * DAY,
+        "a.txt",
+        "b\n",
+        "Edit a",
+    );
+    commit_file(&repo, "Bob", JAN_1_2023 + 35 * DAY, "b.txt", "b\n", "Add b");
+
+    let result = analyze_all(&temp_dir).await;
+
+    let alice = &result.author_commit_frequency["Alice"];
+    assert_eq!(alice.len(), 1);
+    assert_eq!(alice["2023-01"], 2);
+    let bob = &result.author_commit_frequency["Bob"];
+    assert_eq!(bob.len(), 1);
+    assert_eq!(bob["2023-02"], 1);
+    assert_eq!(
+        result
+            .filtered_by_author("Bob")
+            .unwrap()
+            .author_commit_frequency,
+        HashMap::from([("Bob".to_string(), bob.clone())])
+    );
+}
+
+/// Test that code-only analysis leaves out commits touching only docs or config
+#[tokio::test]
+async fn test_code_only() {
+    let (temp_dir, repo) = init_repo();
+    commit_file(
+        &repo,
+        "Alice",
+        JAN_1_2023,
+        "main.rs",
+        "fn main() {}\n",
+        "Add main",
+    );
+    commit_file(
+        &repo,
+        "Bob",
+        JAN_1_2023 + DAY,
+        "README.md",
+        "# Demo\n",
+        "Add docs",
+    );
+    commit_file(
+        &repo,
+        "Bob",
+        JAN_1_2023 + 2 * DAY,
+        "ci.yml",
+        "on: push\n",
+        "Add CI",
+    );
+
+    let analyze = |options: AnalysisOptions| {
+        analyze_repo_with_options(
+            temp_dir.path().to_str().unwrap().to_string(),
+            "main".to_string(),
+            "All".to_string(),
+            options,
+            None,
+        )
+    };
+    let code_only = analyze(AnalysisOptions::builder().code_only(true).build())
+        .await
+        .unwrap();
+    assert_eq!(code_only.commit_count, 1);
+    assert_eq!(code_only.top_contributors, vec![("Alice".to_string(), 1)]);
+
+    // Overriding the table makes YAML count as code again
+    let yaml_is_code = analyze(
+        AnalysisOptions::builder()
+            .code_only(true)
+            .non_code_extensions(["md"])
+            .build(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(yaml_is_code.commit_count, 2);
+}
+
+/// Test that commits leaving the tree unchanged are counted, and can be left out
+#[tokio::test]
+async fn test_empty_commits() {
+    let (temp_dir, repo) = init_repo();
+    commit_file(&repo, "Alice", JAN_1_2023, "a.txt", "a\n", "Add a");
+    // Rewriting the same content commits an identical tree, like `--allow-empty`
+    commit_file(&repo, "Bob", JAN_1_2023 + DAY, "a.txt", "a\n", "Trigger CI");
+    commit_file(
+        &repo,
+        "Alice",
+        JAN_1_2023 + 2 * DAY,
+        "a.txt",
+        "b\n",
+        "Edit a",
+    );
+
+    let analyze = |options: AnalysisOptions| {
+        analyze_repo_with_options(
+            temp_dir.path().to_str().unwrap().to_string(),
+            "main".to_string(),
+            "All".to_string(),
+            options,
+            None,
+        )
+    };
+    let counted = analyze(AnalysisOptions::default()).await.unwrap();
+    let excluded = analyze(
+        AnalysisOptions::builder()
+            .exclude_empty_commits(true)
+            .build(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(counted.commit_count, 3);
+    assert_eq!(counted.empty_commit_count, 1);
+    assert_eq!(
+        counted
+            .filtered_by_author("Bob")
+            .unwrap()
+            .empty_commit_count,
+        1
+    );
+    assert_eq!(
+        counted
+            .filtered_by_author("Alice")
+            .unwrap()
+            .empty_commit_count,
+        0
+    );
+
+    assert_eq!(excluded.commit_count, 2);
+    assert_eq!(excluded.empty_commit_count, 1);
+    assert_eq!(excluded.commit_activity.len(), 2);
+    assert!(excluded
+        .top_contributors
+        .iter()
+        .all(|(name, _)| name != "Bob"));
+    assert_eq!(excluded.total_lines_added, counted.total_lines_added);
+}
+
+/// Test that activity entries come out in the same order on every run
+#[tokio::test]
+async fn test_activity_order_is_deterministic() {
+    let (temp_dir, repo) = init_repo();
+    for i in 0..6 {
+        commit_file(
+            &repo,
+            if i % 2 == 0 { "Alice" } else { "Bob" },
+            JAN_1_2023 + (i / 3) * DAY + i,
+            &format!("file{}.txt", i),
+            "a\n",
+            "Add file",
+        );
+    }
+
+    let first = analyze_all(&temp_dir).await;
+    let second = analyze_all(&temp_dir).await;
+
+    assert_eq!(
+        format!("{:?}", first.commit_activity),
+        format!("{:?}", second.commit_activity)
+    );
+    assert!(first
+        .commit_activity
+        .iter()
+        .all(|entry| !entry.oid.is_empty()));
+    let key = |entry: &gitstats::types::CommitActivity| {
+        (entry.date.clone(), entry.timestamp, entry.oid.clone())
+    };
+    assert!(first
+        .commit_activity
+        .windows(2)
+        .all(|pair| key(&pair[0]) < key(&pair[1])));
+}
+
+/// Test that turning off activity collection keeps the totals but records no entries
+#[tokio::test]
+async fn test_collect_activity_disabled() {
+    let (temp_dir, repo) = init_repo();
+    commit_file(&repo, "Alice", JAN_1_2023, "a.txt", "1\n2\n", "Add a");
+    commit_file(&repo, "Bob", JAN_1_2023 + DAY, "a.txt", "1\n", "Trim a");
+    commit_file(
+        &repo,
+        "Alice",
+        JAN_1_2023 + 2 * DAY,
+        "b.txt",
+        "1\n",
+        "Add b",
+    );
+
+    let full = analyze_all(&temp_dir).await;
+    let totals_only = analyze_repo_with_options(
+        temp_dir.path().to_str().unwrap().to_string(),
+        "main".to_string(),
+        "All".to_string(),
+        AnalysisOptions::builder().collect_activity(false).build(),
+        None,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(full.commit_activity.len(), 3);
+    assert!(totals_only.commit_activity.is_empty());
+    assert_eq!(totals_only.commit_count, full.commit_count);
+    assert_eq!(totals_only.total_lines_added, full.total_lines_added);
+    assert_eq!(totals_only.total_lines_deleted, full.total_lines_deleted);
+    assert_eq!(totals_only.top_contributors, full.top_contributors);
+}
+
+/// Test that a single commit's diff stats match what `git show --numstat` reports
+#[test]
+fn test_commit_diff_stats() {
+    let (_temp_dir, repo) = init_repo();
+    let root = commit_file(&repo, "Alice", JAN_1_2023, "a.txt", "1\n2\n3\n4\n", "Add a");
+    commit_file(&repo, "Alice", JAN_1_2023 + DAY, "b.txt", "x\n", "Add b");
+    // Rewrite two lines of a.txt and append one: numstat reports "3 2 a.txt"
+    let oid = commit_file(
+        &repo,
+        "Alice",
+        JAN_1_2023 + 2 * DAY,
+        "a.txt",
+        "1\ntwo\nthree\n4\n5\n",
+        "Edit a",
+    );
+    let commit = repo.find_commit(oid).unwrap();
+
+    for accuracy in [AccuracyMode::Fast, AccuracyMode::GitNumstat] {
+        let options = AnalysisOptions::builder().accuracy(accuracy).build();
+        assert_eq!(
+            commit_diff_stats(&repo, &commit, &options).unwrap(),
+            (3, 2, 1)
+        );
+    }
+
+    let root = repo.find_commit(root).unwrap();
+    assert_eq!(
+        commit_diff_stats(&repo, &root, &AnalysisOptions::default()).unwrap(),
+        (4, 0, 1)
+    );
+}
+
+#[tokio::test]
+async fn test_estimate_analysis_duration() {
+    let (temp_dir, repo) = init_repo();
+    for day in 0..4 {
+        let content = format!("{}\n", day);
+        commit_file(
+            &repo,
+            "Alice",
+            JAN_1_2023 + day * DAY,
+            "a.txt",
+            &content,
+            "Edit a",
+        );
+    }
+    let path = temp_dir.path().to_str().unwrap().to_string();
+
+    assert_eq!(
+        estimate_commit_count(path.clone(), "main".to_string())
+            .await
+            .unwrap(),
+        4
+    );
+    // 4 commits at 2 commits/sec, using the rate measured by a previous run
+    assert_eq!(
+        estimate_analysis_duration(path.clone(), "main".to_string(), Some(2.0))
+            .await
+            .unwrap(),
+        Duration::from_secs(2)
+    );
+    // Without a measured rate the benchmark rate is used
+    assert_eq!(
+        estimate_analysis_duration(path, "main".to_string(), None)
+            .await
+            .unwrap(),
+        Duration::from_secs_f64(4.0 / DEFAULT_COMMITS_PER_SECOND)
+    );
+
+    // The estimate scales linearly with the commit count
+    for rate in [None, Some(50.0), Some(f64::INFINITY)] {
+        let base = estimated_duration(1_000, rate);
+        assert!(base > Duration::ZERO);
+        assert_eq!(estimated_duration(2_000, rate), base * 2);
+        assert_eq!(estimated_duration(10_000, rate), base * 10);
+    }
+}
+
+/// Test that an oldest-first walk starts from the initial commit
+#[tokio::test]
+async fn test_oldest_first() {
+    let (temp_dir, repo) = init_repo();
+    let initial = commit_file(&repo, "Alice", JAN_1_2023, "a.txt", "1\n", "Initial");
+    commit_file(&repo, "Bob", JAN_1_2023 + DAY, "a.txt", "1\n2\n", "Second");
+    commit_file(
+        &repo,
+        "Carol",
+        JAN_1_2023 + 2 * DAY,
+        "a.txt",
+        "1\n2\n3\n",
+        "Third",
+    );
+
+    let options = AnalysisOptions::builder().oldest_first(true).build();
+    let result = analyze_repo_with_options(
+        temp_dir.path().to_str().unwrap().to_string(),
+        "main".to_string(),
+        "All".to_string(),
+        options,
+        None,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(result.commit_count, 3);
+    assert_eq!(result.commit_activity[0].oid, initial.to_string());
+    assert_eq!(result.commit_activity[0].author, "Alice");
+}
+
+/// Test that an oldest-first walk keeps commits made in the same second in history
+/// order, where the default sort can only fall back to the commit ID
+#[tokio::test]
+async fn test_oldest_first_same_second_order() {
+    let (temp_dir, repo) = init_repo();
+    let mut contents = String::new();
+    let walked: Vec<String> = (0..6)
+        .map(|i| {
+            contents.push_str(&format!("{}\n", i));
+            let message = format!("Commit {}", i);
+            commit_file(&repo, "Alice", JAN_1_2023, "a.txt", &contents, &message).to_string()
+        })
+        .collect();
+    let mut by_id = walked.clone();
+    by_id.sort();
+    assert_ne!(by_id, walked, "commit IDs happen to be in time order");
+
+    let analyze = |oldest_first| {
+        analyze_repo_with_options(
+            temp_dir.path().to_str().unwrap().to_string(),
+            "main".to_string(),
+            "All".to_string(),
+            AnalysisOptions::builder()
+                .oldest_first(oldest_first)
+                .build(),
+            None,
+        )
+    };
+    let oids = |result: AnalysisResult| -> Vec<String> {
+        result
+            .commit_activity
+            .into_iter()
+            .map(|activity| activity.oid)
+            .collect()
+    };
+
+    assert_eq!(oids(analyze(true).await.unwrap()), walked);
+    assert_eq!(oids(analyze(false).await.unwrap()), by_id);
+}
+
+/// Test that commits land in the right logarithmic size buckets, boundaries included
+#[tokio::test]
+async fn test_commit_size_histogram() {
+    let (temp_dir, repo) = init_repo();
+    for (day, (file, lines)) in [
+        ("a.txt", 5),
+        ("b.txt", 10),
+        ("c.txt", 11),
+        ("d.txt", 100),
+        ("e.txt", 1000),
+        ("f.txt", 1001),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let content = "line\n".repeat(lines);
+        commit_file(
+            &repo,
+            "Alice",
+            JAN_1_2023 + day as i64 * DAY,
+            file,
+            &content,
+            "Add",
+        );
+    }
+    // Rewriting a file with the same content changes 0 lines
+    let content = "line\n".repeat(1001);
+    commit_file(
+        &repo,
+        "Alice",
+        JAN_1_2023 + 6 * DAY,
+        "f.txt",
+        &content,
+        "Touch",
+    );
+
+    let result = analyze_all(&temp_dir).await;
+
+    assert_eq!(
+        result.commit_size_histogram,
+        vec![
+            ("0".to_string(), 1),
+            ("1-10".to_string(), 2),
+            ("11-100".to_string(), 2),
+            ("101-1000".to_string(), 1),
+            ("1000+".to_string(), 1),
+        ]
+    );
+}
+
+/// Test that a renamed file's hotspot count includes the edits made before the rename
+#[tokio::test]
+async fn test_file_hotspots_follow_renames() {
+    let (temp_dir, repo) = init_repo();
+    let mut content = String::new();
+    for day in 0..3 {
+        content.push_str(&format!("line {}\n", day));
+        commit_file(
+            &repo,
+            "Alice",
+            JAN_1_2023 + day * DAY,
+            "old.rs",
+            &content,
+            "Edit",
+        );
+    }
+    commit_file(
+        &repo,
+        "Alice",
+        JAN_1_2023 + 3 * DAY,
+        "other.rs",
+        "1\n",
+        "Other",
+    );
+
+    // Rename old.rs to new.rs without changing it
+    fs::rename(
+        temp_dir.path().join("old.rs"),
+        temp_dir.path().join("new.rs"),
+    )
+    .unwrap();
+    let mut index = repo.index().unwrap();
+    index.remove_path(Path::new("old.rs")).unwrap();
+    index.add_path(Path::new("new.rs")).unwrap();
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let signature = Signature::new(
+        "Alice",
+        "alice@example.com",
+        &Time::new(JAN_1_2023 + 4 * DAY, 0),
+    )
+    .unwrap();
+    let parent = repo.head().unwrap().peel_to_commit().unwrap();
+    repo.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        "Rename",
+        &tree,
+        &[&parent],
+    )
+    .unwrap();
+
+    for day in 5..7 {
+        content.push_str(&format!("line {}\n", day));
+        commit_file(
+            &repo,
+            "Alice",
+            JAN_1_2023 + day * DAY,
+            "new.rs",
+            &content,
+            "Edit",
+        );
+    }
+
+    let analyze = |follow_renames| {
+        analyze_repo_with_options(
+            temp_dir.path().to_str().unwrap().to_string(),
+            "main".to_string(),
+            "All".to_string(),
+            AnalysisOptions::builder()
+                .follow_renames(follow_renames)
+                .build(),
+            None,
+        )
+    };
+
+    // 3 edits before the rename, the rename itself and 2 edits after it
+    let followed = analyze(true).await.unwrap();
+    assert_eq!(
+        followed.file_hotspots,
+        vec![("new.rs".to_string(), 6), ("other.rs".to_string(), 1)]
+    );
+
+    // Without rename detection the history is split across both paths
+    let split = analyze(false).await.unwrap();
+    assert_eq!(
+        split.file_hotspots,
+        vec![
+            ("old.rs".to_string(), 4),
+            ("new.rs".to_string(), 3),
+            ("other.rs".to_string(), 1),
+        ]
+    );
+}
+
+/// Test that sampling analyzes about half the commits and scales the totals back up
+#[tokio::test]
+async fn test_sampling() {
+    let (temp_dir, repo) = init_repo();
+    for day in 0..100 {
+        let content = format!("{}\n", day);
+        commit_file(
+            &repo,
+            "Alice",
+            JAN_1_2023 + day * DAY,
+            "a.txt",
+            &content,
+            "Edit",
+        );
+    }
+    let analyze = |sample: Option<f64>| {
+        let options = AnalysisOptions {
+            sample,
+            ..Default::default()
+        };
+        analyze_repo_with_options(
+            temp_dir.path().to_str().unwrap().to_string(),
+            "main".to_string(),
+            "All".to_string(),
+            options,
+            None,
+        )
+    };
+
+    let full = analyze(None).await.unwrap();
+    assert!(!full.approximate);
+    assert_eq!(full.commit_activity.len(), 100);
+
+    let sampled = analyze(Some(0.5)).await.unwrap();
+    let processed = sampled.commit_activity.len();
+    assert!(
+        (35..=65).contains(&processed),
+        "expected about 50 sampled commits, got {}",
+        processed
+    );
+    assert!(sampled.approximate);
+    assert_eq!(sampled.commit_count, processed * 2);
+
+    // The same commits are picked every run
+    let again = analyze(Some(0.5)).await.unwrap();
+    let oids = |result: &gitstats::AnalysisResult| {
+        result
+            .commit_activity
+            .iter()
+            .map(|a| a.oid.clone())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(oids(&sampled), oids(&again));
+}
+
+/// Test that the origin remote's URL is reported, and left out when there is none
+#[tokio::test]
+async fn test_remote_url() {
+    let (temp_dir, repo) = init_repo();
+    commit_file(&repo, "Alice", JAN_1_2023, "a.txt", "1\n", "Add a");
+
+    let result = analyze_all(&temp_dir).await;
+    assert_eq!(result.remote_url, None);
+
+    repo.remote("origin", "https://github.com/tcashel/gitstats.git")
+        .unwrap();
+    let result = analyze_all(&temp_dir).await;
+    assert_eq!(
+        result.remote_url.as_deref(),
+        Some("https://github.com/tcashel/gitstats.git")
+    );
+}
+
+/// Test how counting from diff stats differs from the hunk-by-hunk count
+#[tokio::test]
+async fn test_fast_stats() {
+    let (temp_dir, repo) = init_repo();
+    commit_file(&repo, "Alice", JAN_1_2023, "a.rs", "1\n2\n3\n", "Add a");
+    commit_file(
+        &repo,
+        "Alice",
+        JAN_1_2023 + DAY,
+        "a.rs",
+        "1\ntwo\n3\n4\n",
+        "Edit a",
+    );
+    commit_file(
+        &repo,
+        "Bob",
+        JAN_1_2023 + 2 * DAY,
+        "notes.md",
+        "x\ny\n",
+        "Notes",
+    );
+
+    let analyze = |options: AnalysisOptions| {
+        analyze_repo_with_options(
+            temp_dir.path().to_str().unwrap().to_string(),
+            "main".to_string(),
+            "All".to_string(),
+            options,
+            None,
+        )
+    };
+    let precise = analyze(AnalysisOptions::default()).await.unwrap();
+    let fast = analyze(AnalysisOptions::builder().fast_stats(true).build())
+        .await
+        .unwrap();
+
+    // Line totals agree
+    assert_eq!(precise.total_lines_added, 7);
+    assert_eq!(precise.total_lines_deleted, 1);
+    assert_eq!(fast.commit_count, precise.commit_count);
+    assert_eq!(fast.total_lines_added, precise.total_lines_added);
+    assert_eq!(fast.total_lines_deleted, precise.total_lines_deleted);
+
+    // but nothing is known about individual files
+    assert_eq!(precise.primary_language.as_deref(), Some("rs"));
+    assert_eq!(fast.primary_language, None);
+    assert!(fast.lines_by_extension.is_empty());
+    assert!(fast.file_hotspots.is_empty());
+}
+
+/// Test that fast stats still leave excluded paths out of the totals
+#[tokio::test]
+async fn test_fast_stats_exclude_paths() {
+    let (temp_dir, repo) = init_repo();
+    commit_file(&repo, "Alice", JAN_1_2023, "a.rs", "1\n2\n3\n", "Add a");
+    commit_file(
+        &repo,
+        "Bob",
+        JAN_1_2023 + DAY,
+        "notes.md",
+        "x\ny\n",
+        "Notes",
+    );
+
+    let analyze = |fast_stats| {
+        analyze_repo_with_options(
+            temp_dir.path().to_str().unwrap().to_string(),
+            "main".to_string(),
+            "All".to_string(),
+            AnalysisOptions::builder()
+                .fast_stats(fast_stats)
+                .exclude_paths(vec!["notes.md".to_string()])
+                .build(),
+            None,
+        )
+    };
+    let precise = analyze(false).await.unwrap();
+    let fast = analyze(true).await.unwrap();
+
+    assert_eq!(precise.total_lines_added, 3);
+    assert_eq!(fast.total_lines_added, 3);
+    assert_eq!(fast.commit_count, 2);
+}
+
+/// Test that commits are tallied by their number of parents
+#[tokio::test]
+async fn test_parent_count_distribution() {
+    let (temp_dir, repo) = init_repo();
+    let base = commit_file(&repo, "Alice", JAN_1_2023, "a.txt", "1\n", "Base");
+    let main_ref = repo.head().unwrap().name().unwrap().to_string();
+
+    repo.branch("feature", &repo.find_commit(base).unwrap(), false)
+        .unwrap();
+    repo.set_head("refs/heads/feature").unwrap();
+    let feature = commit_file(&repo, "Bob", JAN_1_2023 + DAY, "b.txt", "1\n", "Feature");
+    repo.set_head(&main_ref).unwrap();
+    repo.checkout_head(Some(git2::build::CheckoutBuilder::new().force()))
+        .unwrap();
+    let main = commit_file(&repo, "Alice", JAN_1_2023 + 2 * DAY, "c.txt", "1\n", "Main");
+
+    // Merge the feature branch by bringing its file into the mainline tree
+    fs::write(temp_dir.path().join("b.txt"), "1\n").unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new("b.txt")).unwrap();
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let signature = Signature::new(
+        "Alice",
+        "alice@example.com",
+        &Time::new(JAN_1_2023 + 3 * DAY, 0),
+    )
+    .unwrap();
+    repo.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        "Merge feature",
+        &tree,
+        &[
+            &repo.find_commit(main).unwrap(),
+            &repo.find_commit(feature).unwrap(),
+        ],
+    )
+    .unwrap();
+
+    let result = analyze_all(&temp_dir).await;
+
+    assert_eq!(
+        result.parent_count_distribution,
+        HashMap::from([(0, 1), (1, 2), (2, 1)])
+    );
+}
+
+/// Test that anonymized results name contributors by commit rank, consistently
+#[tokio::test]
+async fn test_anonymize_contributors() {
+    let (temp_dir, repo) = init_repo();
+    commit_file(&repo, "Alice", JAN_1_2023, "a.txt", "1\n", "First");
+    commit_file(&repo, "Bob", JAN_1_2023 + DAY, "a.txt", "2\n", "Second");
+    commit_file(&repo, "Bob", JAN_1_2023 + 2 * DAY, "a.txt", "3\n", "Third");
+    commit_file(
+        &repo,
+        "Carol",
+        JAN_1_2023 + 3 * DAY,
+        "a.txt",
+        "4\n",
+        "Fourth",
+    );
+
+    let options = AnalysisOptions::builder().anonymize(true).build();
+    let result = analyze_repo_with_options(
+        temp_dir.path().to_str().unwrap().to_string(),
+        "main".to_string(),
+        "All".to_string(),
+        options,
+        None,
+    )
+    .await
+    .unwrap();
+
+    // Bob has the most commits; Alice and Carol tie and are ranked by name
+    assert_eq!(
+        result.top_contributors,
+        vec![
+            ("Contributor 1".to_string(), 2),
+            ("Contributor 2".to_string(), 1),
+            ("Contributor 3".to_string(), 1),
+        ]
+    );
+    let authors: Vec<&str> = result
+        .commit_activity
+        .iter()
+        .map(|activity| activity.author.as_str())
+        .collect();
+    assert_eq!(
+        authors,
+        vec![
+            "Contributor 2",
+            "Contributor 1",
+            "Contributor 1",
+            "Contributor 3"
+        ]
+    );
+    assert_eq!(result.author_first_commit["Contributor 1"], "2023-01-02");
+    assert!(result.contributor_emails.is_empty());
+    assert!(result
+        .author_commit_frequency
+        .keys()
+        .chain(result.weekly_commits_by_author.keys())
+        .chain(result.contributor_add_delete_ratio.keys())
+        .all(|author| author.starts_with("Contributor ")));
+}
+
+/// Test that a linked worktree can be analyzed through its own checkout
+#[tokio::test]
+async fn test_analyze_worktree() {
+    let (_temp_dir, repo) = init_repo();
+    let base = commit_file(&repo, "Alice", JAN_1_2023, "a.txt", "1\n", "Base");
+    commit_file(&repo, "Alice", JAN_1_2023 + DAY, "a.txt", "2\n", "Main");
+
+    // Check out a feature branch from the first commit in a worktree beside the repo
+    let feature = repo
+        .branch("feature", &repo.find_commit(base).unwrap(), false)
+        .unwrap();
+    let worktree_dir = TempDir::new().unwrap();
+    let worktree_path = worktree_dir.path().join("feature");
+    let mut add_options = git2::WorktreeAddOptions::new();
+    add_options.reference(Some(feature.get()));
+    repo.worktree("feature", &worktree_path, Some(&add_options))
+        .unwrap();
+    assert!(worktree_path.join(".git").is_file());
+
+    let worktree = Repository::open(&worktree_path).unwrap();
+    commit_file(
+        &worktree,
+        "Bob",
+        JAN_1_2023 + 2 * DAY,
+        "b.txt",
+        "b\n",
+        "Feature",
+    );
+
+    let path = worktree_path.to_str().unwrap().to_string();
+    let result = analyze_repo_async(path.clone(), "feature".to_string(), "All".to_string(), None)
+        .await
+        .unwrap();
+    assert_eq!(result.commit_count, 2);
+    assert_eq!(
+        result.top_contributors,
+        vec![("Alice".to_string(), 1), ("Bob".to_string(), 1)]
+    );
+    assert!(result.available_branches.contains(&"feature".to_string()));
+
+    // The main checkout's branch is reachable through the worktree too
+    let main_branch = repo.head().unwrap().shorthand().unwrap().to_string();
+    let main = analyze_repo_async(path, main_branch, "All".to_string(), None)
+        .await
+        .unwrap();
+    assert_eq!(main.commit_count, 2);
+    assert_eq!(main.top_contributors, vec![("Alice".to_string(), 2)]);
+}
+
+/// Test that the commits made on a date can be looked up by their IDs
+#[tokio::test]
+async fn test_commits_on_date() {
+    let (temp_dir, repo) = init_repo();
+    commit_file(&repo, "Alice", JAN_1_2023, "a.txt", "1\n", "First");
+    let morning = commit_file(&repo, "Alice", JAN_1_2023 + DAY, "a.txt", "2\n", "Morning");
+    let evening = commit_file(
+        &repo,
+        "Bob",
+        JAN_1_2023 + DAY + 10 * 3600,
+        "b.txt",
+        "b\n",
+        "Evening",
+    );
+    commit_file(&repo, "Bob", JAN_1_2023 + 2 * DAY, "b.txt", "c\n", "Later");
+
+    let result = analyze_all(&temp_dir).await;
+
+    // Activity orders commits within a day by ID
+    let mut expected = vec![morning.to_string(), evening.to_string()];
+    expected.sort();
+    assert_eq!(result.commits_on("2023-01-02"), expected);
+    assert_eq!(result.commits_on("2023-01-01").len(), 1);
+    assert!(result.commits_on("2023-02-01").is_empty());
+}
+
+/// Test that uncommitted changes, staged or not, are counted against HEAD
+#[test]
+fn test_working_tree_stats() {
+    let (temp_dir, repo) = init_repo();
+    commit_file(&repo, "Alice", JAN_1_2023, "a.txt", "1\n2\n", "Add a");
+    let path = temp_dir.path().to_str().unwrap();
+    assert_eq!(working_tree_stats(path).unwrap(), (0, 0, 0));
+
+    // An unstaged edit, a staged new file and an untracked file
+    fs::write(temp_dir.path().join("a.txt"), "1\nx\ny\n").unwrap();
+    fs::write(temp_dir.path().join("c.txt"), "c\n").unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new("c.txt")).unwrap();
+    index.write().unwrap();
+    fs::write(temp_dir.path().join("untracked.txt"), "u\n").unwrap();
+
+    assert_eq!(working_tree_stats(path).unwrap(), (2, 3, 1));
+    assert!(working_tree_stats("/nonexistent/path").is_err());
+}
+
+/// Test that files over the size limit are counted like binaries and tallied
+#[tokio::test]
+async fn test_max_file_size() {
+    let (temp_dir, repo) = init_repo();
+    let bundle: String = (0..1000)
+        .map(|i| format!("var a{} = {};\n", i, i))
+        .collect();
+    commit_file(
+        &repo,
+        "Alice",
+        JAN_1_2023,
+        "bundle.min.js",
+        &bundle,
+        "Add bundle",
+    );
+    commit_file(
+        &repo,
+        "Alice",
+        JAN_1_2023 + DAY,
+        "small.txt",
+        "1\n2\n",
+        "Add small",
+    );
+    let analyze = |max_file_size_bytes: Option<u64>| {
+        let options = AnalysisOptions {
+            max_file_size_bytes,
+            ..Default::default()
+        };
+        analyze_repo_with_options(
+            temp_dir.path().to_str().unwrap().to_string(),
+            "main".to_string(),
+            "All".to_string(),
+            options,
+            None,
+        )
+    };
+
+    let full = analyze(None).await.unwrap();
+    assert_eq!(full.total_lines_added, 1002);
+    assert_eq!(full.large_files_skipped, 0);
+
+    let limited = analyze(Some(1024)).await.unwrap();
+    assert_eq!(limited.commit_count, 2);
+    assert_eq!(limited.total_lines_added, 2);
+    assert_eq!(limited.large_files_skipped, 1);
+}
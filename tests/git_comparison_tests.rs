@@ -1,4 +1,5 @@
-use gitstats::analysis::analyze_repo_async;
+use gitstats::analysis::{analyze_repo_async, analyze_repo_with_options};
+use gitstats::types::{AccuracyMode, AnalysisOptions};
 use std::env;
 use std::io;
 use std::path::Path;
@@ -21,7 +22,10 @@ fn get_git_commit_count(repo_path: &Path) -> usize {
 }
 
 fn get_git_line_stats(repo_path: &Path) -> (usize, usize) {
-    let output = run_git_command(repo_path, &["log", "--numstat"]);
+    let output = run_git_command(
+        repo_path,
+        &["log", "--numstat", "--diff-merges=first-parent"],
+    );
     let mut added = 0;
     let mut deleted = 0;
 
@@ -86,8 +90,24 @@ async fn test_commit_count_accuracy() {
     );
 }
 
+/// Analyze a repository in numstat accuracy mode
+async fn analyze_numstat(repo_path: &Path) -> gitstats::AnalysisResult {
+    let options = AnalysisOptions {
+        accuracy: AccuracyMode::GitNumstat,
+        ..Default::default()
+    };
+    analyze_repo_with_options(
+        repo_path.to_str().unwrap().to_string(),
+        "main".to_string(),
+        "All".to_string(),
+        options,
+        None,
+    )
+    .await
+    .unwrap()
+}
+
 #[tokio::test]
-#[ignore]
 async fn test_line_stats_accuracy() {
     let temp_dir = get_temp_dir().expect("Failed to create temporary directory");
     Command::new("git")
@@ -102,14 +122,7 @@ async fn test_line_stats_accuracy() {
     let (git_added, git_deleted) = get_git_line_stats(repo_path);
 
     // Get our stats
-    let result = analyze_repo_async(
-        repo_path.to_str().unwrap().to_string(),
-        "main".to_string(),
-        "All".to_string(),
-        None,
-    )
-    .await
-    .unwrap();
+    let result = analyze_numstat(repo_path).await;
 
     assert_eq!(
         git_added, result.total_lines_added,
@@ -123,3 +136,61 @@ async fn test_line_stats_accuracy() {
         git_deleted, result.total_lines_deleted
     );
 }
+
+#[tokio::test]
+async fn test_line_stats_accuracy_with_merge() {
+    let temp_dir = get_temp_dir().expect("Failed to create temporary directory");
+    let repo_path = temp_dir.path();
+    let commit = |message: &str| {
+        run_git_command(
+            repo_path,
+            &[
+                "-c",
+                "user.name=Test User",
+                "-c",
+                "user.email=test@example.com",
+                "commit",
+                "-q",
+                "-am",
+                message,
+            ],
+        );
+    };
+
+    run_git_command(repo_path, &["init", "-q", "-b", "main"]);
+    std::fs::write(repo_path.join("a.txt"), "one\ntwo\nthree\n").unwrap();
+    std::fs::write(repo_path.join("b.txt"), "alpha\n").unwrap();
+    run_git_command(repo_path, &["add", "."]);
+    commit("Initial commit");
+
+    run_git_command(repo_path, &["checkout", "-q", "-b", "feature"]);
+    std::fs::write(repo_path.join("b.txt"), "alpha\nbeta\ngamma\n").unwrap();
+    commit("Extend b");
+
+    run_git_command(repo_path, &["checkout", "-q", "main"]);
+    std::fs::write(repo_path.join("a.txt"), "one\n  two\nthree\nfour\n").unwrap();
+    commit("Edit a");
+    run_git_command(
+        repo_path,
+        &[
+            "-c",
+            "user.name=Test User",
+            "-c",
+            "user.email=test@example.com",
+            "merge",
+            "-q",
+            "--no-ff",
+            "--no-edit",
+            "feature",
+        ],
+    );
+
+    let (git_added, git_deleted) = get_git_line_stats(repo_path);
+    let result = analyze_numstat(repo_path).await;
+
+    assert_eq!(result.commit_count, 4);
+    assert_eq!(
+        (result.total_lines_added, result.total_lines_deleted),
+        (git_added, git_deleted)
+    );
+}
@@ -1,7 +1,10 @@
 /// Integration tests for the GitStats application.
 /// Tests the full workflow from repository analysis to plot generation.
 use git2::{Repository, Signature};
-use gitstats::app::App;
+use gitstats::app::{App, AppWrapper};
+use gitstats::plotting::chart::{generate_plot_with_source, PlotSource};
+use gitstats::plotting::{ChartStyle, ColorMode, LegendPosition};
+use gitstats::types::CommitActivity;
 use std::fs;
 use std::path::Path;
 use std::sync::{Arc, Mutex};
@@ -81,9 +84,9 @@ fn setup_test_app() -> App {
     let mut app = App::default();
     app.plot_path = "test_plot.png".to_string();
     app.commit_activity = vec![
-        ("2023-01-01".to_string(), 10, 5),
-        ("2023-01-02".to_string(), 15, 8),
-        ("2023-01-03".to_string(), 20, 10),
+        CommitActivity::new("2023-01-01", 10, 5, "Test User"),
+        CommitActivity::new("2023-01-02", 15, 8, "Test User"),
+        CommitActivity::new("2023-01-03", 20, 10, "Test User"),
     ];
     app
 }
@@ -153,9 +156,9 @@ async fn test_full_workflow() {
             .to_string();
         app.plot_path = plot_path.clone();
         app.commit_activity = vec![
-            ("2023-01-01".to_string(), 10, 5),
-            ("2023-01-02".to_string(), 15, 8),
-            ("2023-01-03".to_string(), 20, 10),
+            CommitActivity::new("2023-01-01", 10, 5, "Test User"),
+            CommitActivity::new("2023-01-02", 15, 8, "Test User"),
+            CommitActivity::new("2023-01-03", 20, 10, "Test User"),
         ];
 
         // Test different metrics
@@ -291,3 +294,675 @@ async fn test_plot_generation() {
     let app = setup_test_app();
     assert!(gitstats::plotting::generate_plot_async(app).await.is_ok());
 }
+
+/// Test that a repository without activity still renders a full image
+#[tokio::test]
+async fn test_empty_plot_generation() {
+    let temp_dir = TempDir::new().unwrap();
+    let app = App {
+        plot_path: temp_dir
+            .path()
+            .join("empty_plot.png")
+            .to_str()
+            .unwrap()
+            .to_string(),
+        ..Default::default()
+    };
+
+    let plot_data = gitstats::plotting::generate_plot_async(app).await.unwrap();
+
+    assert_eq!(plot_data.len(), 640 * 480 * 4);
+    // The "No data" message must have drawn something besides the background
+    let first_pixel = &plot_data[..4];
+    assert!(plot_data.chunks(4).any(|pixel| pixel != first_pixel));
+}
+
+/// Test that small commits are dropped from the plotted series but not from the totals
+#[tokio::test]
+async fn test_min_commit_size_filter() {
+    let mut app = setup_test_app();
+    app.commit_activity
+        .push(CommitActivity::new("2023-01-04", 1, 0, "Test User"));
+    app.commit_count = app.commit_activity.len();
+    app.total_lines_added = app.commit_activity.iter().map(|a| a.lines_added).sum();
+
+    assert_eq!(gitstats::plotting::chart::plot_points(&app).len(), 4);
+
+    app.min_commit_size = 20;
+    let points = gitstats::plotting::chart::plot_points(&app);
+    assert_eq!(points.len(), 2);
+    assert!(points
+        .iter()
+        .all(|(_, added, deleted)| added + deleted >= 20));
+
+    assert!(gitstats::plotting::generate_plot_async(app.clone())
+        .await
+        .is_ok());
+    assert_eq!(app.commit_count, 4);
+    assert_eq!(app.total_lines_added, 46);
+}
+
+/// Test that the category charts honour the minimum commit size too
+#[tokio::test]
+async fn test_min_commit_size_filters_category_charts() {
+    let mut app = setup_test_app();
+    for (activity, commit_type) in app.commit_activity.iter_mut().zip(["feat", "fix", "docs"]) {
+        activity.commit_type = Some(commit_type.to_string());
+    }
+
+    for metric in ["Commit Types", "Commits by Year", "Size Histogram"] {
+        let mut all = app.clone();
+        all.current_metric = metric.to_string();
+        let filtered = App {
+            min_commit_size: 25,
+            ..all.clone()
+        };
+
+        let all = gitstats::plotting::generate_plot_async(all).await.unwrap();
+        let filtered = gitstats::plotting::generate_plot_async(filtered)
+            .await
+            .unwrap();
+        assert_ne!(all, filtered, "{} ignores the minimum commit size", metric);
+    }
+}
+
+/// Test that the commit types chart renders
+#[tokio::test]
+async fn test_commit_types_plot_generation() {
+    let mut app = setup_test_app();
+    app.current_metric = "Commit Types".to_string();
+    app.commit_activity[0].commit_type = Some("feat".to_string());
+    app.commit_activity[1].commit_type = Some("fix".to_string());
+    assert!(gitstats::plotting::generate_plot_async(app).await.is_ok());
+}
+
+/// Test the commit size box plot against a known distribution
+#[tokio::test]
+async fn test_commit_size_distribution_plot() {
+    // Sizes 1, 2, ..., 9 lines changed
+    let sizes: Vec<usize> = (1..=9).collect();
+    assert_eq!(
+        gitstats::plotting::chart::commit_size_summary(&sizes),
+        Some([1.0, 3.0, 5.0, 7.0, 9.0])
+    );
+    assert_eq!(gitstats::plotting::chart::commit_size_summary(&[]), None);
+
+    let mut app = setup_test_app();
+    app.current_metric = "Commit Size Distribution".to_string();
+    app.commit_activity = sizes
+        .iter()
+        .map(|&size| CommitActivity::new("2023-01-01", size, 0, "Test User"))
+        .collect();
+    assert!(gitstats::plotting::generate_plot_async(app).await.is_ok());
+}
+
+/// Test that the commits by year chart renders
+#[tokio::test]
+async fn test_commits_by_year_plot_generation() {
+    let mut app = setup_test_app();
+    app.current_metric = "Commits by Year".to_string();
+    app.commit_activity
+        .push(CommitActivity::new("2022-06-01", 5, 1, "Test User"));
+    assert!(gitstats::plotting::generate_plot_async(app).await.is_ok());
+}
+
+/// Test that the commit size histogram renders
+#[tokio::test]
+async fn test_size_histogram_plot_generation() {
+    let mut app = setup_test_app();
+    app.current_metric = "Size Histogram".to_string();
+    app.commit_activity
+        .push(CommitActivity::new("2022-06-01", 1500, 20, "Test User"));
+    assert!(gitstats::plotting::generate_plot_async(app).await.is_ok());
+}
+
+/// Test that lines added in two languages render as a stacked churn chart
+#[tokio::test]
+async fn test_language_churn_plot_generation() {
+    let mut app = setup_test_app();
+    app.current_metric = "Language Churn".to_string();
+    let mut activity = CommitActivity::new("2022-06-01", 30, 5, "Test User");
+    activity
+        .lines_by_extension
+        .insert("rs".to_string(), (20, 5));
+    activity
+        .lines_by_extension
+        .insert("md".to_string(), (10, 0));
+    app.commit_activity = vec![activity];
+    assert!(gitstats::plotting::generate_plot_async(app).await.is_ok());
+}
+
+/// Test that concurrent plots render to their own scratch files and remove them
+#[tokio::test]
+async fn test_concurrent_plots_clean_up_scratch_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut app = setup_test_app();
+    app.plot_path = temp_dir
+        .path()
+        .join("scratch.png")
+        .to_str()
+        .unwrap()
+        .to_string();
+    // A ratio no other test uses, so neither plot comes from the cache
+    app.pixels_per_point = 1.5;
+    let mut other = app.clone();
+    other.current_metric = "Code Changes".to_string();
+
+    let (first, second) = tokio::join!(
+        generate_plot_with_source(app),
+        generate_plot_with_source(other)
+    );
+    assert_eq!(first.unwrap().1, PlotSource::Rendered);
+    assert_eq!(second.unwrap().1, PlotSource::Rendered);
+    assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 0);
+}
+
+/// Test that rendering at a device pixel ratio of 2 doubles both dimensions
+#[tokio::test]
+async fn test_high_dpi_plot_generation() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut app = setup_test_app();
+    app.plot_path = temp_dir
+        .path()
+        .join("retina_plot.png")
+        .to_str()
+        .unwrap()
+        .to_string();
+    app.pixels_per_point = 2.0;
+
+    assert_eq!(gitstats::plotting::chart::plot_dimensions(2.0), (1280, 960));
+    let plot_data = gitstats::plotting::generate_plot_async(app).await.unwrap();
+    assert_eq!(plot_data.len(), 4 * 640 * 480 * 4);
+}
+
+/// Test that a supersampled plot is scaled back down to the display size
+#[tokio::test]
+async fn test_supersampled_plot_generation() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut app = setup_test_app();
+    app.plot_path = temp_dir
+        .path()
+        .join("supersampled_plot.png")
+        .to_str()
+        .unwrap()
+        .to_string();
+    app.supersample = 2;
+
+    let plot_data = gitstats::plotting::generate_plot_async(app).await.unwrap();
+    let (width, height) = gitstats::plotting::chart::PLOT_SIZE;
+    assert_eq!(plot_data.len(), (width * height * 4) as usize);
+}
+
+/// Test that a grayscale plot has no colored pixels
+#[tokio::test]
+async fn test_grayscale_plot_generation() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut app = setup_test_app();
+    app.plot_path = temp_dir
+        .path()
+        .join("grayscale_plot.png")
+        .to_str()
+        .unwrap()
+        .to_string();
+    app.color_mode = ColorMode::Grayscale;
+
+    let plot_data = gitstats::plotting::generate_plot_async(app).await.unwrap();
+    for pixel in plot_data.chunks_exact(4).step_by(97) {
+        assert_eq!(pixel[0], pixel[1]);
+        assert_eq!(pixel[1], pixel[2]);
+    }
+}
+
+/// Test that a year of activity renders as a contribution calendar
+#[tokio::test]
+async fn test_contribution_calendar_plot() {
+    let temp_dir = TempDir::new().unwrap();
+    let start = chrono::NaiveDate::from_ymd_opt(2023, 1, 1).unwrap();
+    let app = App {
+        plot_path: temp_dir
+            .path()
+            .join("calendar.png")
+            .to_str()
+            .unwrap()
+            .to_string(),
+        current_metric: "Calendar".to_string(),
+        commit_activity: (0..365)
+            .filter(|day| day % 3 != 0)
+            .map(|day| {
+                let date = start + chrono::Duration::days(day);
+                CommitActivity::new(&date.to_string(), (day % 40) as usize, 1, "Test User")
+            })
+            .collect(),
+        ..Default::default()
+    };
+
+    let plot_data = gitstats::plotting::generate_plot_async(app).await.unwrap();
+    let (width, height) = gitstats::plotting::chart::PLOT_SIZE;
+    assert_eq!(plot_data.len(), (width * height * 4) as usize);
+}
+
+/// Test that the average commit size chart normalizes each day's churn by its commit count
+#[tokio::test]
+async fn test_avg_commit_size_plot_generation() {
+    let points = vec![
+        ("2023-01-01".to_string(), 10, 2),
+        ("2023-01-02".to_string(), 3, 1),
+        ("2023-01-02".to_string(), 5, 3),
+        ("2023-01-02".to_string(), 1, 5),
+        ("2023-01-01".to_string(), 6, 0),
+    ];
+    assert_eq!(
+        gitstats::plotting::chart::daily_avg_commit_size(&points),
+        vec![
+            ("2023-01-01".to_string(), 9.0),
+            ("2023-01-02".to_string(), 6.0)
+        ]
+    );
+
+    let mut app = setup_test_app();
+    app.current_metric = "Avg Commit Size".to_string();
+    app.commit_activity = points
+        .iter()
+        .map(|(date, added, deleted)| CommitActivity::new(date, *added, *deleted, "Test User"))
+        .collect();
+    assert!(gitstats::plotting::generate_plot_async(app).await.is_ok());
+}
+
+/// Test that the stacked area chart of additions and deletions renders
+#[tokio::test]
+async fn test_code_area_plot_generation() {
+    let mut app = setup_test_app();
+    app.current_metric = "Code Area".to_string();
+    assert_eq!(gitstats::plotting::chart::y_axis_unit("Code Area"), "lines");
+    assert!(gitstats::plotting::generate_plot_async(app).await.is_ok());
+}
+
+/// Test that the contributor comparison renders with its legend in the lower left
+#[tokio::test]
+async fn test_legend_position_plot_generation() {
+    let mut app = setup_test_app();
+    app.current_metric = "Contributor Comparison".to_string();
+    app.legend_position = LegendPosition::LowerLeft;
+    assert!(gitstats::plotting::generate_plot_async(app).await.is_ok());
+}
+
+/// Test that charts render with a custom font family and size
+#[tokio::test]
+async fn test_custom_chart_style_plot_generation() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut app = setup_test_app();
+    app.plot_path = temp_dir
+        .path()
+        .join("styled.png")
+        .to_str()
+        .unwrap()
+        .to_string();
+    app.chart_style = ChartStyle {
+        font_size: 22,
+        font_family: "serif".to_string(),
+        ..ChartStyle::default()
+    };
+    assert_eq!(app.chart_style.caption_font(1.0).get_size(), 44.0);
+
+    for metric in ["Commits", "Commit Types", "Avg Commit Size"] {
+        app.current_metric = metric.to_string();
+        let plot_data = gitstats::plotting::generate_plot_async(app.clone())
+            .await
+            .unwrap();
+        let (width, height) = gitstats::plotting::chart::PLOT_SIZE;
+        assert_eq!(plot_data.len(), (width * height * 4) as usize);
+    }
+}
+
+/// Test that reset clears analysis state but keeps display preferences
+#[tokio::test]
+async fn test_app_reset() {
+    let (temp_dir, _repo) = setup_test_repo();
+    let mut app = App {
+        repo_path: temp_dir.path().to_str().unwrap().to_string(),
+        use_log_scale: true,
+        current_metric: "Code Changes".to_string(),
+        ..Default::default()
+    };
+
+    let (_rx, analysis) = app.analyze_repo();
+    let result = analysis.await.unwrap();
+    app.update_with_result(result);
+    assert!(app.commit_count > 0);
+    assert!(!app.commit_activity.is_empty());
+    assert!(!app.analysis_cache.is_empty());
+
+    app.reset();
+
+    assert_eq!(app.commit_count, 0);
+    assert_eq!(app.total_lines_added, 0);
+    assert!(app.commit_activity.is_empty());
+    assert!(app.available_branches.is_empty());
+    assert!(app.analysis_cache.is_empty());
+    assert!(app.analysis_result.is_none());
+    assert!(app.use_log_scale);
+    assert_eq!(app.current_metric, "Code Changes");
+    assert_eq!(app.repo_path, temp_dir.path().to_str().unwrap());
+}
+
+/// Test that reset aborts a running analysis instead of leaving it to repopulate the app
+#[tokio::test]
+async fn test_app_reset_cancels_analysis() {
+    let mut app = App::default();
+    let task = tokio::spawn(std::future::pending::<()>());
+    app.analysis_task = Some(task.abort_handle());
+    app.is_analyzing = true;
+
+    app.reset();
+
+    assert!(!app.is_analyzing);
+    assert!(app.analysis_task.is_none());
+    assert!(task.await.unwrap_err().is_cancelled());
+}
+
+/// Test that background tasks stay tracked across a reset, so exit can wait for them
+#[tokio::test]
+async fn test_app_reset_keeps_tasks() {
+    let mut app = App::default();
+    let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+    app.tasks.spawn(async move {
+        let _ = rx.await;
+    });
+
+    app.reset();
+    assert_eq!(app.tasks.len(), 1);
+
+    app.tasks.close();
+    tx.send(()).unwrap();
+    tokio::time::timeout(std::time::Duration::from_secs(5), app.tasks.wait())
+        .await
+        .expect("tracked task should finish");
+}
+
+/// Test that the cached-selection check follows branch and contributor changes
+#[test]
+fn test_is_current_selection_cached() {
+    let mut app = App::default();
+    let key = |branch: &str, contributor: &str| gitstats::types::CacheKey {
+        branch: branch.to_string(),
+        contributor: contributor.to_string(),
+    };
+    app.analysis_cache
+        .store(key("main", "Alice"), gitstats::AnalysisResult::default());
+
+    assert!(!app.is_current_selection_cached());
+    app.selected_contributor = "Alice".to_string();
+    assert!(app.is_current_selection_cached());
+    app.selected_branch = "dev".to_string();
+    assert!(!app.is_current_selection_cached());
+
+    // Any contributor can be derived from the branch's full analysis, unless it was
+    // sampled
+    app.selected_contributor = "Bob".to_string();
+    app.analysis_cache.store(
+        key("dev", "All"),
+        gitstats::AnalysisResult {
+            approximate: true,
+            ..Default::default()
+        },
+    );
+    assert!(!app.is_current_selection_cached());
+    app.analysis_cache
+        .store(key("dev", "All"), gitstats::AnalysisResult::default());
+    assert!(app.is_current_selection_cached());
+    assert_eq!(app.analysis_cache.stats(), (0, 0));
+}
+
+/// Test that cancelling an analysis aborts its task and resets the analyzing state
+#[tokio::test]
+async fn test_cancel_analysis() {
+    let mut app = App::default();
+    let task = tokio::spawn(std::future::pending::<()>());
+    app.analysis_task = Some(task.abort_handle());
+    app.is_analyzing = true;
+
+    app.cancel_analysis();
+
+    assert!(!app.is_analyzing);
+    assert!(app.progress.is_none());
+    assert!(app.analysis_task.is_none());
+    assert!(task.await.unwrap_err().is_cancelled());
+}
+
+/// Test that a plot finishing after a newer request is recognised as stale
+#[test]
+fn test_out_of_order_plot_completion() {
+    let mut app = App {
+        current_metric: "Code Changes".to_string(),
+        ..Default::default()
+    };
+    let code_changes = app.next_plot_generation();
+    app.current_metric = "Commits".to_string();
+    let commits = app.next_plot_generation();
+
+    // The "Commits" plot finishes first and is applied; the slower "Code Changes"
+    // plot arrives afterwards and must be dropped
+    assert!(app.is_current_plot(commits));
+    assert!(!app.is_current_plot(code_changes));
+}
+
+/// Test the chart status shown while plotting and once a plot has arrived
+#[tokio::test]
+async fn test_plot_status() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut app = setup_test_app();
+    app.plot_path = temp_dir
+        .path()
+        .join("status.png")
+        .to_str()
+        .unwrap()
+        .to_string();
+    // The plot cache is shared, so use a setting no other test renders with
+    app.min_commit_size = 17;
+    assert_eq!(app.plot_status(), None);
+
+    app.is_plotting = true;
+    assert_eq!(app.plot_status(), Some("Rendering chart..."));
+
+    app.current_metric = "Commits by Year".to_string();
+    let (_, first) = generate_plot_with_source(app.clone()).await.unwrap();
+    let (_, second) = generate_plot_with_source(app.clone()).await.unwrap();
+    assert_eq!(first, PlotSource::Rendered);
+    assert_eq!(second, PlotSource::Cache);
+
+    app.is_plotting = false;
+    app.last_plot_source = Some(second);
+    assert_eq!(app.plot_status(), Some("Chart loaded from cache"));
+    app.last_plot_source = Some(first);
+    assert_eq!(app.plot_status(), Some("Chart rendered"));
+}
+
+/// Test that the app can be embedded in a runtime the caller already runs, and that
+/// its runtime is usable from threads outside it
+#[tokio::test(flavor = "multi_thread")]
+async fn test_app_wrapper_existing_runtime() {
+    let wrapper = AppWrapper::new(Arc::new(Mutex::new(App::default())));
+    let runtime = wrapper.runtime.clone();
+
+    let spawned = std::thread::spawn(move || {
+        let _runtime = runtime.enter();
+        tokio::spawn(async { 42 })
+    })
+    .join()
+    .unwrap();
+
+    assert_eq!(spawned.await.unwrap(), 42);
+}
+
+/// Test that the scratch plot file is kept in the system temp directory
+#[test]
+fn test_default_plot_path_in_temp_dir() {
+    let app = App::default();
+    assert!(Path::new(&app.plot_path).starts_with(std::env::temp_dir()));
+}
+
+/// Test that charts can be saved as PNG and SVG, chosen by file extension
+#[test]
+fn test_save_plot_as() {
+    let temp_dir = TempDir::new().unwrap();
+    let app = setup_test_app();
+
+    let png_path = temp_dir.path().join("chart.png");
+    app.save_plot_as(&png_path).unwrap();
+    let png = fs::read(&png_path).unwrap();
+    assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+
+    let svg_path = temp_dir.path().join("chart.svg");
+    app.save_plot_as(&svg_path).unwrap();
+    let svg = fs::read_to_string(&svg_path).unwrap();
+    assert!(svg.starts_with("<svg"));
+
+    assert!(app
+        .save_plot_as(&temp_dir.path().join("chart.bmp"))
+        .is_err());
+}
+
+/// Test that the contributor comparison chart draws one labelled line per author
+#[test]
+fn test_contributor_comparison_plot() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut app = setup_test_app();
+    app.current_metric = "Contributor Comparison".to_string();
+    app.commit_activity = vec![
+        CommitActivity::new("2023-01-02", 10, 5, "Alice"),
+        CommitActivity::new("2023-01-03", 10, 5, "Alice"),
+        CommitActivity::new("2023-01-10", 15, 8, "Alice"),
+        CommitActivity::new("2023-01-04", 20, 10, "Bob"),
+        CommitActivity::new("2023-01-17", 20, 10, "Bob"),
+    ];
+
+    let svg_path = temp_dir.path().join("comparison.svg");
+    app.save_plot_as(&svg_path).unwrap();
+    let svg = fs::read_to_string(&svg_path).unwrap();
+
+    // Each author gets a line in their own color and a legend entry with their name
+    let colors = gitstats::plotting::contributor_colors(&["Alice".to_string(), "Bob".to_string()]);
+    for name in ["Alice", "Bob"] {
+        let color = colors[name];
+        let stroke = format!("stroke=\"#{:02X}{:02X}{:02X}\"", color.0, color.1, color.2);
+        assert!(svg.contains(&stroke), "no line drawn for {}", name);
+        assert!(
+            svg.lines().any(|line| line.trim() == name),
+            "no label for {}",
+            name
+        );
+    }
+}
+
+/// Test that `gitstats chart` writes a PNG without starting the GUI
+#[test]
+fn test_headless_chart_command() {
+    let (temp_dir, _repo) = setup_test_repo();
+    let out_path = temp_dir.path().join("chart.png");
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_gitstats"))
+        .args(["chart", "--path"])
+        .arg(temp_dir.path())
+        .args(["--metric", "code-changes", "--out"])
+        .arg(&out_path)
+        .output()
+        .unwrap();
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let png = fs::read(&out_path).unwrap();
+    assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+}
+
+/// Test that `--progress-json` prints one parseable JSON progress event per line
+#[test]
+fn test_headless_chart_progress_json() {
+    let (temp_dir, _repo) = setup_test_repo();
+    let out_path = temp_dir.path().join("chart.png");
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_gitstats"))
+        .args(["chart", "--path"])
+        .arg(temp_dir.path())
+        .arg("--out")
+        .arg(&out_path)
+        .arg("--progress-json")
+        .output()
+        .unwrap();
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{}", stderr);
+    let events: Vec<serde_json::Value> = stderr
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert!(!events.is_empty());
+    let last = events.last().unwrap();
+    assert_eq!(last["processed"], last["total"]);
+    assert_eq!(last["total"], 4);
+    assert_eq!(last["pct"], 100.0);
+}
+
+/// Test that `--cache-file` reuses the previous run's result for an unchanged branch
+/// and analyzes only new commits once the branch moves
+#[test]
+fn test_headless_chart_cache_file() {
+    let (temp_dir, repo) = setup_test_repo();
+    let cache_path = temp_dir.path().join("cache.json");
+    let run_chart = || {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_gitstats"))
+            .args(["chart", "--path"])
+            .arg(temp_dir.path())
+            .arg("--out")
+            .arg(temp_dir.path().join("chart.png"))
+            .arg("--cache-file")
+            .arg(&cache_path)
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+
+    assert!(!run_chart().contains("Cache hit"));
+    assert!(cache_path.exists());
+    assert!(run_chart().contains("Cache hit"));
+
+    let signature = Signature::now("Test User", "test@example.com").unwrap();
+    let tree = repo.head().unwrap().peel_to_tree().unwrap();
+    let parent = repo.head().unwrap().peel_to_commit().unwrap();
+    repo.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        "More",
+        &tree,
+        &[&parent],
+    )
+    .unwrap();
+
+    let stdout = run_chart();
+    assert!(!stdout.contains("Cache hit"));
+    assert!(
+        stdout.contains("Cache updated with 1 new commits"),
+        "{}",
+        stdout
+    );
+}
+
+/// Test that an unknown metric is rejected before any analysis runs
+#[test]
+fn test_headless_chart_unknown_metric() {
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_gitstats"))
+        .args(["chart", "--metric", "bogus"])
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Unknown metric"));
+}